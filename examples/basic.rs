@@ -1,5 +1,4 @@
-use grapher::renderer::Renderer;
-use grapher::simulator::SimulatorBuilder;
+use grapher::prelude::*;
 use petgraph::Directed;
 
 fn main() {
@@ -0,0 +1,104 @@
+//! Headless layout of a generated graph that prints phase timings and how well the layout
+//! reproduces the graph distances (`analysis::distance_correlation`) as JSON.
+//!
+//! ```text
+//! cargo run --release --example bench_large -- --nodes 50000 --density 2 --steps 200
+//! ```
+//!
+//! Options (all optional):
+//! - `--model ba|gnm`: Barabási–Albert or uniform random graph. Default: `ba`
+//! - `--nodes N`: Number of nodes. Default: `10000`
+//! - `--density D`: Edges per node. Default: `1`
+//! - `--steps S`: Number of simulation steps. Default: `100`
+//! - `--threads T`: Maximum number of simulation threads. Default: `8`
+//! - `--seed X`: Seed for the graph and the initial layout. Default: `0`
+
+use std::{collections::HashMap, time::Instant};
+
+use grapher::analysis;
+use grapher::prelude::*;
+use petgraph::{prelude::StableGraph, Directed, Graph};
+use rand::{rngs::StdRng, SeedableRng};
+use serde_json::json;
+
+fn main() {
+    let args = parse_args();
+    let arg = |name: &str, default: u64| {
+        args.get(name).map_or(default, |v| {
+            v.parse()
+                .unwrap_or_else(|_| panic!("--{name} expects a number, got {v}"))
+        })
+    };
+
+    let model = args.get("model").map_or("ba", String::as_str);
+    let nodes = arg("nodes", 10000) as usize;
+    let density = arg("density", 1) as usize;
+    let steps = arg("steps", 100) as usize;
+    let threads = arg("threads", 8) as u32;
+    let seed = arg("seed", 0);
+
+    let generate_start = Instant::now();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let graph: Graph<(), (), Directed> = match model {
+        "ba" => petgraph_gen::barabasi_albert_graph(&mut rng, nodes, density.max(1), None),
+        "gnm" => petgraph_gen::random_gnm_graph(&mut rng, nodes, nodes * density),
+        _ => panic!("unknown --model {model}, expected ba or gnm"),
+    };
+    let edges = graph.edge_count();
+    let generate = generate_start.elapsed();
+    let unit_graph: StableGraph<(), f32> = graph.map(|_, _| (), |_, _| 1.0).into();
+
+    let build_start = Instant::now();
+    let simulator = SimulatorBuilder::new()
+        .seed(seed)
+        .max_threads(threads)
+        .freeze_threshold(-1.0)
+        .build(graph.into());
+    let build = build_start.elapsed();
+
+    let mut stats = StepStats::default();
+    for _ in 0..steps {
+        simulator.simulation_step();
+        stats += simulator.step_stats();
+    }
+
+    let distance_correlation = analysis::distance_correlation(&simulator.positions(), &unit_graph);
+
+    let ms = |d: std::time::Duration| d.as_secs_f64() * 1000.0;
+    let report = json!({
+        "model": model,
+        "nodes": nodes,
+        "edges": edges,
+        "steps": steps,
+        "threads": threads,
+        "seed": seed,
+        "distance_correlation": distance_correlation,
+        "timings_ms": {
+            "generate": ms(generate),
+            "build": ms(build),
+            "quadtree": ms(stats.quadtree),
+            "node_forces": ms(stats.node_forces),
+            "spring_forces": ms(stats.spring_forces),
+            "integration": ms(stats.integration),
+            "steps_total": ms(stats.total),
+            "step_average": ms(stats.total) / steps.max(1) as f64,
+        },
+    });
+    println!("{report}");
+}
+
+/// Parses `--name value` pairs
+fn parse_args() -> HashMap<String, String> {
+    let mut args = std::env::args().skip(1);
+    let mut parsed = HashMap::new();
+    while let Some(name) = args.next() {
+        let Some(name) = name.strip_prefix("--") else {
+            panic!("unexpected argument {name}");
+        };
+        let value = args
+            .next()
+            .unwrap_or_else(|| panic!("missing value for --{name}"));
+        parsed.insert(name.to_string(), value);
+    }
+    parsed
+}
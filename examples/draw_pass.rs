@@ -0,0 +1,83 @@
+use glium::{implement_vertex, uniform, Surface};
+use grapher::prelude::*;
+use grapher::renderer::{DrawContext, DrawPass, Layer};
+use petgraph::Directed;
+
+#[derive(Copy, Clone)]
+struct Vertex {
+    position: [f32; 3],
+}
+implement_vertex!(Vertex, position);
+
+/// Outlines the bounding box of the graph
+struct BoundingBox;
+
+impl DrawPass for BoundingBox {
+    fn draw(&mut self, context: &mut DrawContext<'_>) {
+        let positions = context.snapshot.rigid_bodies.iter().map(|rb| rb.position);
+        let (min, max) = positions.fold(
+            (glam::Vec2::INFINITY, glam::Vec2::NEG_INFINITY),
+            |(min, max), p| (min.min(p), max.max(p)),
+        );
+        if min.x > max.x {
+            return;
+        }
+
+        // Behind the nodes, in front of the edges
+        let z = (context.layer_z(Layer::Edges) + context.layer_z(Layer::Nodes)) / 2.0;
+        let corners = [
+            [min.x, min.y],
+            [max.x, min.y],
+            [max.x, max.y],
+            [min.x, max.y],
+        ];
+        let vertices: Vec<Vertex> = corners
+            .iter()
+            .map(|[x, y]| Vertex {
+                position: [*x, *y, z],
+            })
+            .collect();
+
+        let program = glium::Program::from_source(
+            context.display,
+            "#version 150
+            in vec3 position;
+            uniform mat4 view;
+            uniform mat4 projection;
+            void main() { gl_Position = projection * view * vec4(position, 1.0); }",
+            "#version 140
+            out vec4 color;
+            void main() { color = vec4(0.2, 0.6, 1.0, 1.0); }",
+            None,
+        )
+        .unwrap();
+        let vertex_buffer = glium::VertexBuffer::new(context.display, &vertices).unwrap();
+        let uniforms = uniform! {
+            view: context.view.to_cols_array_2d(),
+            projection: context.projection.to_cols_array_2d(),
+        };
+        context
+            .frame
+            .draw(
+                &vertex_buffer,
+                glium::index::NoIndices(glium::index::PrimitiveType::LineLoop),
+                &program,
+                &uniforms,
+                context.parameters,
+            )
+            .unwrap();
+    }
+}
+
+fn main() {
+    let mut rng = rand::thread_rng();
+    let graph: petgraph::Graph<(), (), Directed> =
+        petgraph_gen::barabasi_albert_graph(&mut rng, 500, 1, None);
+
+    let simulator = SimulatorBuilder::new().build(graph.into());
+    simulator.resume();
+
+    let mut renderer = Renderer::new(simulator);
+    renderer.add_draw_pass(Box::new(BoundingBox));
+    renderer.create_window();
+}
@@ -1,4 +1,4 @@
-use grapher::simulator::SimulatorBuilder;
+use grapher::prelude::*;
 use petgraph::Directed;
 
 fn main() {
@@ -14,7 +14,5 @@ fn main() {
         .build(graph.into());
 
     // Run 10k simulation steps
-    for _ in 0..10000 {
-        simulator.simulation_step();
-    }
+    simulator.step_n(10000);
 }
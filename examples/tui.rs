@@ -0,0 +1,18 @@
+use grapher::prelude::*;
+use petgraph::Directed;
+
+fn main() {
+    // Build a PetGraph
+    let mut rng = rand::thread_rng();
+    let graph: petgraph::Graph<(), (), Directed> =
+        petgraph_gen::barabasi_albert_graph(&mut rng, 300, 1, None);
+
+    // Configure the simulator
+    let simulator = SimulatorBuilder::new()
+        .delta_time(0.01)
+        .freeze_threshold(-1.0)
+        .build(graph.into());
+
+    // Preview the layout in a 80x24 terminal
+    grapher::renderer_tui::run(&simulator, 80, 24);
+}
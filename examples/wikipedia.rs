@@ -1,5 +1,5 @@
-use grapher::renderer::Renderer;
-use grapher::simulator::SimulatorBuilder;
+use grapher::prelude::*;
+use petgraph::prelude::StableGraph;
 use petgraph::Directed;
 use petgraph::Graph;
 use serde::Deserialize;
@@ -30,14 +30,23 @@ fn main() {
     let mut g: Graph<Data, u32> = Graph::new();
     graph_wiki(&mut g);
 
+    // Report and remove self-loops and duplicate edges
+    let mut g: StableGraph<Data, u32> = g.into();
+    let report = io::validate(&g);
+    print!("{}", report);
+    io::fix(&mut g, &report);
+
+    let labels = g.node_weights().map(|data| data.name.clone()).collect();
+
     // Configure the Simulator
     let simulator = SimulatorBuilder::new()
         .delta_time(0.01)
         .freeze_threshold(-1.0)
-        .build(g.into());
+        .build(g);
 
     // Render Graph
-    let renderer = Renderer::new(simulator);
+    let mut renderer = Renderer::new(simulator);
+    renderer.set_labels(labels);
     renderer.create_window();
 }
 
@@ -51,25 +60,23 @@ fn graph_wiki(g: &mut Graph<Data, u32, Directed, u32>) {
             }
             let node_data = Data::new(e.title);
 
-            let mut _index;
-            let opt = hashmap.get(&node_data);
-            if opt.is_none() {
-                _index = g.add_node(node_data.clone());
-                hashmap.insert(node_data, _index);
+            let _index = if let Some(index) = hashmap.get(&node_data) {
+                *index
             } else {
-                _index = *opt.unwrap();
-            }
+                let index = g.add_node(node_data.clone());
+                hashmap.insert(node_data, index);
+                index
+            };
 
             for r in e.references {
                 let ref_data = Data::new(r);
-                let opt_ref = hashmap.get(&ref_data);
-                let mut _index_ref;
-                if opt_ref.is_none() {
-                    _index_ref = g.add_node(ref_data.clone());
-                    hashmap.insert(ref_data, _index);
+                let _index_ref = if let Some(index) = hashmap.get(&ref_data) {
+                    *index
                 } else {
-                    _index_ref = *opt_ref.unwrap();
-                }
+                    let index = g.add_node(ref_data.clone());
+                    hashmap.insert(ref_data, _index);
+                    index
+                };
                 g.add_edge(_index, _index_ref, 1);
             }
         }
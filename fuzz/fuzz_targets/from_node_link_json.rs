@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// Malformed input has to return an error, never panic
+fuzz_target!(|input: &str| {
+    let _ = grapher::io::from_node_link_json(input);
+});
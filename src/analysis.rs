@@ -0,0 +1,690 @@
+//! Graph analysis used to style the rendered graph.
+
+use std::collections::{HashMap, VecDeque};
+
+use glam::Vec2;
+use petgraph::{
+    algo,
+    graph::NodeIndex,
+    prelude::{Graph, StableGraph},
+    visit::{EdgeRef, IntoEdgeReferences, NodeIndexable},
+    EdgeType, Undirected,
+};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::simulator::{remap, GraphChange, Simulator};
+
+const MAX_ITERATIONS: usize = 100;
+const PAGERANK_DAMPING: f32 = 0.85;
+const PAGERANK_EPSILON: f32 = 1e-6;
+const STRESS_SOURCES: usize = 64;
+
+/// Finds communities with label propagation, ignoring edge directions.
+///
+/// Returns the community of every node by node index, numbered from `0`.
+/// The result is the same for the same graph.
+pub fn label_propagation<T, E, D>(graph: &StableGraph<T, E, D, u32>) -> Vec<u32>
+where
+    D: EdgeType,
+{
+    let edges: Vec<(usize, usize)> = graph
+        .edge_references()
+        .map(|e| (e.source().index(), e.target().index()))
+        .collect();
+    propagate(graph.node_bound(), &edges)
+}
+
+/// Finds communities among the nodes of `simulator` with label propagation over its springs.
+///
+/// Returns the community of every rigid body, numbered from `0`.
+pub fn communities(simulator: &Simulator) -> Vec<u32> {
+    let (node_count, edges) = simulator_edges(simulator);
+    propagate(node_count, &edges)
+}
+
+/// Number of springs attached to every rigid body, scaled to `0..=1`
+pub fn degree_centrality(simulator: &Simulator) -> Vec<f32> {
+    let (node_count, edges) = simulator_edges(simulator);
+    let mut degree = vec![0.0; node_count];
+    for (a, b) in edges {
+        degree[a] += 1.0;
+        degree[b] += 1.0;
+    }
+    normalize(degree)
+}
+
+/// PageRank of every rigid body with springs pointing from `rb1` to `rb2`, scaled to `0..=1`
+pub fn pagerank(simulator: &Simulator) -> Vec<f32> {
+    let (node_count, edges) = simulator_edges(simulator);
+    if node_count == 0 {
+        return vec![];
+    }
+
+    let rank = vec![1.0 / node_count as f32; node_count];
+    normalize(power_iteration(&edges, rank))
+}
+
+/// Betweenness centrality of every rigid body, ignoring spring directions, scaled to `0..=1`.
+///
+/// Takes `O(nodes * springs)` time.
+pub fn betweenness_centrality(simulator: &Simulator) -> Vec<f32> {
+    let (node_count, edges) = simulator_edges(simulator);
+    let neighbours = adjacency(node_count, &edges);
+    let mut centrality = vec![0.0; node_count];
+
+    // Brandes' algorithm
+    let mut stack = Vec::with_capacity(node_count);
+    let mut queue = VecDeque::new();
+    for source in 0..node_count {
+        let mut predecessors: Vec<Vec<usize>> = vec![vec![]; node_count];
+        let mut paths = vec![0.0f32; node_count];
+        let mut distance = vec![usize::MAX; node_count];
+        paths[source] = 1.0;
+        distance[source] = 0;
+        queue.push_back(source);
+
+        while let Some(v) = queue.pop_front() {
+            stack.push(v);
+            for &w in &neighbours[v] {
+                if distance[w] == usize::MAX {
+                    distance[w] = distance[v] + 1;
+                    queue.push_back(w);
+                }
+                if distance[w] == distance[v] + 1 {
+                    paths[w] += paths[v];
+                    predecessors[w].push(v);
+                }
+            }
+        }
+
+        let mut dependency = vec![0.0f32; node_count];
+        while let Some(w) = stack.pop() {
+            for &v in &predecessors[w] {
+                dependency[v] += paths[v] / paths[w] * (1.0 + dependency[w]);
+            }
+            if w != source {
+                centrality[w] += dependency[w];
+            }
+        }
+    }
+    normalize(centrality)
+}
+
+/// Connected component of every rigid body, ignoring spring directions, numbered from `0` in
+/// the order of their first node
+pub fn connected_components(simulator: &Simulator) -> Vec<u32> {
+    let (node_count, edges) = simulator_edges(simulator);
+    components(node_count, &edges)
+}
+
+pub(crate) fn components(node_count: usize, edges: &[(usize, usize)]) -> Vec<u32> {
+    let neighbours = adjacency(node_count, edges);
+    let mut component = vec![u32::MAX; node_count];
+    let mut count = 0;
+    let mut stack = vec![];
+    for start in 0..node_count {
+        if component[start] != u32::MAX {
+            continue;
+        }
+        component[start] = count;
+        stack.push(start);
+        while let Some(v) = stack.pop() {
+            for &w in &neighbours[v] {
+                if component[w] == u32::MAX {
+                    component[w] = count;
+                    stack.push(w);
+                }
+            }
+        }
+        count += 1;
+    }
+    component
+}
+
+/// Normalized stress of the layout, how badly the distances between nodes match the lengths of
+/// the shortest paths between them, in `0..=1` where `0` is a perfect match.
+///
+/// The layout is scaled to fit the path lengths best before comparing, so it doesn't depend on
+/// the spring length. Paths are counted from at most 64 sources spread over the nodes, so it
+/// takes `O(springs)` time.
+pub fn stress(simulator: &Simulator) -> f32 {
+    let (node_count, edges) = simulator_edges(simulator);
+    let neighbours = adjacency(node_count, &edges);
+    let positions = simulator.body_positions();
+    let source_step = node_count.div_ceil(STRESS_SOURCES).max(1);
+
+    // With the layout scaled by s the stress is sum((s * d - g)² / g²) over the pairs, which is
+    // smallest for s = sum(d / g) / sum(d² / g²)
+    let (mut pairs, mut distance_ratio, mut squared_ratio) = (0.0f64, 0.0f64, 0.0f64);
+    let mut queue = VecDeque::new();
+    for source in (0..node_count).step_by(source_step) {
+        let mut hops = vec![usize::MAX; node_count];
+        hops[source] = 0;
+        queue.push_back(source);
+        while let Some(v) = queue.pop_front() {
+            for &w in &neighbours[v] {
+                if hops[w] == usize::MAX {
+                    hops[w] = hops[v] + 1;
+                    queue.push_back(w);
+                }
+            }
+        }
+
+        for (target, &hops) in hops.iter().enumerate() {
+            if hops == 0 || hops == usize::MAX {
+                continue;
+            }
+            let ratio = (positions[source].distance(positions[target]) / hops as f32) as f64;
+            pairs += 1.0;
+            distance_ratio += ratio;
+            squared_ratio += ratio * ratio;
+        }
+    }
+
+    if pairs == 0.0 {
+        return 0.0;
+    }
+    if squared_ratio == 0.0 {
+        return 1.0;
+    }
+    (1.0 - distance_ratio * distance_ratio / (squared_ratio * pairs)) as f32
+}
+
+/// Standard deviation of the spring lengths divided by their mean, `0` if all edges are equally
+/// long
+pub fn edge_length_variation(simulator: &Simulator) -> f32 {
+    let (_, edges) = simulator_edges(simulator);
+    let positions = simulator.body_positions();
+    let lengths: Vec<f32> = edges
+        .iter()
+        .map(|&(a, b)| positions[a].distance(positions[b]))
+        .collect();
+    if lengths.is_empty() {
+        return 0.0;
+    }
+
+    let mean = lengths.iter().sum::<f32>() / lengths.len() as f32;
+    if mean == 0.0 {
+        return 0.0;
+    }
+    let variance = lengths.iter().map(|l| (l - mean).powi(2)).sum::<f32>() / lengths.len() as f32;
+    variance.sqrt() / mean
+}
+
+/// Number of pairs of springs that cross, springs sharing a node don't count.
+///
+/// Takes `O(springs²)` time.
+pub fn edge_crossings(simulator: &Simulator) -> usize {
+    let (_, edges) = simulator_edges(simulator);
+    let positions = simulator.body_positions();
+    let mut crossings = 0;
+    for (i, &(a, b)) in edges.iter().enumerate() {
+        for &(c, d) in &edges[i + 1..] {
+            if a == c || a == d || b == c || b == d {
+                continue;
+            }
+            if segments_cross(positions[a], positions[b], positions[c], positions[d]) {
+                crossings += 1;
+            }
+        }
+    }
+    crossings
+}
+
+/// If the segments `a`-`b` and `c`-`d` cross in a point inside both of them
+fn segments_cross(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> bool {
+    let side = |p: Vec2, q: Vec2, r: Vec2| (q - p).perp_dot(r - p);
+    let (c_side, d_side) = (side(a, b, c), side(a, b, d));
+    let (a_side, b_side) = (side(c, d, a), side(c, d, b));
+    c_side * d_side < 0.0 && a_side * b_side < 0.0
+}
+
+/// Pearson correlation of the distances between nodes in `layout` and the lengths of the
+/// shortest paths between them in `graph`, in `-1..=1` where `1` means the layout reproduces the
+/// graph distances up to scale.
+///
+/// Edge weights are the lengths of the edges, negative weights count as their absolute value and
+/// directions are ignored. `layout` has a position for every node of `graph` in the order of
+/// `node_indices`, like `Simulator::positions` of a simulator built from `graph`. Returns `0` if
+/// no pair of nodes is connected or either distance is the same for all pairs. Paths are counted
+/// from at most 64 sources spread over the nodes.
+pub fn distance_correlation<T, E, D>(layout: &[Vec2], graph: &StableGraph<T, E, D, u32>) -> f32
+where
+    E: Copy + Into<f32>,
+    D: EdgeType,
+{
+    let index: HashMap<_, usize> = graph
+        .node_indices()
+        .enumerate()
+        .map(|(i, node)| (node, i))
+        .collect();
+    let mut undirected: Graph<(), f32, Undirected, u32> = Graph::default();
+    for _ in 0..index.len() {
+        undirected.add_node(());
+    }
+    for edge in graph.edge_references() {
+        undirected.add_edge(
+            NodeIndex::new(index[&edge.source()]),
+            NodeIndex::new(index[&edge.target()]),
+            (*edge.weight()).into().abs(),
+        );
+    }
+
+    let node_count = undirected.node_count().min(layout.len());
+    let source_step = node_count.div_ceil(STRESS_SOURCES).max(1);
+    let mut pairs: Vec<(f64, f64)> = vec![];
+    for source in (0..node_count).step_by(source_step) {
+        let lengths = algo::dijkstra(&undirected, NodeIndex::new(source), None, |e| *e.weight());
+        for (target, length) in lengths {
+            if target.index() != source && target.index() < node_count {
+                let distance = layout[source].distance(layout[target.index()]);
+                pairs.push((distance as f64, length as f64));
+            }
+        }
+    }
+
+    let n = pairs.len() as f64;
+    let (mean_x, mean_y) = pairs
+        .iter()
+        .fold((0.0, 0.0), |(x, y), (dx, dy)| (x + dx / n, y + dy / n));
+    let (mut covariance, mut variance_x, mut variance_y) = (0.0, 0.0, 0.0);
+    for (x, y) in &pairs {
+        covariance += (x - mean_x) * (y - mean_y);
+        variance_x += (x - mean_x).powi(2);
+        variance_y += (y - mean_y).powi(2);
+    }
+    if variance_x == 0.0 || variance_y == 0.0 {
+        return 0.0;
+    }
+    (covariance / (variance_x * variance_y).sqrt()) as f32
+}
+
+/// Metric of every node that is kept up to date while the graph of a simulation changes, see
+/// `Simulator::subscribe_changes` and `Renderer::set_dynamic_metric`
+pub trait DynamicMetric: Send {
+    /// Recomputes the metric for the whole graph of `simulator`
+    fn reset(&mut self, simulator: &Simulator);
+
+    /// Updates the metric after the graph changed
+    fn apply(&mut self, change: &GraphChange);
+
+    /// Value of every rigid body, scaled to `0..=1`
+    fn values(&mut self) -> Vec<f32>;
+}
+
+/// `degree_centrality` that is updated in constant time per change
+#[derive(Clone, Debug, Default)]
+pub struct DynamicDegree {
+    degree: Vec<f32>,
+}
+
+impl DynamicDegree {
+    pub fn new(simulator: &Simulator) -> Self {
+        let mut metric = Self::default();
+        metric.reset(simulator);
+        metric
+    }
+}
+
+impl DynamicMetric for DynamicDegree {
+    fn reset(&mut self, simulator: &Simulator) {
+        let (node_count, edges) = simulator_edges(simulator);
+        self.degree = vec![0.0; node_count];
+        for (a, b) in edges {
+            self.degree[a] += 1.0;
+            self.degree[b] += 1.0;
+        }
+    }
+
+    fn apply(&mut self, change: &GraphChange) {
+        match change {
+            GraphChange::NodeInserted => self.degree.push(0.0),
+            GraphChange::EdgeInserted(a, b) | GraphChange::EdgeRemoved(a, b) => {
+                let delta = if matches!(change, GraphChange::EdgeInserted(..)) {
+                    1.0
+                } else {
+                    -1.0
+                };
+                for i in [*a, *b] {
+                    if let Some(degree) = self.degree.get_mut(i) {
+                        *degree += delta;
+                    }
+                }
+            }
+            GraphChange::NodesRemoved(mapping) | GraphChange::NodesReordered(mapping) => {
+                remap(&mut self.degree, mapping);
+            }
+        }
+    }
+
+    fn values(&mut self) -> Vec<f32> {
+        normalize(self.degree.clone())
+    }
+}
+
+/// `pagerank` that continues the power iteration from the previous ranks after a change, which
+/// takes far fewer iterations than starting over for small changes
+#[derive(Clone, Debug, Default)]
+pub struct DynamicPageRank {
+    edges: Vec<(usize, usize)>,
+    rank: Vec<f32>,
+    changed: bool,
+}
+
+impl DynamicPageRank {
+    pub fn new(simulator: &Simulator) -> Self {
+        let mut metric = Self::default();
+        metric.reset(simulator);
+        metric
+    }
+}
+
+impl DynamicMetric for DynamicPageRank {
+    fn reset(&mut self, simulator: &Simulator) {
+        let (node_count, edges) = simulator_edges(simulator);
+        self.edges = edges;
+        self.rank = vec![1.0 / node_count.max(1) as f32; node_count];
+        self.changed = true;
+    }
+
+    fn apply(&mut self, change: &GraphChange) {
+        match change {
+            GraphChange::NodeInserted => {
+                self.rank.push(1.0 / (self.rank.len() + 1) as f32);
+            }
+            GraphChange::EdgeInserted(a, b) => self.edges.push((*a, *b)),
+            GraphChange::EdgeRemoved(a, b) => {
+                if let Some(i) = self.edges.iter().position(|e| *e == (*a, *b)) {
+                    self.edges.swap_remove(i);
+                }
+            }
+            GraphChange::NodesRemoved(mapping) | GraphChange::NodesReordered(mapping) => {
+                for (a, b) in self.edges.iter_mut() {
+                    *a = mapping.get(*a).map_or(usize::MAX, |m| *m as usize);
+                    *b = mapping.get(*b).map_or(usize::MAX, |m| *m as usize);
+                }
+                remap(&mut self.rank, mapping);
+            }
+        }
+        self.changed = true;
+    }
+
+    fn values(&mut self) -> Vec<f32> {
+        if self.changed {
+            let node_count = self.rank.len();
+            self.edges
+                .retain(|(a, b)| *a < node_count && *b < node_count);
+
+            // The previous ranks no longer sum to 1 after nodes were added or removed
+            let sum: f32 = self.rank.iter().sum();
+            if sum > 0.0 {
+                self.rank.iter_mut().for_each(|r| *r /= sum);
+            }
+            self.rank = power_iteration(&self.edges, std::mem::take(&mut self.rank));
+            self.changed = false;
+        }
+        normalize(self.rank.clone())
+    }
+}
+
+/// Iterates PageRank starting from `rank` until it converges
+fn power_iteration(edges: &[(usize, usize)], mut rank: Vec<f32>) -> Vec<f32> {
+    let node_count = rank.len();
+    if node_count == 0 {
+        return rank;
+    }
+
+    let mut out_degree = vec![0usize; node_count];
+    for &(a, _) in edges {
+        out_degree[a] += 1;
+    }
+
+    let n = node_count as f32;
+    for _ in 0..MAX_ITERATIONS {
+        // Rank of nodes without outgoing edges is spread over all nodes
+        let dangling: f32 = (0..node_count)
+            .filter(|i| out_degree[*i] == 0)
+            .map(|i| rank[i])
+            .sum();
+        let base = (1.0 - PAGERANK_DAMPING) / n + PAGERANK_DAMPING * dangling / n;
+        let mut next = vec![base; node_count];
+        for &(a, b) in edges {
+            next[b] += PAGERANK_DAMPING * rank[a] / out_degree[a] as f32;
+        }
+
+        let delta: f32 = rank.iter().zip(&next).map(|(r, n)| (r - n).abs()).sum();
+        rank = next;
+        if delta < PAGERANK_EPSILON {
+            break;
+        }
+    }
+    rank
+}
+
+fn simulator_edges(simulator: &Simulator) -> (usize, Vec<(usize, usize)>) {
+    let node_count = simulator.rigid_bodies.read().unwrap().len();
+    let edges = simulator
+        .springs
+        .read()
+        .unwrap()
+        .iter()
+        .map(|s| (s.rb1, s.rb2))
+        .filter(|(a, b)| *a < node_count && *b < node_count)
+        .collect();
+    (node_count, edges)
+}
+
+fn adjacency(node_count: usize, edges: &[(usize, usize)]) -> Vec<Vec<usize>> {
+    let mut neighbours = vec![vec![]; node_count];
+    for &(a, b) in edges {
+        if a != b && a < node_count && b < node_count {
+            neighbours[a].push(b);
+            neighbours[b].push(a);
+        }
+    }
+    neighbours
+}
+
+/// Divides by the largest value
+fn normalize(mut values: Vec<f32>) -> Vec<f32> {
+    let max = values.iter().copied().fold(0.0, f32::max);
+    if max > 0.0 {
+        values.iter_mut().for_each(|v| *v /= max);
+    }
+    values
+}
+
+fn propagate(node_count: usize, edges: &[(usize, usize)]) -> Vec<u32> {
+    let neighbours = adjacency(node_count, edges);
+
+    let mut labels: Vec<usize> = (0..node_count).collect();
+    let mut order: Vec<usize> = (0..node_count).collect();
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut counts: HashMap<usize, usize> = HashMap::new();
+
+    for _ in 0..MAX_ITERATIONS {
+        order.shuffle(&mut rng);
+        let mut changed = false;
+
+        for &node in &order {
+            if neighbours[node].is_empty() {
+                continue;
+            }
+            counts.clear();
+            for &n in &neighbours[node] {
+                *counts.entry(labels[n]).or_default() += 1;
+            }
+            let max = counts.values().copied().max().unwrap_or(0);
+
+            // Keep the current label on ties so the labels settle
+            if counts.get(&labels[node]) == Some(&max) {
+                continue;
+            }
+            labels[node] = counts
+                .iter()
+                .filter(|(_, c)| **c == max)
+                .map(|(l, _)| *l)
+                .min()
+                .unwrap();
+            changed = true;
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    // Number the communities by first appearance
+    let mut ids: HashMap<usize, u32> = HashMap::new();
+    labels
+        .iter()
+        .map(|l| {
+            let next = ids.len() as u32;
+            *ids.entry(*l).or_insert(next)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use petgraph::Undirected;
+
+    use super::*;
+    use crate::simulator::SimulatorBuilder;
+
+    #[test]
+    fn test_label_propagation_two_cliques() {
+        let mut graph: StableGraph<(), (), Undirected, u32> = StableGraph::default();
+        let nodes: Vec<_> = (0..8).map(|_| graph.add_node(())).collect();
+        for clique in [&nodes[0..4], &nodes[4..8]] {
+            for (i, a) in clique.iter().enumerate() {
+                for b in &clique[i + 1..] {
+                    graph.add_edge(*a, *b, ());
+                }
+            }
+        }
+        graph.add_edge(nodes[3], nodes[4], ());
+
+        let communities = label_propagation(&graph);
+        assert_eq!(communities.len(), 8);
+        assert!(communities[0..4].iter().all(|c| *c == communities[0]));
+        assert!(communities[4..8].iter().all(|c| *c == communities[4]));
+        assert_ne!(communities[0], communities[4]);
+        assert_eq!(communities[0], 0);
+    }
+
+    #[test]
+    fn test_centrality_of_path() {
+        let mut graph: StableGraph<(), (), Undirected, u32> = StableGraph::default();
+        let nodes: Vec<_> = (0..3).map(|_| graph.add_node(())).collect();
+        graph.add_edge(nodes[0], nodes[1], ());
+        graph.add_edge(nodes[1], nodes[2], ());
+        let simulator = SimulatorBuilder::new().build(graph);
+
+        assert_eq!(degree_centrality(&simulator), vec![0.5, 1.0, 0.5]);
+        assert_eq!(betweenness_centrality(&simulator), vec![0.0, 1.0, 0.0]);
+
+        let rank = pagerank(&simulator);
+        assert_eq!(rank[2], 1.0);
+        assert!(rank[0] < rank[1] && rank[1] < rank[2]);
+    }
+
+    #[test]
+    fn test_dynamic_metrics_follow_changes() {
+        let mut graph: StableGraph<(), (), Undirected, u32> = StableGraph::default();
+        let nodes: Vec<_> = (0..3).map(|_| graph.add_node(())).collect();
+        graph.add_edge(nodes[0], nodes[1], ());
+        let simulator = SimulatorBuilder::new().build(graph);
+
+        let changes = simulator.subscribe_changes();
+        let mut degree = DynamicDegree::new(&simulator);
+        let mut rank = DynamicPageRank::new(&simulator);
+        simulator.insert_edge(1, 2, None);
+        simulator.remove_nodes(&[0]);
+        for change in changes.try_iter() {
+            degree.apply(&change);
+            rank.apply(&change);
+        }
+
+        assert_eq!(degree.values(), degree_centrality(&simulator));
+        let expected = pagerank(&simulator);
+        for (a, b) in rank.values().iter().zip(&expected) {
+            assert!((a - b).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_layout_quality_of_square() {
+        let mut graph: StableGraph<(), (), Undirected, u32> = StableGraph::default();
+        let nodes: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        for i in 0..4 {
+            graph.add_edge(nodes[i], nodes[(i + 1) % 4], ());
+        }
+        let simulator = SimulatorBuilder::new().build(graph);
+        let corners = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+        for (rb, (x, y)) in simulator
+            .rigid_bodies
+            .write()
+            .unwrap()
+            .iter_mut()
+            .zip(corners)
+        {
+            rb.position = Vec2::new(x, y);
+        }
+
+        assert_eq!(edge_crossings(&simulator), 0);
+        assert_eq!(edge_length_variation(&simulator), 0.0);
+        // The diagonals are sqrt(2) times the side but two hops long
+        assert!(stress(&simulator) > 0.0 && stress(&simulator) < 0.1);
+
+        // Swapping two corners makes the square a bow tie
+        simulator.rigid_bodies.write().unwrap()[1].position = Vec2::new(10.0, 10.0);
+        simulator.rigid_bodies.write().unwrap()[2].position = Vec2::new(10.0, 0.0);
+        assert_eq!(edge_crossings(&simulator), 1);
+        assert!(edge_length_variation(&simulator) > 0.0);
+    }
+
+    #[test]
+    fn test_distance_correlation() {
+        // Path 0 - 1 - 2 where the second edge is three times as long
+        let mut graph: StableGraph<(), f32, petgraph::Directed, u32> = StableGraph::default();
+        let nodes: Vec<_> = (0..3).map(|_| graph.add_node(())).collect();
+        graph.add_edge(nodes[0], nodes[1], 1.0);
+        graph.add_edge(nodes[2], nodes[1], -3.0);
+
+        let faithful = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(8.0, 0.0),
+        ];
+        assert!((distance_correlation(&faithful, &graph) - 1.0).abs() < 1e-5);
+
+        let reversed = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(3.0, 0.0),
+            Vec2::new(4.0, 0.0),
+        ];
+        assert!(distance_correlation(&reversed, &graph) < 0.5);
+        assert_eq!(distance_correlation(&[Vec2::ZERO; 3], &graph), 0.0);
+    }
+
+    #[test]
+    fn test_connected_components() {
+        let mut graph: StableGraph<(), (), Undirected, u32> = StableGraph::default();
+        let nodes: Vec<_> = (0..5).map(|_| graph.add_node(())).collect();
+        graph.add_edge(nodes[0], nodes[2], ());
+        graph.add_edge(nodes[3], nodes[1], ());
+        graph.add_edge(nodes[2], nodes[4], ());
+        let simulator = SimulatorBuilder::new().build(graph);
+
+        assert_eq!(connected_components(&simulator), vec![0, 1, 0, 1, 0]);
+    }
+
+    #[test]
+    fn test_isolated_nodes_keep_own_community() {
+        let mut graph: StableGraph<(), (), Undirected, u32> = StableGraph::default();
+        graph.add_node(());
+        graph.add_node(());
+        assert_eq!(label_propagation(&graph), vec![0, 1]);
+    }
+}
@@ -0,0 +1,300 @@
+//! Multilevel coarsening, every level merges pairs of neighboring nodes into one node. Build
+//! hierarchical visualizations on top of it, e.g. show a coarser level when zoomed out.
+//!
+//! # Example
+//! ```
+//!use grapher::coarsen;
+//!use petgraph::{prelude::StableGraph, Undirected};
+//!
+//!let mut graph: StableGraph<(), (), Undirected> = StableGraph::default();
+//!let nodes: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+//!graph.add_edge(nodes[0], nodes[1], ());
+//!graph.add_edge(nodes[1], nodes[2], ());
+//!graph.add_edge(nodes[2], nodes[3], ());
+//!
+//!let (coarse, mapping) = coarsen::coarsen(&graph);
+//!assert_eq!(coarse.node_count(), 2);
+//!assert_eq!(mapping.members(mapping.coarse_node(nodes[0]).unwrap()).len(), 2);
+//! ```
+
+use petgraph::{
+    prelude::StableGraph,
+    stable_graph::NodeIndex,
+    visit::{EdgeRef, IntoEdgeReferences, NodeIndexable},
+    EdgeType, Undirected,
+};
+
+/// Coarsened graph. The weight of a node is the number of original nodes merged into it, the
+/// weight of a edge the number of original edges between its nodes.
+pub type CoarseGraph = StableGraph<u32, u32, Undirected, u32>;
+
+/// Which coarse node every node of the finer graph was merged into
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mapping {
+    /// By index of the finer node, `u32::MAX` for removed indices of a `StableGraph`
+    coarse: Vec<u32>,
+    members: Vec<Vec<NodeIndex>>,
+}
+
+impl Mapping {
+    /// Coarse node `node` was merged into, `None` if it isn't a node of the finer graph
+    pub fn coarse_node(&self, node: NodeIndex) -> Option<NodeIndex> {
+        self.coarse
+            .get(node.index())
+            .filter(|c| **c != u32::MAX)
+            .map(|c| NodeIndex::new(*c as usize))
+    }
+
+    /// Nodes of the finer graph merged into `coarse_node`, empty if there is no such node
+    pub fn members(&self, coarse_node: NodeIndex) -> &[NodeIndex] {
+        self.members
+            .get(coarse_node.index())
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Maps the nodes of two levels from the finest to the coarsest, `coarser` must be the mapping
+    /// of the graph `self` maps to
+    pub fn then(&self, coarser: &Mapping) -> Mapping {
+        let coarse: Vec<u32> = self
+            .coarse
+            .iter()
+            .map(|c| coarser.coarse.get(*c as usize).copied().unwrap_or(u32::MAX))
+            .collect();
+        Mapping {
+            members: members(&coarse, coarser.members.len()),
+            coarse,
+        }
+    }
+}
+
+/// Merges pairs of neighbors into one node by heavy edge matching, ignoring edge directions.
+///
+/// Every node is merged with the unmatched neighbor it shares the most edges with, preferring
+/// small neighbors so the coarse nodes stay similar in size. Nodes without such a neighbor are
+/// kept as they are, so a level has at least half as many nodes as the graph.
+pub fn coarsen<T, E, D>(graph: &StableGraph<T, E, D, u32>) -> (CoarseGraph, Mapping)
+where
+    D: EdgeType,
+{
+    let mut weights = vec![None; graph.node_bound()];
+    for node in graph.node_indices() {
+        weights[node.index()] = Some(1);
+    }
+    let edges: Vec<(usize, usize, u32)> = graph
+        .edge_references()
+        .map(|e| (e.source().index(), e.target().index(), 1))
+        .collect();
+    coarsen_weighted(&weights, &edges)
+}
+
+/// Coarsens a graph again, keeping track of how many original nodes and edges were merged
+pub fn coarsen_coarse(graph: &CoarseGraph) -> (CoarseGraph, Mapping) {
+    let mut weights = vec![None; graph.node_bound()];
+    for node in graph.node_indices() {
+        weights[node.index()] = Some(graph[node]);
+    }
+    let edges: Vec<(usize, usize, u32)> = graph
+        .edge_references()
+        .map(|e| (e.source().index(), e.target().index(), *e.weight()))
+        .collect();
+    coarsen_weighted(&weights, &edges)
+}
+
+/// Coarsens `graph` until a level has at most `min_nodes` nodes or stops shrinking.
+///
+/// Returns every level from the finest to the coarsest, each mapping maps the original nodes
+/// directly to that level.
+pub fn coarsen_levels<T, E, D>(
+    graph: &StableGraph<T, E, D, u32>,
+    min_nodes: usize,
+) -> Vec<(CoarseGraph, Mapping)>
+where
+    D: EdgeType,
+{
+    let mut levels: Vec<(CoarseGraph, Mapping)> = vec![];
+    let mut node_count = graph.node_count();
+    while node_count > min_nodes {
+        let (coarse, mapping) = match levels.last() {
+            None => coarsen(graph),
+            Some((finer, finer_mapping)) => {
+                let (coarse, mapping) = coarsen_coarse(finer);
+                (coarse, finer_mapping.then(&mapping))
+            }
+        };
+        // Only isolated nodes or stars are left, which hardly merge
+        if coarse.node_count() as f32 > node_count as f32 * MIN_SHRINK {
+            break;
+        }
+        node_count = coarse.node_count();
+        levels.push((coarse, mapping));
+    }
+    levels
+}
+
+/// Largest fraction of nodes a level may keep for `coarsen_levels` to continue
+const MIN_SHRINK: f32 = 0.95;
+
+/// `weights` by node index, `None` for removed indices
+fn coarsen_weighted(
+    weights: &[Option<u32>],
+    edges: &[(usize, usize, u32)],
+) -> (CoarseGraph, Mapping) {
+    let node_bound = weights.len();
+    let mut neighbours: Vec<Vec<(usize, u32)>> = vec![vec![]; node_bound];
+    for &(a, b, weight) in edges {
+        if a != b {
+            neighbours[a].push((b, weight));
+            neighbours[b].push((a, weight));
+        }
+    }
+
+    // Nodes with few neighbors first, they have the fewest chances to find a partner
+    let mut order: Vec<usize> = (0..node_bound).filter(|i| weights[*i].is_some()).collect();
+    order.sort_by_key(|i| neighbours[*i].len());
+
+    let mut partner = vec![usize::MAX; node_bound];
+    for &node in &order {
+        if partner[node] != usize::MAX {
+            continue;
+        }
+        let best = neighbours[node]
+            .iter()
+            .filter(|(n, _)| partner[*n] == usize::MAX && *n != node)
+            .max_by_key(|(n, weight)| {
+                (
+                    *weight,
+                    std::cmp::Reverse(weights[*n]),
+                    std::cmp::Reverse(*n),
+                )
+            })
+            .map(|(n, _)| *n);
+        let best = best.unwrap_or(node);
+        partner[node] = best;
+        partner[best] = node;
+    }
+
+    let mut coarse_graph = CoarseGraph::default();
+    let mut coarse = vec![u32::MAX; node_bound];
+    for node in 0..node_bound {
+        let Some(weight) = weights[node] else {
+            continue;
+        };
+        if coarse[node] != u32::MAX {
+            continue;
+        }
+        let other = partner[node];
+        let merged_weight = if other != node {
+            weight + weights[other].unwrap_or(0)
+        } else {
+            weight
+        };
+        let index = coarse_graph.add_node(merged_weight).index() as u32;
+        coarse[node] = index;
+        coarse[other] = index;
+    }
+
+    // Parallel edges are merged into one edge weighted by their count
+    let mut coarse_edges: Vec<(u32, u32, u32)> = edges
+        .iter()
+        .map(|&(a, b, weight)| (coarse[a].min(coarse[b]), coarse[a].max(coarse[b]), weight))
+        .filter(|(a, b, _)| a != b)
+        .collect();
+    coarse_edges.sort_unstable_by_key(|(a, b, _)| (*a, *b));
+    coarse_edges.dedup_by(|next, kept| {
+        let same = (next.0, next.1) == (kept.0, kept.1);
+        if same {
+            kept.2 += next.2;
+        }
+        same
+    });
+    for (a, b, weight) in coarse_edges {
+        coarse_graph.add_edge(
+            NodeIndex::new(a as usize),
+            NodeIndex::new(b as usize),
+            weight,
+        );
+    }
+
+    let mapping = Mapping {
+        members: members(&coarse, coarse_graph.node_count()),
+        coarse,
+    };
+    (coarse_graph, mapping)
+}
+
+fn members(coarse: &[u32], coarse_count: usize) -> Vec<Vec<NodeIndex>> {
+    let mut members = vec![vec![]; coarse_count];
+    for (node, c) in coarse.iter().enumerate() {
+        if let Some(members) = members.get_mut(*c as usize) {
+            members.push(NodeIndex::new(node));
+        }
+    }
+    members
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use petgraph::Directed;
+
+    fn path(n: usize) -> StableGraph<(), (), Directed, u32> {
+        let mut graph = StableGraph::default();
+        let nodes: Vec<_> = (0..n).map(|_| graph.add_node(())).collect();
+        for pair in nodes.windows(2) {
+            graph.add_edge(pair[0], pair[1], ());
+        }
+        graph
+    }
+
+    #[test]
+    fn test_coarsen_path() {
+        let graph = path(6);
+        let (coarse, mapping) = coarsen(&graph);
+
+        assert_eq!(coarse.node_count(), 3);
+        assert_eq!(coarse.edge_count(), 2);
+        assert!(coarse.node_weights().all(|w| *w == 2));
+        for node in graph.node_indices() {
+            let c = mapping.coarse_node(node).unwrap();
+            assert!(mapping.members(c).contains(&node));
+        }
+        assert_eq!(mapping.coarse_node(NodeIndex::new(6)), None);
+    }
+
+    #[test]
+    fn test_coarsen_merges_parallel_edges() {
+        // 0-1 and 2-3 are merged, the other three edges join the two pairs
+        let mut graph: StableGraph<(), (), Undirected, u32> = StableGraph::default();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        graph.add_edge(n[0], n[1], ());
+        graph.add_edge(n[2], n[3], ());
+        graph.add_edge(n[0], n[2], ());
+        graph.add_edge(n[1], n[3], ());
+        graph.add_edge(n[1], n[2], ());
+
+        let (coarse, _) = coarsen(&graph);
+        assert_eq!(coarse.node_count(), 2);
+        let weights: Vec<u32> = coarse.edge_weights().copied().collect();
+        assert_eq!(weights, [3]);
+    }
+
+    #[test]
+    fn test_coarsen_levels() {
+        let mut graph = path(32);
+        graph.remove_node(NodeIndex::new(5));
+        let levels = coarsen_levels(&graph, 4);
+
+        let counts: Vec<usize> = levels.iter().map(|(g, _)| g.node_count()).collect();
+        assert!(counts.windows(2).all(|w| w[1] < w[0]));
+        assert!(*counts.last().unwrap() <= 4);
+
+        let (coarsest, mapping) = levels.last().unwrap();
+        assert_eq!(coarsest.node_weights().sum::<u32>(), 31);
+        assert_eq!(mapping.coarse_node(NodeIndex::new(5)), None);
+        let members: usize = coarsest
+            .node_indices()
+            .map(|c| mapping.members(c).len())
+            .sum();
+        assert_eq!(members, 31);
+    }
+}
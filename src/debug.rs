@@ -0,0 +1,99 @@
+//! Tools to debug the simulation.
+
+use std::fmt::Display;
+
+use glam::Vec2;
+
+use crate::simulator::Simulator;
+
+/// First node whose position differs between two simulations
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Divergence {
+    /// Number of steps after which the positions differed, `0` if they differed from the start
+    pub step: usize,
+    pub node: usize,
+    pub a: Vec2,
+    pub b: Vec2,
+}
+
+impl Display for Divergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "node {} diverged after {} steps: {} != {}",
+            self.node, self.step, self.a, self.b
+        )
+    }
+}
+
+impl std::error::Error for Divergence {}
+
+/// Steps `a` and `b` side by side for `steps` steps and checks that all positions stay bitwise identical.
+///
+/// Build both simulators with the same `SimulatorBuilder::seed`, graph and settings. Use it to make
+/// sure changes to the parallelism don't break deterministic simulations.
+pub fn check_determinism(a: &Simulator, b: &Simulator, steps: usize) -> Result<(), Divergence> {
+    compare(a, b, 0)?;
+    for step in 1..=steps {
+        a.simulation_step();
+        b.simulation_step();
+        compare(a, b, step)?;
+    }
+    Ok(())
+}
+
+fn compare(a: &Simulator, b: &Simulator, step: usize) -> Result<(), Divergence> {
+    let (positions_a, positions_b) = (a.body_positions(), b.body_positions());
+    if positions_a.len() != positions_b.len() {
+        return Err(Divergence {
+            step,
+            node: positions_a.len().min(positions_b.len()),
+            a: Vec2::NAN,
+            b: Vec2::NAN,
+        });
+    }
+
+    let bits = |p: &Vec2| (p.x.to_bits(), p.y.to_bits());
+    match positions_a
+        .iter()
+        .zip(&positions_b)
+        .position(|(pa, pb)| bits(pa) != bits(pb))
+    {
+        Some(node) => Err(Divergence {
+            step,
+            node,
+            a: positions_a[node],
+            b: positions_b[node],
+        }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use petgraph::{Directed, Graph};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::simulator::SimulatorBuilder;
+
+    #[test]
+    fn test_seeded_simulation_is_deterministic() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let graph: Graph<(), (), Directed> =
+            petgraph_gen::barabasi_albert_graph(&mut rng, 300, 2, None);
+
+        let a = SimulatorBuilder::new()
+            .seed(42)
+            .max_threads(1)
+            .build(graph.clone().into());
+        let b = SimulatorBuilder::new()
+            .seed(42)
+            .max_threads(4)
+            .build(graph.clone().into());
+        assert_eq!(check_determinism(&a, &b, 50), Ok(()));
+
+        let c = SimulatorBuilder::new().seed(7).build(graph.into());
+        assert_eq!(check_determinism(&a, &c, 0).map_err(|d| d.step), Err(0));
+    }
+}
@@ -0,0 +1,144 @@
+//! Uniform grid over the springs of a simulation to find the edge closest to a point.
+
+use std::collections::HashMap;
+
+use glam::Vec2;
+
+use crate::properties::Spring;
+
+/// Edges covering more cells than this are checked on every query instead
+const MAX_CELLS_PER_EDGE: i64 = 64;
+
+/// Spatial index over the straight line segments between the nodes of every spring
+pub struct EdgeGrid {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+    long_edges: Vec<usize>,
+    segments: Vec<(Vec2, Vec2)>,
+}
+
+impl EdgeGrid {
+    /// Indexes `springs` between the nodes at `positions`, the cell size is the average spring length
+    pub fn new(positions: &[Vec2], springs: &[Spring]) -> Self {
+        let segments: Vec<(Vec2, Vec2)> = springs
+            .iter()
+            .map(|s| (positions[s.rb1], positions[s.rb2]))
+            .collect();
+        let total_len: f32 = segments.iter().map(|(a, b)| a.distance(*b)).sum();
+        let cell_size = (total_len / segments.len().max(1) as f32).max(1.0);
+
+        let mut grid = Self {
+            cell_size,
+            cells: HashMap::new(),
+            long_edges: vec![],
+            segments,
+        };
+
+        for (i, (a, b)) in grid.segments.iter().enumerate() {
+            let (min, max) = (grid.cell(a.min(*b)), grid.cell(a.max(*b)));
+            let cell_count = (max.0 - min.0 + 1) as i64 * (max.1 - min.1 + 1) as i64;
+            if cell_count > MAX_CELLS_PER_EDGE {
+                grid.long_edges.push(i);
+                continue;
+            }
+            for x in min.0..=max.0 {
+                for y in min.1..=max.1 {
+                    grid.cells.entry((x, y)).or_default().push(i);
+                }
+            }
+        }
+        grid
+    }
+
+    /// Number of indexed springs
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Index and distance of the spring closest to `point` that is at most `max_distance` away
+    pub fn closest(&self, point: Vec2, max_distance: f32) -> Option<(usize, f32)> {
+        let min = self.cell(point - max_distance);
+        let max = self.cell(point + max_distance);
+
+        let mut candidates: Vec<usize> = self.long_edges.clone();
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                if let Some(cell) = self.cells.get(&(x, y)) {
+                    candidates.extend(cell);
+                }
+            }
+        }
+
+        candidates
+            .into_iter()
+            .map(|i| {
+                let (a, b) = self.segments[i];
+                (i, distance_to_segment(point, a, b))
+            })
+            .filter(|(_, d)| *d <= max_distance)
+            .min_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)))
+    }
+
+    fn cell(&self, p: Vec2) -> (i32, i32) {
+        let p = (p / self.cell_size).floor();
+        (p.x as i32, p.y as i32)
+    }
+}
+
+/// Distance from `p` to the closest point on the segment from `a` to `b`
+pub fn distance_to_segment(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let len_sq = ab.length_squared();
+    if len_sq == 0.0 {
+        return p.distance(a);
+    }
+    let t = ((p - a).dot(ab) / len_sq).clamp(0.0, 1.0);
+    p.distance(a + ab * t)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn spring(rb1: usize, rb2: usize) -> Spring {
+        Spring {
+            rb1,
+            rb2,
+            spring_stiffness: 1.0,
+            spring_neutral_len: 1.0,
+            damping: 0.0,
+            kind: None,
+        }
+    }
+
+    #[test]
+    fn test_distance_to_segment() {
+        let (a, b) = (Vec2::ZERO, Vec2::new(2.0, 0.0));
+        assert_eq!(distance_to_segment(Vec2::new(1.0, 1.0), a, b), 1.0);
+        assert_eq!(distance_to_segment(Vec2::new(3.0, 0.0), a, b), 1.0);
+        assert_eq!(distance_to_segment(Vec2::new(0.0, 2.0), a, a), 2.0);
+    }
+
+    #[test]
+    fn test_closest_edge() {
+        let positions = vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(0.0, 2.0),
+            Vec2::new(1000.0, 1000.0),
+        ];
+        let springs = vec![spring(0, 1), spring(0, 2), spring(2, 3)];
+        let grid = EdgeGrid::new(&positions, &springs);
+
+        assert_eq!(grid.closest(Vec2::new(1.0, 0.2), 0.5).map(|c| c.0), Some(0));
+        assert_eq!(
+            grid.closest(Vec2::new(-0.1, 1.0), 0.5).map(|c| c.0),
+            Some(1)
+        );
+        assert_eq!(
+            grid.closest(Vec2::new(500.0, 501.0), 2.0).map(|c| c.0),
+            Some(2)
+        );
+        assert_eq!(grid.closest(Vec2::new(10.0, 0.0), 0.5), None);
+    }
+}
@@ -0,0 +1,248 @@
+//! Draws the layout with egui's painter, for apps that want the layout inside their own egui UI
+//! instead of a dedicated window.
+//!
+//! Scroll to zoom, drag the background to pan, drag a node to move it, click a node to select it
+//! and double click to fit the whole graph into view.
+//!
+//! # Example
+//! ```no_run
+//!use grapher::egui_widget::{egui, GraphView};
+//!use grapher::prelude::*;
+//!
+//!fn graph_panel(ui: &mut egui::Ui, view: &mut GraphView, simulator: &Simulator) {
+//!    view.show(ui, simulator);
+//!    if let Some(node) = view.selected() {
+//!        ui.label(format!("Node {}", node));
+//!    }
+//!}
+//! ```
+
+pub use egui;
+use egui::{Color32, Pos2, Rect, Sense, Stroke};
+use glam::Vec2;
+
+use crate::simulator::Simulator;
+
+/// Space around the graph when it is fitted into view, relative to its size
+const FIT_MARGIN: f32 = 1.1;
+/// Zoom factor per scrolled point
+const SCROLL_ZOOM: f32 = 0.002;
+/// Smallest radius of a node on screen in points, so far zoomed out nodes stay visible
+const MIN_NODE_RADIUS: f32 = 1.5;
+
+/// Pan, zoom and selection of the graph shown in an egui `Ui`, keep it between frames
+#[derive(Clone, Debug)]
+pub struct GraphView {
+    /// World position in the center of the widget
+    center: Vec2,
+    /// Points per world unit
+    zoom: f32,
+    fitted: bool,
+    selected: Option<u32>,
+    dragged: Option<u32>,
+    node_radius: f32,
+    node_color: Color32,
+    selected_color: Color32,
+    edge_color: Color32,
+}
+
+impl GraphView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Radius of the nodes in world units
+    ///
+    /// Default: `1.0`
+    pub fn node_radius(mut self, node_radius: f32) -> Self {
+        self.node_radius = node_radius;
+        self
+    }
+
+    /// Default: light blue
+    pub fn node_color(mut self, color: Color32) -> Self {
+        self.node_color = color;
+        self
+    }
+
+    /// Default: orange
+    pub fn selected_color(mut self, color: Color32) -> Self {
+        self.selected_color = color;
+        self
+    }
+
+    /// Default: gray
+    pub fn edge_color(mut self, color: Color32) -> Self {
+        self.edge_color = color;
+        self
+    }
+
+    /// Index of the selected node
+    pub fn selected(&self) -> Option<u32> {
+        self.selected
+    }
+
+    pub fn set_selected(&mut self, selected: Option<u32>) {
+        self.selected = selected;
+    }
+
+    /// Fits the whole graph into view the next time it is shown
+    pub fn fit(&mut self) {
+        self.fitted = false;
+    }
+
+    /// Draws the layout of `simulator` into all of the available space of `ui` and handles the
+    /// input. The graph is fitted into view the first time it is shown.
+    pub fn show(&mut self, ui: &mut egui::Ui, simulator: &Simulator) -> egui::Response {
+        let (response, painter) = ui.allocate_painter(ui.available_size(), Sense::click_and_drag());
+        let rect = response.rect;
+
+        if !self.fitted || response.double_clicked() {
+            self.fit_bounds(simulator, rect);
+        }
+        self.handle_input(ui, &response, simulator);
+
+        let rb_read = simulator.rigid_bodies.read().unwrap();
+        let springs = simulator.springs.read().unwrap();
+
+        let edge_stroke = Stroke::new(1.0, self.edge_color);
+        for spring in springs.iter() {
+            let a = self.to_screen(rect, rb_read[spring.rb1].position);
+            let b = self.to_screen(rect, rb_read[spring.rb2].position);
+            painter.line_segment([a, b], edge_stroke);
+        }
+
+        let radius = (self.node_radius * self.zoom).max(MIN_NODE_RADIUS);
+        let visible = rect.expand(radius);
+        for (i, rb) in rb_read.iter().enumerate().filter(|(_, rb)| !rb.rope) {
+            let p = self.to_screen(rect, rb.position);
+            if !visible.contains(p) {
+                continue;
+            }
+            let color = if self.selected == Some(i as u32) {
+                self.selected_color
+            } else {
+                self.node_color
+            };
+            painter.circle_filled(p, radius, color);
+        }
+
+        response
+    }
+
+    fn handle_input(&mut self, ui: &egui::Ui, response: &egui::Response, simulator: &Simulator) {
+        let rect = response.rect;
+
+        if let Some(pointer) = response.hover_pos() {
+            let (scroll, pinch) = ui.input(|i| (i.scroll_delta.y, i.zoom_delta()));
+            let factor = (scroll * SCROLL_ZOOM).exp() * pinch;
+            if factor != 1.0 {
+                // Keep the world position under the pointer in place
+                let anchor = self.to_world(rect, pointer);
+                self.zoom *= factor;
+                self.center += anchor - self.to_world(rect, pointer);
+            }
+        }
+
+        if response.drag_started() || response.clicked() {
+            let node = response
+                .interact_pointer_pos()
+                .and_then(|p| self.node_at(rect, p, simulator));
+            if response.drag_started() {
+                self.dragged = node;
+            }
+            if response.clicked() || node.is_some() {
+                self.selected = node;
+            }
+        }
+
+        if response.dragged() {
+            match (self.dragged, response.interact_pointer_pos()) {
+                (Some(node), Some(pointer)) => {
+                    let position = self.to_world(rect, pointer);
+                    simulator.set_node_location_by_index(position.extend(0.0), node);
+                }
+                _ => {
+                    let delta = response.drag_delta();
+                    self.center -= Vec2::new(delta.x, -delta.y) / self.zoom;
+                }
+            }
+        } else {
+            self.dragged = None;
+        }
+    }
+
+    /// Node under the screen position `p`
+    fn node_at(&self, rect: Rect, p: Pos2, simulator: &Simulator) -> Option<u32> {
+        let point = self.to_world(rect, p);
+        let max_distance = self.node_radius.max(MIN_NODE_RADIUS / self.zoom);
+        let rb_read = simulator.rigid_bodies.read().unwrap();
+        rb_read
+            .iter()
+            .enumerate()
+            .filter(|(_, rb)| !rb.rope)
+            .map(|(i, rb)| (i, rb.position.distance(point)))
+            .filter(|(_, distance)| *distance <= max_distance)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i as u32)
+    }
+
+    fn fit_bounds(&mut self, simulator: &Simulator, rect: Rect) {
+        let Some(bounds) = simulator.bounding_box() else {
+            return;
+        };
+        let size = Vec2::new(bounds.width, bounds.height).max(Vec2::splat(self.node_radius * 2.0));
+        self.center = bounds.center;
+        self.zoom = (rect.width() / size.x).min(rect.height() / size.y) / FIT_MARGIN;
+        self.fitted = true;
+    }
+
+    /// Screen position of the world position `p`, the world y axis points up
+    fn to_screen(&self, rect: Rect, p: Vec2) -> Pos2 {
+        let offset = (p - self.center) * self.zoom;
+        rect.center() + egui::vec2(offset.x, -offset.y)
+    }
+
+    fn to_world(&self, rect: Rect, p: Pos2) -> Vec2 {
+        let offset = p - rect.center();
+        self.center + Vec2::new(offset.x, -offset.y) / self.zoom
+    }
+}
+
+impl Default for GraphView {
+    fn default() -> Self {
+        Self {
+            center: Vec2::ZERO,
+            zoom: 1.0,
+            fitted: false,
+            selected: None,
+            dragged: None,
+            node_radius: 1.0,
+            node_color: Color32::from_rgb(120, 170, 255),
+            selected_color: Color32::from_rgb(255, 160, 50),
+            edge_color: Color32::from_gray(110),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_screen_world_roundtrip() {
+        let rect = Rect::from_min_size(Pos2::new(10.0, 20.0), egui::vec2(200.0, 100.0));
+        let view = GraphView {
+            center: Vec2::new(5.0, -3.0),
+            zoom: 4.0,
+            ..Default::default()
+        };
+
+        assert_eq!(view.to_screen(rect, view.center), rect.center());
+        // Up in the world is up on screen
+        assert!(view.to_screen(rect, Vec2::new(5.0, 0.0)).y < rect.center().y);
+
+        let p = Vec2::new(7.5, 1.25);
+        assert!(view.to_world(rect, view.to_screen(rect, p)).distance(p) < 1e-4);
+    }
+}
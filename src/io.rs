@@ -0,0 +1,292 @@
+use std::{collections::HashSet, fmt::Display, fs, io::Read, path::Path};
+
+use petgraph::{
+    prelude::StableGraph,
+    stable_graph::{EdgeIndex, NodeIndex},
+    visit::{EdgeRef, IntoEdgeReferences},
+    EdgeType,
+};
+
+mod csv;
+mod dot;
+mod edgelist;
+mod gexf;
+mod graphml;
+#[cfg(feature = "serde")]
+mod node_link;
+mod registry;
+
+pub use csv::from_csv_str;
+pub use dot::{from_dot, to_dot};
+pub use edgelist::from_edgelist_str;
+pub use gexf::from_gexf_str;
+pub use graphml::from_graphml_str;
+#[cfg(feature = "serde")]
+pub use node_link::{from_node_link_json, to_node_link_json};
+pub use registry::{FormatRegistry, GraphImporter, LayoutExporter};
+
+/// Error returned when a graph file can't be loaded
+#[derive(Debug)]
+pub enum LoadError {
+    Io(std::io::Error),
+    Parse { line: usize, message: String },
+    UnsupportedFormat(String),
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Io(e) => write!(f, "{}", e),
+            LoadError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            LoadError::UnsupportedFormat(extension) => {
+                write!(f, "unsupported file format `{}`", extension)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<std::io::Error> for LoadError {
+    fn from(e: std::io::Error) -> Self {
+        LoadError::Io(e)
+    }
+}
+
+/// Loads a GraphML file
+pub fn load_graphml<P: AsRef<Path>>(
+    path: P,
+) -> Result<StableGraph<String, (), petgraph::Directed, u32>, LoadError> {
+    from_graphml_str(&fs::read_to_string(path)?)
+}
+
+/// Loads a GEXF file
+pub fn load_gexf<P: AsRef<Path>>(
+    path: P,
+) -> Result<StableGraph<String, (), petgraph::Directed, u32>, LoadError> {
+    from_gexf_str(&fs::read_to_string(path)?)
+}
+
+/// Loads a graph file, detecting the format by its extension.
+///
+/// Supported extensions: `csv`, `dot`, `gv`, `graphml`, `gexf` and `json` with the `serde` feature,
+/// see `FormatRegistry` to add more formats
+pub fn load_file<P: AsRef<Path>>(
+    path: P,
+) -> Result<StableGraph<String, (), petgraph::Directed, u32>, LoadError> {
+    load_file_with_progress(path, |_| ())
+}
+
+/// Like `load_file`, but calls `progress` with the fraction of the file read so far
+pub fn load_file_with_progress<P, F>(
+    path: P,
+    progress: F,
+) -> Result<StableGraph<String, (), petgraph::Directed, u32>, LoadError>
+where
+    P: AsRef<Path>,
+    F: FnMut(f32),
+{
+    FormatRegistry::default().load_file_with_progress(path, progress)
+}
+
+/// Reads a UTF-8 file in chunks, calling `progress` with the fraction read so far
+fn read_with_progress<F: FnMut(f32)>(path: &Path, mut progress: F) -> Result<String, LoadError> {
+    const CHUNK_SIZE: usize = 1 << 20;
+
+    let mut file = fs::File::open(path)?;
+    let len = file.metadata()?.len().max(1) as f32;
+    let mut bytes = vec![];
+    let mut chunk = vec![0; CHUNK_SIZE];
+    loop {
+        let read = file.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        bytes.extend_from_slice(&chunk[..read]);
+        progress((bytes.len() as f32 / len).min(1.0));
+    }
+    String::from_utf8(bytes)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e).into())
+}
+
+fn xml_error(e: roxmltree::Error) -> LoadError {
+    LoadError::Parse {
+        line: e.pos().row as usize,
+        message: e.to_string(),
+    }
+}
+
+fn xml_parse_error(doc: &roxmltree::Document, node: roxmltree::Node, message: String) -> LoadError {
+    LoadError::Parse {
+        line: doc.text_pos_at(node.range().start).row as usize,
+        message,
+    }
+}
+
+/// Problems found in a graph before it is simulated
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    pub self_loops: Vec<EdgeIndex>,
+    pub duplicate_edges: Vec<EdgeIndex>,
+    pub isolated_nodes: Vec<NodeIndex>,
+    pub non_finite_weights: Vec<EdgeIndex>,
+}
+
+impl ValidationReport {
+    /// `true` if no problems were found
+    pub fn is_ok(&self) -> bool {
+        self.self_loops.is_empty()
+            && self.duplicate_edges.is_empty()
+            && self.isolated_nodes.is_empty()
+            && self.non_finite_weights.is_empty()
+    }
+}
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_ok() {
+            return writeln!(f, "Graph is valid");
+        }
+        if !self.self_loops.is_empty() {
+            writeln!(f, "Warning: {} self-loop(s)", self.self_loops.len())?;
+        }
+        if !self.duplicate_edges.is_empty() {
+            writeln!(
+                f,
+                "Warning: {} duplicate edge(s)",
+                self.duplicate_edges.len()
+            )?;
+        }
+        if !self.isolated_nodes.is_empty() {
+            writeln!(f, "Warning: {} isolated node(s)", self.isolated_nodes.len())?;
+        }
+        if !self.non_finite_weights.is_empty() {
+            writeln!(
+                f,
+                "Warning: {} edge(s) with non-finite weight",
+                self.non_finite_weights.len()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks a graph for self-loops, duplicate edges and isolated nodes
+pub fn validate<T, E, D>(graph: &StableGraph<T, E, D, u32>) -> ValidationReport
+where
+    D: EdgeType,
+{
+    validate_with_weights(graph, |_| 0.0)
+}
+
+/// Like `validate`, but also reports edges whose weight, as returned by `weight_fn`, is NaN or infinite
+pub fn validate_with_weights<T, E, D, F>(
+    graph: &StableGraph<T, E, D, u32>,
+    weight_fn: F,
+) -> ValidationReport
+where
+    D: EdgeType,
+    F: Fn(&E) -> f32,
+{
+    let mut report = ValidationReport::default();
+    let mut seen = HashSet::new();
+    let mut connected = HashSet::new();
+
+    for edge in graph.edge_references() {
+        let (source, target) = (edge.source(), edge.target());
+        connected.insert(source);
+        connected.insert(target);
+
+        if source == target {
+            report.self_loops.push(edge.id());
+        } else if !seen.insert(edge_key::<D>(source, target)) {
+            report.duplicate_edges.push(edge.id());
+        }
+
+        if !weight_fn(edge.weight()).is_finite() {
+            report.non_finite_weights.push(edge.id());
+        }
+    }
+
+    report.isolated_nodes = graph
+        .node_indices()
+        .filter(|n| !connected.contains(n))
+        .collect();
+
+    report
+}
+
+/// Removes the self-loops and duplicate edges listed in `report` from `graph`
+pub fn fix<T, E, D>(graph: &mut StableGraph<T, E, D, u32>, report: &ValidationReport)
+where
+    D: EdgeType,
+{
+    for edge in report.self_loops.iter().chain(&report.duplicate_edges) {
+        graph.remove_edge(*edge);
+    }
+}
+
+fn edge_key<D: EdgeType>(source: NodeIndex, target: NodeIndex) -> (NodeIndex, NodeIndex) {
+    if D::is_directed() {
+        (source, target)
+    } else {
+        (source.min(target), source.max(target))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use petgraph::{Directed, Undirected};
+
+    #[test]
+    fn test_validate_reports_problems() {
+        let mut g: StableGraph<(), f32, Directed> = StableGraph::new();
+        let a = g.add_node(());
+        let b = g.add_node(());
+        let c = g.add_node(());
+        g.add_edge(a, b, 1.0);
+        g.add_edge(b, a, 1.0);
+        let dup = g.add_edge(a, b, 1.0);
+        let self_loop = g.add_edge(b, b, f32::NAN);
+
+        let report = validate_with_weights(&g, |w| *w);
+        assert_eq!(report.self_loops, vec![self_loop]);
+        assert_eq!(report.duplicate_edges, vec![dup]);
+        assert_eq!(report.isolated_nodes, vec![c]);
+        assert_eq!(report.non_finite_weights, vec![self_loop]);
+
+        fix(&mut g, &report);
+        assert_eq!(g.edge_count(), 2);
+    }
+
+    #[test]
+    fn test_validate_undirected_duplicates() {
+        let mut g: StableGraph<(), (), Undirected> = StableGraph::default();
+        let a = g.add_node(());
+        let b = g.add_node(());
+        g.add_edge(a, b, ());
+        g.add_edge(b, a, ());
+
+        let report = validate(&g);
+        assert_eq!(report.duplicate_edges.len(), 1);
+        assert!(report.isolated_nodes.is_empty());
+    }
+
+    #[test]
+    fn test_load_file_with_progress() {
+        let path = std::env::temp_dir().join("grapher_test_load_file_with_progress.csv");
+        fs::write(&path, "a,b\nb,c\n").unwrap();
+
+        let mut progress = vec![];
+        let graph = load_file_with_progress(&path, |p| progress.push(p)).unwrap();
+        assert_eq!(graph.edge_count(), 2);
+        assert_eq!(progress.last(), Some(&1.0));
+
+        assert!(matches!(
+            load_file("graph.unknown"),
+            Err(LoadError::UnsupportedFormat(_))
+        ));
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+
+use petgraph::{prelude::StableGraph, stable_graph::NodeIndex, Directed};
+
+use super::LoadError;
+
+/// Parses a edge list where every line contains `source,target`.
+///
+/// Nodes are labeled with the value found in the file. Empty lines and lines starting with `#` are skipped,
+/// additional columns are ignored.
+pub fn from_csv_str(csv: &str) -> Result<StableGraph<String, (), Directed, u32>, LoadError> {
+    let mut graph = StableGraph::new();
+    let mut nodes: HashMap<String, NodeIndex> = HashMap::new();
+
+    for (line_index, line) in csv.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut columns = line.split(',').map(str::trim);
+        let (Some(source), Some(target)) = (columns.next(), columns.next()) else {
+            return Err(LoadError::Parse {
+                line: line_index + 1,
+                message: "expected `source,target`".to_string(),
+            });
+        };
+
+        let source = *nodes
+            .entry(source.to_string())
+            .or_insert_with(|| graph.add_node(source.to_string()));
+        let target = *nodes
+            .entry(target.to_string())
+            .or_insert_with(|| graph.add_node(target.to_string()));
+        graph.add_edge(source, target, ());
+    }
+
+    Ok(graph)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_csv_str() {
+        let graph = from_csv_str("# comment\na,b\nb,c,1.0\n\na,c").unwrap();
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(graph.edge_count(), 3);
+
+        assert!(matches!(
+            from_csv_str("a,b\nc"),
+            Err(LoadError::Parse { line: 2, .. })
+        ));
+    }
+}
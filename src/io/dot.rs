@@ -0,0 +1,372 @@
+use std::{collections::HashMap, fmt::Write};
+
+use petgraph::{prelude::StableGraph, stable_graph::NodeIndex, Directed};
+
+use crate::simulator::Simulator;
+
+use super::LoadError;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Id(String),
+    Edge,
+    LBracket,
+    RBracket,
+    LBrace,
+    RBrace,
+    Equal,
+    Separator,
+    Colon,
+}
+
+/// Parses a Graphviz DOT graph.
+///
+/// Nodes are labeled with their `label` attribute and fall back to their id.
+/// Subgraphs are flattened, ports and all other attributes are ignored.
+pub fn from_dot(dot: &str) -> Result<StableGraph<String, (), Directed, u32>, LoadError> {
+    let tokens = tokenize(dot)?;
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+        graph: StableGraph::new(),
+        nodes: HashMap::new(),
+    };
+    parser.parse()?;
+    Ok(parser.graph)
+}
+
+/// Writes the current layout as a Graphviz DOT graph with `pos` attributes.
+///
+/// Nodes are named by their index in `Simulator::positions`. Use `neato -n` to render the graph at the simulated positions.
+pub fn to_dot(simulator: &Simulator) -> String {
+    let mut dot = String::from("digraph {\n");
+
+    for (i, position) in simulator.positions().iter().enumerate() {
+        writeln!(dot, "    {} [pos=\"{},{}!\"];", i, position.x, position.y).unwrap();
+    }
+
+    for (a, b) in simulator.edges() {
+        writeln!(dot, "    {} -> {};", a, b).unwrap();
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    graph: StableGraph<String, (), Directed, u32>,
+    nodes: HashMap<String, NodeIndex>,
+}
+
+impl Parser {
+    fn parse(&mut self) -> Result<(), LoadError> {
+        // Header: [strict] (graph | digraph) [id] {
+        if self.peek_id("strict") {
+            self.position += 1;
+        }
+        if !(self.peek_id("graph") || self.peek_id("digraph")) {
+            return Err(self.error("expected `graph` or `digraph`"));
+        }
+        self.position += 1;
+        if matches!(self.peek(), Some(Token::Id(_))) {
+            self.position += 1;
+        }
+        self.expect(Token::LBrace)?;
+
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next() {
+                None => return Err(self.error("unexpected end of file")),
+                Some(Token::LBrace) => depth += 1,
+                Some(Token::RBrace) => depth -= 1,
+                Some(Token::Separator) => (),
+                Some(Token::Id(id)) if id == "subgraph" => {
+                    if matches!(self.peek(), Some(Token::Id(_))) {
+                        self.position += 1;
+                    }
+                }
+                Some(Token::Id(id)) => self.statement(id)?,
+                Some(_) => return Err(self.error("unexpected token")),
+            }
+        }
+        Ok(())
+    }
+
+    fn statement(&mut self, id: String) -> Result<(), LoadError> {
+        match self.peek() {
+            // Graph attribute: id = id
+            Some(Token::Equal) => {
+                self.position += 1;
+                self.expect_id()?;
+                return Ok(());
+            }
+            // Attribute statement: (graph | node | edge) [..]
+            Some(Token::LBracket) if matches!(id.as_str(), "graph" | "node" | "edge") => {
+                self.attributes()?;
+                return Ok(());
+            }
+            _ => (),
+        }
+
+        self.skip_port()?;
+        let mut chain = vec![id];
+        while self.peek() == Some(&Token::Edge) {
+            self.position += 1;
+            chain.push(self.expect_id()?);
+            self.skip_port()?;
+        }
+
+        let attributes = if self.peek() == Some(&Token::LBracket) {
+            self.attributes()?
+        } else {
+            HashMap::new()
+        };
+
+        let indices: Vec<NodeIndex> = chain.iter().map(|id| self.node(id)).collect();
+        if indices.len() == 1 {
+            if let Some(label) = attributes.get("label") {
+                self.graph[indices[0]] = label.clone();
+            }
+        }
+        for pair in indices.windows(2) {
+            self.graph.add_edge(pair[0], pair[1], ());
+        }
+        Ok(())
+    }
+
+    fn attributes(&mut self) -> Result<HashMap<String, String>, LoadError> {
+        let mut attributes = HashMap::new();
+        while self.peek() == Some(&Token::LBracket) {
+            self.position += 1;
+            loop {
+                match self.next() {
+                    Some(Token::RBracket) => break,
+                    Some(Token::Separator) => (),
+                    Some(Token::Id(key)) => {
+                        self.expect(Token::Equal)?;
+                        let value = self.expect_id()?;
+                        attributes.insert(key, value);
+                    }
+                    _ => return Err(self.error("malformed attribute list")),
+                }
+            }
+        }
+        Ok(attributes)
+    }
+
+    fn skip_port(&mut self) -> Result<(), LoadError> {
+        while self.peek() == Some(&Token::Colon) {
+            self.position += 1;
+            self.expect_id()?;
+        }
+        Ok(())
+    }
+
+    fn node(&mut self, id: &str) -> NodeIndex {
+        if let Some(index) = self.nodes.get(id) {
+            return *index;
+        }
+        let index = self.graph.add_node(id.to_string());
+        self.nodes.insert(id.to_string(), index);
+        index
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(t, _)| t)
+    }
+
+    fn peek_id(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Id(id)) if id.eq_ignore_ascii_case(keyword))
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).map(|(t, _)| t.clone());
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), LoadError> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            _ => Err(self.error(&format!("expected {:?}", expected))),
+        }
+    }
+
+    fn expect_id(&mut self) -> Result<String, LoadError> {
+        match self.next() {
+            Some(Token::Id(id)) => Ok(id),
+            _ => Err(self.error("expected identifier")),
+        }
+    }
+
+    fn error(&self, message: &str) -> LoadError {
+        let line = self
+            .tokens
+            .get(self.position.saturating_sub(1))
+            .or(self.tokens.last())
+            .map_or(1, |(_, line)| *line);
+        LoadError::Parse {
+            line,
+            message: message.to_string(),
+        }
+    }
+}
+
+fn tokenize(dot: &str) -> Result<Vec<(Token, usize)>, LoadError> {
+    let mut tokens = vec![];
+    let mut chars = dot.chars().peekable();
+    let mut line = 1;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            c if c.is_whitespace() => (),
+            '[' => tokens.push((Token::LBracket, line)),
+            ']' => tokens.push((Token::RBracket, line)),
+            '{' => tokens.push((Token::LBrace, line)),
+            '}' => tokens.push((Token::RBrace, line)),
+            '=' => tokens.push((Token::Equal, line)),
+            ':' => tokens.push((Token::Colon, line)),
+            ';' | ',' => tokens.push((Token::Separator, line)),
+            '#' => {
+                // Preprocessor output line
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        line += 1;
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        line += 1;
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if previous == '*' => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            previous = c;
+                        }
+                        None => {
+                            return Err(LoadError::Parse {
+                                line,
+                                message: "unterminated comment".to_string(),
+                            })
+                        }
+                    }
+                }
+            }
+            '-' if matches!(chars.peek(), Some('>') | Some('-')) => {
+                chars.next();
+                tokens.push((Token::Edge, line));
+            }
+            '"' => {
+                let start_line = line;
+                let mut id = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            id.push('"');
+                        }
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            id.push(c);
+                        }
+                        None => {
+                            return Err(LoadError::Parse {
+                                line: start_line,
+                                message: "unterminated string".to_string(),
+                            })
+                        }
+                    }
+                }
+                tokens.push((Token::Id(id), start_line));
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' => {
+                let mut id = String::from(c);
+                while let Some(c) = chars.peek() {
+                    if c.is_alphanumeric() || *c == '_' || *c == '.' {
+                        id.push(*c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push((Token::Id(id), line));
+            }
+            c => {
+                return Err(LoadError::Parse {
+                    line,
+                    message: format!("unexpected character `{}`", c),
+                })
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_dot() {
+        let dot = r#"
+            // comment
+            strict digraph G {
+                rankdir = LR;
+                node [shape=box];
+                a [label="Node A"];
+                a -> b -> c [weight=2];
+                subgraph cluster_0 { c -> "d e" }
+            }"#;
+        let graph = from_dot(dot).unwrap();
+        let labels: Vec<&String> = graph.node_weights().collect();
+        assert_eq!(labels, vec!["Node A", "b", "c", "d e"]);
+        assert_eq!(graph.edge_count(), 3);
+    }
+
+    #[test]
+    fn test_from_dot_error_line() {
+        let dot = "graph {\n a -- b\n c -- [ }";
+        assert!(matches!(
+            from_dot(dot),
+            Err(LoadError::Parse { line: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_to_dot_leaves_out_rope_particles() {
+        let mut graph: StableGraph<(), (), Directed> = StableGraph::new();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        graph.add_edge(a, b, ());
+        let simulator = Simulator::builder()
+            .spring_neutral_length(10.0)
+            .rope_segment_length(3.0)
+            .build(graph);
+        assert!(simulator.rigid_bodies.read().unwrap().len() > 2);
+
+        let dot = from_dot(&to_dot(&simulator)).unwrap();
+        assert_eq!(dot.node_count(), 2);
+        assert_eq!(dot.edge_count(), 1);
+        assert!(to_dot(&simulator).contains("0 -> 1;"));
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+
+use petgraph::{prelude::StableGraph, stable_graph::NodeIndex, Directed};
+
+use super::LoadError;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Id(String),
+    Arrow,
+    LBracket,
+    RBracket,
+    Equal,
+    Comma,
+    /// `;` or a line break
+    End,
+}
+
+/// Parses a tiny edge list language to define graphs inline, e.g. in examples and tests.
+///
+/// Statements are separated by `;` or line breaks and `#` starts a comment:
+/// ```text
+/// a -> b -> c [weight = 2]   # a chain of edges, the attributes apply to every edge
+/// c -> a
+/// d [label = "Node d"]       # a node without edges, or the label of a node
+/// ```
+/// Ids are words or quoted strings. Nodes are labeled with their `label` attribute and fall back
+/// to their id, edges are weighted with their `weight` attribute and default to `1.0`.
+///
+/// ```
+/// let graph = grapher::io::from_edgelist_str("a -> b; b -> c [weight = 0.5]").unwrap();
+/// assert_eq!(graph.node_count(), 3);
+/// assert_eq!(graph.edge_count(), 2);
+/// ```
+pub fn from_edgelist_str(
+    edgelist: &str,
+) -> Result<StableGraph<String, f32, Directed, u32>, LoadError> {
+    let mut parser = Parser {
+        tokens: tokenize(edgelist)?,
+        position: 0,
+        graph: StableGraph::new(),
+        nodes: HashMap::new(),
+    };
+    parser.parse()?;
+    Ok(parser.graph)
+}
+
+fn tokenize(edgelist: &str) -> Result<Vec<(Token, usize)>, LoadError> {
+    let mut tokens = vec![];
+    let mut line = 1;
+    let mut chars = edgelist.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => {
+                tokens.push((Token::End, line));
+                line += 1;
+            }
+            ';' => tokens.push((Token::End, line)),
+            '#' => while chars.next_if(|c| *c != '\n').is_some() {},
+            '[' => tokens.push((Token::LBracket, line)),
+            ']' => tokens.push((Token::RBracket, line)),
+            '=' => tokens.push((Token::Equal, line)),
+            ',' => tokens.push((Token::Comma, line)),
+            '-' if chars.next_if_eq(&'>').is_some() => tokens.push((Token::Arrow, line)),
+            '"' => {
+                let start = line;
+                let mut id = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => id.extend(chars.next()),
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            id.push(c);
+                        }
+                        None => {
+                            return Err(LoadError::Parse {
+                                line: start,
+                                message: "unterminated string".to_string(),
+                            })
+                        }
+                    }
+                }
+                tokens.push((Token::Id(id), start));
+            }
+            c if c.is_whitespace() => {}
+            // A `-` that doesn't start a arrow starts a negative number
+            c if is_id_char(c) || c == '-' => {
+                let mut id = c.to_string();
+                while let Some(c) = chars.next_if(|c| is_id_char(*c)) {
+                    id.push(c);
+                }
+                tokens.push((Token::Id(id), line));
+            }
+            c => {
+                return Err(LoadError::Parse {
+                    line,
+                    message: format!("unexpected character `{}`", c),
+                })
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn is_id_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.'
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    graph: StableGraph<String, f32, Directed, u32>,
+    nodes: HashMap<String, NodeIndex>,
+}
+
+impl Parser {
+    fn parse(&mut self) -> Result<(), LoadError> {
+        while let Some(token) = self.peek() {
+            if *token == Token::End {
+                self.position += 1;
+            } else {
+                self.statement()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// A chain of nodes connected by `->`, followed by optional attributes
+    fn statement(&mut self) -> Result<(), LoadError> {
+        let mut chain = vec![self.node()?];
+        while self.peek() == Some(&Token::Arrow) {
+            self.position += 1;
+            chain.push(self.node()?);
+        }
+
+        let line = self.line();
+        let attributes = if self.peek() == Some(&Token::LBracket) {
+            self.attributes()?
+        } else {
+            vec![]
+        };
+        match self.peek() {
+            None | Some(Token::End) => (),
+            Some(_) => return Err(self.error("expected `->`, `[`, `;` or a line break")),
+        }
+
+        if let [node] = chain[..] {
+            for (key, value) in attributes {
+                match key.as_str() {
+                    "label" => self.graph[node] = value,
+                    _ => return Err(unknown_attribute(line, &key, "node")),
+                }
+            }
+            return Ok(());
+        }
+
+        let mut weight = 1.0;
+        for (key, value) in attributes {
+            match key.as_str() {
+                "weight" => {
+                    weight = value.parse().map_err(|_| LoadError::Parse {
+                        line,
+                        message: format!("weight `{}` is not a number", value),
+                    })?
+                }
+                _ => return Err(unknown_attribute(line, &key, "edge")),
+            }
+        }
+        for pair in chain.windows(2) {
+            self.graph.add_edge(pair[0], pair[1], weight);
+        }
+        Ok(())
+    }
+
+    fn node(&mut self) -> Result<NodeIndex, LoadError> {
+        let id = self.id()?;
+        let graph = &mut self.graph;
+        Ok(*self
+            .nodes
+            .entry(id.clone())
+            .or_insert_with(|| graph.add_node(id)))
+    }
+
+    fn attributes(&mut self) -> Result<Vec<(String, String)>, LoadError> {
+        self.expect(Token::LBracket)?;
+        let mut attributes = vec![];
+        while self.peek() != Some(&Token::RBracket) {
+            let key = self.id()?;
+            self.expect(Token::Equal)?;
+            attributes.push((key, self.id()?));
+            if self.peek() == Some(&Token::Comma) {
+                self.position += 1;
+            }
+        }
+        self.expect(Token::RBracket)?;
+        Ok(attributes)
+    }
+
+    fn id(&mut self) -> Result<String, LoadError> {
+        match self.tokens.get(self.position) {
+            Some((Token::Id(id), _)) => {
+                let id = id.clone();
+                self.position += 1;
+                Ok(id)
+            }
+            _ => Err(self.error("expected a id")),
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), LoadError> {
+        if self.peek() == Some(&token) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected {:?}", token)))
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .or(self.tokens.last())
+            .map_or(1, |(_, line)| *line)
+    }
+
+    fn error(&self, message: &str) -> LoadError {
+        LoadError::Parse {
+            line: self.line(),
+            message: message.to_string(),
+        }
+    }
+}
+
+fn unknown_attribute(line: usize, key: &str, kind: &str) -> LoadError {
+    LoadError::Parse {
+        line,
+        message: format!("unknown {} attribute `{}`", kind, key),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use petgraph::visit::IntoEdgeReferences;
+
+    use super::*;
+
+    #[test]
+    fn test_from_edgelist_str() {
+        let graph = from_edgelist_str(
+            "# comment\n\
+             a -> b -> c [weight = 2.5]; c->a [weight = -1]\n\
+             \n\
+             d [label = \"Node \\\"d\\\"\"]\n\
+             \"long id\" -> a",
+        )
+        .unwrap();
+        assert_eq!(graph.node_count(), 5);
+        assert_eq!(graph.edge_count(), 4);
+
+        let labels: Vec<&str> = graph.node_weights().map(String::as_str).collect();
+        assert_eq!(labels, ["a", "b", "c", "Node \"d\"", "long id"]);
+        let weights: Vec<f32> = graph.edge_references().map(|e| *e.weight()).collect();
+        assert_eq!(weights, [2.5, 2.5, -1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_edgelist_errors() {
+        let line = |edgelist: &str| match from_edgelist_str(edgelist) {
+            Err(LoadError::Parse { line, .. }) => line,
+            other => panic!("expected a parse error, got {:?}", other),
+        };
+        assert_eq!(line("a -> b\na ->"), 2);
+        assert_eq!(line("a -> b\n\na b"), 3);
+        assert_eq!(line("a [color = red]"), 1);
+        assert_eq!(line("a -> b [weight = heavy]"), 1);
+        assert_eq!(line("a -> \"b"), 1);
+        assert_eq!(line("a -> b!"), 1);
+    }
+}
@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+
+use petgraph::{prelude::StableGraph, stable_graph::NodeIndex, Directed};
+use roxmltree::Document;
+
+use super::{
+    graphml::{node_reference, required_attribute},
+    xml_error, LoadError,
+};
+
+/// Parses a GEXF document.
+///
+/// Nodes are labeled with their `label` attribute and fall back to their id.
+pub fn from_gexf_str(xml: &str) -> Result<StableGraph<String, (), Directed, u32>, LoadError> {
+    let doc = Document::parse(xml).map_err(xml_error)?;
+
+    let mut graph = StableGraph::new();
+    let mut nodes: HashMap<&str, NodeIndex> = HashMap::new();
+
+    for node in doc.descendants().filter(|n| n.has_tag_name("node")) {
+        let id = required_attribute(&doc, node, "id")?;
+        let label = node.attribute("label").unwrap_or(id);
+        nodes.insert(id, graph.add_node(label.to_string()));
+    }
+
+    for edge in doc.descendants().filter(|n| n.has_tag_name("edge")) {
+        let source = node_reference(&doc, edge, "source", &nodes)?;
+        let target = node_reference(&doc, edge, "target", &nodes)?;
+        graph.add_edge(source, target, ());
+    }
+
+    Ok(graph)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_gexf_str() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gexf xmlns="http://gexf.net/1.3" version="1.3">
+  <graph defaultedgetype="directed">
+    <nodes>
+      <node id="0" label="Hello"/>
+      <node id="1" label="World"/>
+    </nodes>
+    <edges>
+      <edge id="0" source="0" target="1"/>
+    </edges>
+  </graph>
+</gexf>"#;
+        let graph = from_gexf_str(xml).unwrap();
+        let labels: Vec<&String> = graph.node_weights().collect();
+        assert_eq!(labels, vec!["Hello", "World"]);
+        assert_eq!(graph.edge_count(), 1);
+    }
+}
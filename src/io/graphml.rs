@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use petgraph::{prelude::StableGraph, stable_graph::NodeIndex, Directed};
+use roxmltree::{Document, Node};
+
+use super::{xml_error, xml_parse_error, LoadError};
+
+/// Parses a GraphML document.
+///
+/// Nodes are labeled with their `label` or `name` data attribute and fall back to their id.
+pub fn from_graphml_str(xml: &str) -> Result<StableGraph<String, (), Directed, u32>, LoadError> {
+    let doc = Document::parse(xml).map_err(xml_error)?;
+
+    // Keys whose data is used as node label
+    let label_keys: Vec<&str> = doc
+        .descendants()
+        .filter(|n| n.has_tag_name("key"))
+        .filter(|n| n.attribute("for").is_none_or(|f| f == "node" || f == "all"))
+        .filter(|n| matches!(n.attribute("attr.name"), Some("label") | Some("name")))
+        .filter_map(|n| n.attribute("id"))
+        .collect();
+
+    let mut graph = StableGraph::new();
+    let mut nodes: HashMap<&str, NodeIndex> = HashMap::new();
+
+    for node in doc.descendants().filter(|n| n.has_tag_name("node")) {
+        let id = required_attribute(&doc, node, "id")?;
+        let label = node
+            .children()
+            .filter(|c| c.has_tag_name("data"))
+            .find(|c| c.attribute("key").is_some_and(|k| label_keys.contains(&k)))
+            .and_then(|c| c.text())
+            .unwrap_or(id);
+
+        nodes.insert(id, graph.add_node(label.trim().to_string()));
+    }
+
+    for edge in doc.descendants().filter(|n| n.has_tag_name("edge")) {
+        let source = node_reference(&doc, edge, "source", &nodes)?;
+        let target = node_reference(&doc, edge, "target", &nodes)?;
+        graph.add_edge(source, target, ());
+    }
+
+    Ok(graph)
+}
+
+pub(super) fn required_attribute<'a>(
+    doc: &Document,
+    node: Node<'a, '_>,
+    name: &str,
+) -> Result<&'a str, LoadError> {
+    node.attribute(name).ok_or_else(|| {
+        xml_parse_error(
+            doc,
+            node,
+            format!("<{}> is missing `{}`", node.tag_name().name(), name),
+        )
+    })
+}
+
+pub(super) fn node_reference(
+    doc: &Document,
+    edge: Node,
+    name: &str,
+    nodes: &HashMap<&str, NodeIndex>,
+) -> Result<NodeIndex, LoadError> {
+    let id = required_attribute(doc, edge, name)?;
+    nodes
+        .get(id)
+        .copied()
+        .ok_or_else(|| xml_parse_error(doc, edge, format!("unknown node `{}`", id)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_graphml_str() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="d0" for="node" attr.name="label" attr.type="string"/>
+  <graph id="G" edgedefault="directed">
+    <node id="n0"><data key="d0">Alice</data></node>
+    <node id="n1"/>
+    <edge source="n0" target="n1"/>
+  </graph>
+</graphml>"#;
+        let graph = from_graphml_str(xml).unwrap();
+        let labels: Vec<&String> = graph.node_weights().collect();
+        assert_eq!(labels, vec!["Alice", "n1"]);
+        assert_eq!(graph.edge_count(), 1);
+
+        let missing =
+            r#"<graphml><graph><node id="a"/><edge source="a" target="b"/></graph></graphml>"#;
+        assert!(matches!(
+            from_graphml_str(missing),
+            Err(LoadError::Parse { line: 1, .. })
+        ));
+    }
+}
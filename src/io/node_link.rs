@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+
+use glam::Vec2;
+use petgraph::{prelude::StableGraph, stable_graph::NodeIndex, Directed};
+use serde_json::{json, Value};
+
+use crate::simulator::Simulator;
+
+use super::LoadError;
+
+/// Parses a d3-force style node-link JSON document `{"nodes": [...], "links": [...]}`.
+///
+/// Nodes are labeled with their `label` or `name` and fall back to their `id`.
+/// Links may reference nodes by `id`, by index or by an embedded node object.
+///
+/// Returns the graph and the `x`/`y` position of every node, if present.
+/// Use `Simulator::set_node_location_by_index` to restore the layout.
+#[allow(clippy::type_complexity)]
+pub fn from_node_link_json(
+    json: &str,
+) -> Result<(StableGraph<String, (), Directed, u32>, Vec<Option<Vec2>>), LoadError> {
+    let doc: Value = serde_json::from_str(json).map_err(|e| LoadError::Parse {
+        line: e.line(),
+        message: e.to_string(),
+    })?;
+
+    let mut graph = StableGraph::new();
+    let mut positions = vec![];
+    let mut ids: HashMap<String, NodeIndex> = HashMap::new();
+
+    for (i, node) in array(&doc, "nodes")?.iter().enumerate() {
+        let id = node.get("id").map_or(i.to_string(), id_string);
+        let label = node
+            .get("label")
+            .or(node.get("name"))
+            .and_then(Value::as_str)
+            .map_or(id.clone(), str::to_string);
+
+        let position = match (
+            node.get("x").and_then(Value::as_f64),
+            node.get("y").and_then(Value::as_f64),
+        ) {
+            (Some(x), Some(y)) => Some(Vec2::new(x as f32, y as f32)),
+            _ => None,
+        };
+
+        ids.insert(id, graph.add_node(label));
+        positions.push(position);
+    }
+
+    for link in array(&doc, "links")? {
+        let source = node_reference(link, "source", &ids, graph.node_count())?;
+        let target = node_reference(link, "target", &ids, graph.node_count())?;
+        graph.add_edge(source, target, ());
+    }
+
+    Ok((graph, positions))
+}
+
+/// Writes the current layout as d3-force style node-link JSON.
+///
+/// Nodes are identified by their index in `Simulator::positions` and carry their position as `x` and `y`.
+pub fn to_node_link_json(simulator: &Simulator) -> String {
+    let nodes: Vec<Value> = simulator
+        .positions()
+        .iter()
+        .enumerate()
+        .map(|(i, position)| json!({ "id": i, "x": position.x, "y": position.y }))
+        .collect();
+
+    let links: Vec<Value> = simulator
+        .edges()
+        .iter()
+        .map(|(source, target)| json!({ "source": source, "target": target }))
+        .collect();
+
+    json!({ "nodes": nodes, "links": links }).to_string()
+}
+
+fn array<'a>(doc: &'a Value, key: &str) -> Result<&'a Vec<Value>, LoadError> {
+    doc.get(key)
+        .and_then(Value::as_array)
+        .ok_or_else(|| parse_error(format!("missing `{}` array", key)))
+}
+
+fn id_string(id: &Value) -> String {
+    match id {
+        Value::String(s) => s.clone(),
+        id => id.to_string(),
+    }
+}
+
+fn node_reference(
+    link: &Value,
+    key: &str,
+    ids: &HashMap<String, NodeIndex>,
+    node_count: usize,
+) -> Result<NodeIndex, LoadError> {
+    let reference = match link.get(key) {
+        // d3 replaces the reference with the node object once the simulation started
+        Some(Value::Object(node)) => node.get("id").or(node.get("index")),
+        reference => reference,
+    }
+    .ok_or_else(|| parse_error(format!("link without `{}`", key)))?;
+
+    if let Some(index) = ids.get(&id_string(reference)) {
+        return Ok(*index);
+    }
+    match reference.as_u64() {
+        Some(index) if (index as usize) < node_count => Ok(NodeIndex::new(index as usize)),
+        _ => Err(parse_error(format!("unknown node `{}`", reference))),
+    }
+}
+
+fn parse_error(message: String) -> LoadError {
+    // serde_json doesn't keep the location of values
+    LoadError::Parse { line: 1, message }
+}
+
+#[cfg(test)]
+mod test {
+    use petgraph::visit::{EdgeRef, IntoEdgeReferences};
+
+    use super::*;
+
+    #[test]
+    fn test_from_node_link_json() {
+        let json = r#"{
+            "nodes": [
+                {"id": "a", "name": "Node A", "x": 1.5, "y": -2},
+                {"id": "b"},
+                {"id": 3}
+            ],
+            "links": [
+                {"source": "a", "target": "b"},
+                {"source": {"id": "b", "index": 1}, "target": 3}
+            ]
+        }"#;
+        let (graph, positions) = from_node_link_json(json).unwrap();
+        let labels: Vec<&String> = graph.node_weights().collect();
+        assert_eq!(labels, vec!["Node A", "b", "3"]);
+        assert_eq!(graph.edge_count(), 2);
+        assert_eq!(positions, vec![Some(Vec2::new(1.5, -2.0)), None, None]);
+    }
+
+    #[test]
+    fn test_node_link_json_roundtrip() {
+        let mut graph: StableGraph<(), (), Directed> = StableGraph::new();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        graph.add_edge(a, b, ());
+        let simulator = Simulator::builder().build(graph);
+
+        let (graph, positions) = from_node_link_json(&to_node_link_json(&simulator)).unwrap();
+        assert_eq!(graph.edge_count(), 1);
+        let positions: Vec<Vec2> = positions.into_iter().map(Option::unwrap).collect();
+        assert_eq!(positions, simulator.positions());
+    }
+
+    #[test]
+    fn test_node_link_json_leaves_out_rope_particles() {
+        let mut graph: StableGraph<(), (), Directed> = StableGraph::new();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+        graph.add_edge(a, b, ());
+        graph.add_edge(c, b, ());
+        let simulator = Simulator::builder()
+            .spring_neutral_length(10.0)
+            .rope_segment_length(3.0)
+            .build(graph);
+        assert!(simulator.rigid_bodies.read().unwrap().len() > 3);
+
+        let (graph, _) = from_node_link_json(&to_node_link_json(&simulator)).unwrap();
+        assert_eq!(graph.node_count(), 3);
+        let edges: Vec<(usize, usize)> = graph
+            .edge_references()
+            .map(|e| (e.source().index(), e.target().index()))
+            .collect();
+        assert_eq!(edges, vec![(0, 1), (2, 1)]);
+    }
+}
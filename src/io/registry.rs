@@ -0,0 +1,225 @@
+use std::{collections::HashMap, fs, path::Path, sync::Arc};
+
+use petgraph::{prelude::StableGraph, Directed};
+
+use crate::simulator::Simulator;
+
+use super::{read_with_progress, LoadError};
+
+/// Parses a graph file format, see `FormatRegistry::register_importer`
+pub trait GraphImporter: Send + Sync {
+    /// Lowercase file extensions of the format without the dot, e.g. `["dot", "gv"]`
+    fn extensions(&self) -> Vec<String>;
+
+    /// Parses a graph, nodes are labeled with their name in the file
+    fn import(&self, content: &str) -> Result<StableGraph<String, (), Directed, u32>, LoadError>;
+}
+
+/// Writes the graph and current layout of a simulation, see `FormatRegistry::register_exporter`
+pub trait LayoutExporter: Send + Sync {
+    /// Lowercase file extensions of the format without the dot
+    fn extensions(&self) -> Vec<String>;
+
+    fn export(&self, simulator: &Simulator) -> String;
+}
+
+/// Importers and exporters by file extension.
+///
+/// `FormatRegistry::default()` contains the formats of this crate, formats registered later
+/// replace them for the same extension.
+#[derive(Clone)]
+pub struct FormatRegistry {
+    importers: HashMap<String, Arc<dyn GraphImporter>>,
+    exporters: HashMap<String, Arc<dyn LayoutExporter>>,
+}
+
+impl FormatRegistry {
+    /// Registry without any formats
+    pub fn empty() -> Self {
+        Self {
+            importers: HashMap::new(),
+            exporters: HashMap::new(),
+        }
+    }
+
+    pub fn register_importer(&mut self, importer: Arc<dyn GraphImporter>) {
+        for extension in importer.extensions() {
+            self.importers
+                .insert(extension.to_lowercase(), Arc::clone(&importer));
+        }
+    }
+
+    pub fn register_exporter(&mut self, exporter: Arc<dyn LayoutExporter>) {
+        for extension in exporter.extensions() {
+            self.exporters
+                .insert(extension.to_lowercase(), Arc::clone(&exporter));
+        }
+    }
+
+    pub fn importer(&self, extension: &str) -> Option<&Arc<dyn GraphImporter>> {
+        self.importers.get(&extension.to_lowercase())
+    }
+
+    pub fn exporter(&self, extension: &str) -> Option<&Arc<dyn LayoutExporter>> {
+        self.exporters.get(&extension.to_lowercase())
+    }
+
+    /// Extensions that can be loaded, sorted
+    pub fn import_extensions(&self) -> Vec<&str> {
+        let mut extensions: Vec<&str> = self.importers.keys().map(String::as_str).collect();
+        extensions.sort_unstable();
+        extensions
+    }
+
+    /// Like `io::load_file_with_progress`, using the importer registered for the extension
+    pub fn load_file_with_progress<P, F>(
+        &self,
+        path: P,
+        progress: F,
+    ) -> Result<StableGraph<String, (), Directed, u32>, LoadError>
+    where
+        P: AsRef<Path>,
+        F: FnMut(f32),
+    {
+        let path = path.as_ref();
+        let extension = extension(path);
+        let importer = self
+            .importer(&extension)
+            .ok_or(LoadError::UnsupportedFormat(extension))?;
+        importer.import(&read_with_progress(path, progress)?)
+    }
+
+    /// Writes the layout of `simulator` with the exporter registered for the extension of `path`
+    pub fn save_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        simulator: &Simulator,
+    ) -> Result<(), LoadError> {
+        let path = path.as_ref();
+        let extension = extension(path);
+        let exporter = self
+            .exporter(&extension)
+            .ok_or(LoadError::UnsupportedFormat(extension))?;
+        fs::write(path, exporter.export(simulator))?;
+        Ok(())
+    }
+}
+
+impl Default for FormatRegistry {
+    fn default() -> Self {
+        type Parser = fn(&str) -> Result<StableGraph<String, (), Directed, u32>, LoadError>;
+        type Writer = fn(&Simulator) -> String;
+
+        let mut registry = Self::empty();
+        let importers: Vec<(&[&str], Parser)> = vec![
+            (&["csv"], super::from_csv_str),
+            (&["dot", "gv"], super::from_dot),
+            (&["graphml"], super::from_graphml_str),
+            (&["gexf"], super::from_gexf_str),
+            #[cfg(feature = "serde")]
+            (&["json"], |json| Ok(super::from_node_link_json(json)?.0)),
+        ];
+        for (extensions, parse) in importers {
+            registry.register_importer(Arc::new(Builtin(extensions, parse)));
+        }
+
+        let exporters: Vec<(&[&str], Writer)> = vec![
+            (&["dot", "gv"], super::to_dot),
+            #[cfg(feature = "serde")]
+            (&["json"], super::to_node_link_json),
+        ];
+        for (extensions, write) in exporters {
+            registry.register_exporter(Arc::new(Builtin(extensions, write)));
+        }
+        registry
+    }
+}
+
+impl std::fmt::Debug for FormatRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut exporters: Vec<&str> = self.exporters.keys().map(String::as_str).collect();
+        exporters.sort_unstable();
+        f.debug_struct("FormatRegistry")
+            .field("importers", &self.import_extensions())
+            .field("exporters", &exporters)
+            .finish()
+    }
+}
+
+/// Format of this crate implemented by a function
+struct Builtin<F>(&'static [&'static str], F);
+
+impl GraphImporter
+    for Builtin<fn(&str) -> Result<StableGraph<String, (), Directed, u32>, LoadError>>
+{
+    fn extensions(&self) -> Vec<String> {
+        self.0.iter().map(|e| e.to_string()).collect()
+    }
+
+    fn import(&self, content: &str) -> Result<StableGraph<String, (), Directed, u32>, LoadError> {
+        (self.1)(content)
+    }
+}
+
+impl LayoutExporter for Builtin<fn(&Simulator) -> String> {
+    fn extensions(&self) -> Vec<String> {
+        self.0.iter().map(|e| e.to_string()).collect()
+    }
+
+    fn export(&self, simulator: &Simulator) -> String {
+        (self.1)(simulator)
+    }
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Every line is a node, nodes on consecutive lines are connected
+    struct Lines;
+
+    impl GraphImporter for Lines {
+        fn extensions(&self) -> Vec<String> {
+            vec!["lines".to_string()]
+        }
+
+        fn import(
+            &self,
+            content: &str,
+        ) -> Result<StableGraph<String, (), Directed, u32>, LoadError> {
+            let mut graph = StableGraph::default();
+            let nodes: Vec<_> = content
+                .lines()
+                .map(|l| graph.add_node(l.to_string()))
+                .collect();
+            for pair in nodes.windows(2) {
+                graph.add_edge(pair[0], pair[1], ());
+            }
+            Ok(graph)
+        }
+    }
+
+    #[test]
+    fn test_register_importer() {
+        let path = std::env::temp_dir().join("grapher_test_register_importer.LINES");
+        fs::write(&path, "a\nb\nc\n").unwrap();
+
+        let mut registry = FormatRegistry::default();
+        assert!(matches!(
+            registry.load_file_with_progress(&path, |_| ()),
+            Err(LoadError::UnsupportedFormat(_))
+        ));
+        registry.register_importer(Arc::new(Lines));
+        let graph = registry.load_file_with_progress(&path, |_| ()).unwrap();
+        assert_eq!(graph.edge_count(), 2);
+        assert!(registry.import_extensions().contains(&"csv"));
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,185 @@
+//! Initial placement of the nodes, see `SimulatorBuilder::initial_layout`.
+
+use std::{f32::consts::TAU, ops::Range};
+
+use glam::Vec2;
+use rand::{rngs::StdRng, Rng};
+
+/// Half side length of the square new components are placed in when they are merged into a
+/// running simulation
+pub(crate) const RANDOM_PLACEMENT: f32 = 60.0;
+const SPECTRAL_ITERATIONS: usize = 200;
+
+/// Where the nodes start before the simulation moves them.
+///
+/// A start close to the final layout converges much faster than a random one.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InitialLayout {
+    /// Uniformly random in the square `range` x `range`
+    Random(Range<f32>),
+    /// Evenly spaced on a circle, in node order
+    Circle,
+    /// On a square grid, in node order
+    Grid,
+    /// By the two smallest non-trivial eigenvectors of the graph Laplacian, which places connected
+    /// nodes close to each other
+    Spectral,
+    /// At the given positions by node index, nodes without a position are placed randomly
+    FromPositions(Vec<Vec2>),
+}
+
+impl Default for InitialLayout {
+    fn default() -> Self {
+        InitialLayout::Random(-RANDOM_PLACEMENT..RANDOM_PLACEMENT)
+    }
+}
+
+impl InitialLayout {
+    /// Positions of `node_count` nodes connected by `edges`, roughly `spacing` apart
+    pub(crate) fn positions(
+        &self,
+        node_count: usize,
+        edges: &[(usize, usize)],
+        spacing: f32,
+        rng: &mut StdRng,
+    ) -> Vec<Vec2> {
+        match self {
+            InitialLayout::Random(range) => random(node_count, range, rng),
+            InitialLayout::Circle => {
+                let radius = node_count as f32 * spacing / TAU;
+                (0..node_count)
+                    .map(|i| Vec2::from_angle(i as f32 / node_count as f32 * TAU) * radius)
+                    .collect()
+            }
+            InitialLayout::Grid => {
+                let columns = (node_count as f32).sqrt().ceil().max(1.0) as usize;
+                let offset = (columns - 1) as f32 * spacing / 2.0;
+                (0..node_count)
+                    .map(|i| {
+                        Vec2::new((i % columns) as f32, (i / columns) as f32) * spacing
+                            - Vec2::splat(offset)
+                    })
+                    .collect()
+            }
+            InitialLayout::Spectral => spectral(node_count, edges, spacing, rng),
+            InitialLayout::FromPositions(positions) => {
+                let default_range = -RANDOM_PLACEMENT..RANDOM_PLACEMENT;
+                let missing = node_count.saturating_sub(positions.len());
+                positions
+                    .iter()
+                    .copied()
+                    .take(node_count)
+                    .chain(random(missing, &default_range, rng))
+                    .collect()
+            }
+        }
+    }
+}
+
+fn random(node_count: usize, range: &Range<f32>, rng: &mut StdRng) -> Vec<Vec2> {
+    (0..node_count)
+        .map(|_| Vec2::new(rng.gen_range(range.clone()), rng.gen_range(range.clone())))
+        .collect()
+}
+
+/// Orthogonal iteration on `c * I - L`, whose largest eigenvectors are the smallest eigenvectors
+/// of the Laplacian `L`. The constant eigenvector is projected out.
+fn spectral(
+    node_count: usize,
+    edges: &[(usize, usize)],
+    spacing: f32,
+    rng: &mut StdRng,
+) -> Vec<Vec2> {
+    if node_count < 3 {
+        return random(node_count, &(-spacing..spacing), rng);
+    }
+
+    let mut degree = vec![0.0f32; node_count];
+    for &(a, b) in edges.iter().filter(|(a, b)| a != b) {
+        degree[a] += 1.0;
+        degree[b] += 1.0;
+    }
+    // Larger than the largest eigenvalue of the Laplacian, which is at most twice the max degree
+    let shift = 2.0 * degree.iter().copied().fold(1.0, f32::max) + 1.0;
+
+    let mut vectors: [Vec<f32>; 2] =
+        [0, 1].map(|_| (0..node_count).map(|_| rng.gen_range(-1.0..1.0)).collect());
+    for _ in 0..SPECTRAL_ITERATIONS {
+        for k in 0..vectors.len() {
+            let v = &vectors[k];
+            // (c * I - L) v = c * v - D v + A v
+            let mut next: Vec<f32> = (0..node_count)
+                .map(|i| (shift - degree[i]) * v[i])
+                .collect();
+            for &(a, b) in edges.iter().filter(|(a, b)| a != b) {
+                next[a] += v[b];
+                next[b] += v[a];
+            }
+
+            let mean = next.iter().sum::<f32>() / node_count as f32;
+            next.iter_mut().for_each(|x| *x -= mean);
+            for previous in &vectors[..k] {
+                let dot: f32 = next.iter().zip(previous).map(|(x, p)| x * p).sum();
+                next.iter_mut()
+                    .zip(previous)
+                    .for_each(|(x, p)| *x -= dot * p);
+            }
+            let norm = next.iter().map(|x| x * x).sum::<f32>().sqrt();
+            if norm > f32::EPSILON {
+                next.iter_mut().for_each(|x| *x /= norm);
+            }
+            vectors[k] = next;
+        }
+    }
+
+    // Normalized entries are around 1 / sqrt(n), the layout should span around sqrt(n) * spacing.
+    // The jitter separates nodes the eigenvectors can't tell apart, e.g. of other components.
+    let scale = node_count as f32 * spacing;
+    (0..node_count)
+        .map(|i| {
+            let jitter = Vec2::new(rng.gen_range(-0.1..0.1), rng.gen_range(-0.1..0.1)) * spacing;
+            Vec2::new(vectors[0][i], vectors[1][i]) * scale + jitter
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_layouts() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let random = InitialLayout::Random(-1.0..1.0).positions(10, &[], 1.0, &mut rng);
+        assert_eq!(random.len(), 10);
+        assert!(random.iter().all(|p| p.abs().max_element() <= 1.0));
+
+        let circle = InitialLayout::Circle.positions(4, &[], TAU, &mut rng);
+        assert!(circle.iter().all(|p| (p.length() - 4.0).abs() < 1e-4));
+
+        let grid = InitialLayout::Grid.positions(4, &[], 2.0, &mut rng);
+        assert_eq!(grid[0], Vec2::new(-1.0, -1.0));
+        assert_eq!(grid[3], Vec2::new(1.0, 1.0));
+
+        let given = vec![Vec2::ONE, Vec2::NEG_ONE];
+        let from_positions = InitialLayout::FromPositions(given).positions(3, &[], 1.0, &mut rng);
+        assert_eq!(from_positions[..2], [Vec2::ONE, Vec2::NEG_ONE]);
+        assert_eq!(from_positions.len(), 3);
+    }
+
+    #[test]
+    fn test_spectral_layout_keeps_neighbors_close() {
+        let mut rng = StdRng::seed_from_u64(0);
+        // A path, its ends should end up far apart
+        let edges: Vec<(usize, usize)> = (0..19).map(|i| (i, i + 1)).collect();
+        let positions = InitialLayout::Spectral.positions(20, &edges, 1.0, &mut rng);
+
+        let longest_edge = edges
+            .iter()
+            .map(|(a, b)| positions[*a].distance(positions[*b]))
+            .fold(0.0, f32::max);
+        assert!(positions[0].distance(positions[19]) > 4.0 * longest_edge);
+    }
+}
@@ -1,7 +1,6 @@
 //! # Example
 //! ```no_run
-//!use grapher::renderer::Renderer;
-//!use grapher::simulator::SimulatorBuilder;
+//!use grapher::prelude::*;
 //!use petgraph::Directed;
 //!
 //!let mut rng = rand::thread_rng();
@@ -17,7 +16,27 @@
 //!renderer.create_window();
 //! ```
 
+pub mod analysis;
+pub mod coarsen;
+pub(crate) mod debug;
+pub(crate) mod edge_grid;
+#[cfg(feature = "egui_widget")]
+pub mod egui_widget;
+pub mod io;
+pub(crate) mod layout;
+#[cfg(feature = "net")]
+pub mod net;
+pub mod prelude;
 pub mod properties;
 pub mod quadtree;
+#[cfg(feature = "raster")]
+pub mod raster;
 pub mod renderer;
+#[cfg(feature = "renderer_tui")]
+pub mod renderer_tui;
 pub mod simulator;
+pub mod stream;
+pub mod tune;
+
+pub use debug::{check_determinism, Divergence};
+pub use layout::InitialLayout;
@@ -0,0 +1,226 @@
+//! Lets programs in any language push nodes and edges into a running simulation over TCP.
+//!
+//! Every line a client sends is a JSON `GraphUpdate`, missing fields are empty:
+//! ```text
+//! {"add_nodes": 2, "add_edges": [[0, 1], [1, 2]]}
+//! {"remove_nodes": [0]}
+//! ```
+//! Lines that can't be parsed are answered with a JSON object with an `error` field, valid lines
+//! are not answered.
+//!
+//! # Example
+//! ```no_run
+//!use grapher::net::NetFeed;
+//!use grapher::prelude::*;
+//!use petgraph::{prelude::StableGraph, Directed};
+//!
+//!let feed = NetFeed::bind("127.0.0.1:7878").unwrap();
+//!let graph: StableGraph<(), (), Directed> = StableGraph::new();
+//!let mut renderer = Renderer::new(SimulatorBuilder::new().build(graph));
+//!renderer.set_stream(feed);
+//!renderer.create_window();
+//! ```
+
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Write},
+    net::{Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+use serde_json::json;
+
+use crate::{simulator::GraphUpdate, stream::GraphStream};
+
+/// Listens for clients sending `GraphUpdate`s, see the module documentation.
+///
+/// Add it to the renderer with `Renderer::set_stream`, or apply `poll_updates` to a `Simulator`
+/// with `Simulator::apply`.
+#[derive(Debug)]
+pub struct NetFeed {
+    receiver: Receiver<GraphUpdate>,
+    local_addr: SocketAddr,
+    closed: Arc<AtomicBool>,
+    clients: Clients,
+    listener: Option<JoinHandle<()>>,
+}
+
+/// Streams of the connected clients by id, shut down when the feed is dropped
+type Clients = Arc<Mutex<HashMap<u64, TcpStream>>>;
+
+impl NetFeed {
+    /// Listens on `addr` in a background thread, each client is read in its own thread
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        let (sender, receiver) = mpsc::channel();
+        let closed = Arc::new(AtomicBool::new(false));
+        let clients: Clients = Arc::default();
+
+        let listener_closed = Arc::clone(&closed);
+        let listener_clients = Arc::clone(&clients);
+        let listener = thread::spawn(move || {
+            let mut next_id = 0;
+            for stream in listener.incoming() {
+                if listener_closed.load(Ordering::Relaxed) {
+                    return;
+                }
+                match stream.and_then(|stream| Ok((stream.try_clone()?, stream))) {
+                    Ok((handle, stream)) => {
+                        let id = next_id;
+                        next_id += 1;
+                        listener_clients.lock().unwrap().insert(id, handle);
+                        let sender = sender.clone();
+                        let clients = Arc::clone(&listener_clients);
+                        thread::spawn(move || {
+                            read_updates(stream, sender);
+                            clients.lock().unwrap().remove(&id);
+                        });
+                    }
+                    Err(e) => eprintln!("Failed to accept a graph feed client: {}", e),
+                }
+            }
+        });
+
+        Ok(Self {
+            receiver,
+            local_addr,
+            closed,
+            clients,
+            listener: Some(listener),
+        })
+    }
+
+    /// Address the feed listens on, e.g. to find the port when bound to port 0
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+impl GraphStream for NetFeed {
+    fn poll_updates(&mut self) -> Vec<GraphUpdate> {
+        self.receiver.poll_updates()
+    }
+}
+
+impl Drop for NetFeed {
+    /// Wakes the listener thread with a connection and waits for it to release the port, then
+    /// shuts down the client streams so their threads end
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::Relaxed);
+
+        let mut wake_addr = self.local_addr;
+        if wake_addr.ip().is_unspecified() {
+            wake_addr.set_ip(match wake_addr {
+                SocketAddr::V4(_) => Ipv4Addr::LOCALHOST.into(),
+                SocketAddr::V6(_) => Ipv6Addr::LOCALHOST.into(),
+            });
+        }
+        if let Some(listener) = self.listener.take() {
+            // Without the wake up connection the listener would block forever
+            if TcpStream::connect(wake_addr).is_ok() {
+                let _ = listener.join();
+            }
+        }
+
+        for client in self.clients.lock().unwrap().values() {
+            let _ = client.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+/// Sends the updates of `stream` until it is closed or the feed is dropped
+fn read_updates(stream: TcpStream, sender: Sender<GraphUpdate>) {
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(e) => {
+            eprintln!("Failed to read a graph feed client: {}", e);
+            return;
+        }
+    };
+
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<GraphUpdate>(&line) {
+            Ok(update) => {
+                if sender.send(update).is_err() {
+                    return;
+                }
+            }
+            Err(e) => {
+                let reply = json!({ "error": e.to_string() });
+                if writeln!(writer, "{}", reply).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_feed_receives_json_lines() {
+        let mut feed = NetFeed::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(feed.local_addr()).unwrap();
+        writeln!(client, r#"{{"add_nodes": 2, "add_edges": [[0, 1]]}}"#).unwrap();
+        writeln!(client, "not json").unwrap();
+        writeln!(client, r#"{{"remove_nodes": [0]}}"#).unwrap();
+
+        let mut updates = vec![];
+        let start = Instant::now();
+        while updates.len() < 2 && start.elapsed() < Duration::from_secs(5) {
+            updates.extend(feed.poll_updates());
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(
+            updates,
+            vec![
+                GraphUpdate {
+                    add_nodes: 2,
+                    add_edges: vec![(0, 1)],
+                    ..Default::default()
+                },
+                GraphUpdate {
+                    remove_nodes: vec![0],
+                    ..Default::default()
+                },
+            ]
+        );
+
+        let mut reply = String::new();
+        BufReader::new(client).read_line(&mut reply).unwrap();
+        assert!(reply.contains("\"error\""));
+    }
+
+    #[test]
+    fn test_drop_releases_port_and_clients() {
+        let feed = NetFeed::bind("127.0.0.1:0").unwrap();
+        let addr = feed.local_addr();
+        let client = TcpStream::connect(addr).unwrap();
+        writeln!(&client, r#"{{"add_nodes": 1}}"#).unwrap();
+        thread::sleep(Duration::from_millis(50));
+
+        drop(feed);
+
+        client
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut rest = String::new();
+        assert_eq!(BufReader::new(&client).read_line(&mut rest).unwrap(), 0);
+        assert!(NetFeed::bind(addr).is_ok());
+    }
+}
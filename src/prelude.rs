@@ -0,0 +1,15 @@
+//! Commonly used types
+//!
+//! ```
+//! use grapher::prelude::*;
+//! ```
+
+pub use crate::io;
+pub use crate::layout::InitialLayout;
+pub use crate::properties::{EdgeKind, GravityWell, Obstacle, ObstacleShape};
+pub use crate::renderer::{ColorScheme, KeyBindings, Renderer, RendererBuilder, WindowMode};
+pub use crate::simulator::{
+    GraphChange, GraphUpdate, GravityMode, MassMode, SimulationParameters, Simulator,
+    SimulatorBuilder, SimulatorMetrics, SimulatorSnapshot, StepInfo, StepStats,
+};
+pub use crate::stream::GraphStream;
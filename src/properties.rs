@@ -1,11 +1,17 @@
 use glam::Vec2;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RigidBody2D {
     pub position: Vec2,
     pub velocity: Vec2,
     pub mass: f32,
+    pub charge: f32,
     pub fixed: bool,
+    pub frozen: bool,
+    /// Intermediate particle of a rope edge instead of a node of the graph
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rope: bool,
 }
 
 impl RigidBody2D {
@@ -14,7 +20,20 @@ impl RigidBody2D {
             position,
             velocity: Vec2::ZERO,
             mass,
+            charge: 1.0,
             fixed: false,
+            frozen: false,
+            rope: false,
+        }
+    }
+
+    /// Creates a rigid body whose repulsion is scaled by `charge`.
+    ///
+    /// A negative charge attracts nodes with a positive charge.
+    pub fn with_charge(position: Vec2, mass: f32, charge: f32) -> Self {
+        Self {
+            charge,
+            ..Self::new(position, mass)
         }
     }
 
@@ -24,9 +43,153 @@ impl RigidBody2D {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Spring {
     pub rb1: usize,
     pub rb2: usize,
     pub spring_stiffness: f32,
     pub spring_neutral_len: f32,
+    /// Damps the relative velocity of the two bodies along the spring
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub damping: f32,
+    pub kind: Option<usize>,
+}
+
+/// Physics and styling shared by all springs of one edge kind
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EdgeKind {
+    pub spring_stiffness: f32,
+    pub spring_neutral_len: f32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub damping: f32,
+    pub color: Option<[f32; 4]>,
+}
+
+impl EdgeKind {
+    pub fn new(spring_stiffness: f32, spring_neutral_len: f32) -> Self {
+        Self {
+            spring_stiffness,
+            spring_neutral_len,
+            damping: 0.0,
+            color: None,
+        }
+    }
+
+    /// See `SimulatorBuilder::spring_damping`
+    pub fn with_damping(mut self, damping: f32) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
+/// Point that pulls the nodes assigned to it, e.g. one per category to get a map like layout
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GravityWell {
+    pub position: Vec2,
+    pub strength: f32,
+}
+
+impl GravityWell {
+    pub fn new(position: Vec2, strength: f32) -> Self {
+        Self { position, strength }
+    }
+
+    /// `count` wells evenly spaced on a circle around the origin
+    pub fn circle(count: usize, radius: f32, strength: f32) -> Vec<Self> {
+        (0..count)
+            .map(|i| {
+                let angle = i as f32 / count as f32 * std::f32::consts::TAU;
+                Self::new(Vec2::from_angle(angle) * radius, strength)
+            })
+            .collect()
+    }
+}
+
+/// Area of an `Obstacle`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ObstacleShape {
+    /// Axis aligned rectangle
+    Rectangle {
+        center: Vec2,
+        size: Vec2,
+    },
+    Circle {
+        center: Vec2,
+        radius: f32,
+    },
+}
+
+/// Region that pushes nodes out, e.g. an area reserved for UI or water on a map, so the layout
+/// flows around it
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Obstacle {
+    pub shape: ObstacleShape,
+    pub strength: f32,
+    /// Distance outside the shape from which nodes are already pushed away
+    pub margin: f32,
+}
+
+impl Obstacle {
+    pub fn rectangle(center: Vec2, size: Vec2, strength: f32) -> Self {
+        Self {
+            shape: ObstacleShape::Rectangle { center, size },
+            strength,
+            margin: 0.0,
+        }
+    }
+
+    pub fn circle(center: Vec2, radius: f32, strength: f32) -> Self {
+        Self {
+            shape: ObstacleShape::Circle { center, radius },
+            strength,
+            margin: 0.0,
+        }
+    }
+
+    pub fn with_margin(mut self, margin: f32) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Push on a body with a mass of `1` at `position`. It points out of the shape and grows
+    /// with the depth of the body inside the shape and margin, like a spring.
+    pub fn force(&self, position: Vec2) -> Vec2 {
+        let (distance, normal) = self.signed_distance(position);
+        if distance >= self.margin {
+            return Vec2::ZERO;
+        }
+        normal * self.strength * (self.margin - distance)
+    }
+
+    /// Distance of `position` to the border, negative inside, and the direction out of the shape
+    fn signed_distance(&self, position: Vec2) -> (f32, Vec2) {
+        match self.shape {
+            ObstacleShape::Circle { center, radius } => {
+                let offset = position - center;
+                let length = offset.length();
+                (length - radius, offset.try_normalize().unwrap_or(Vec2::X))
+            }
+            ObstacleShape::Rectangle { center, size } => {
+                let offset = position - center;
+                let overlap = offset.abs() - size / 2.0;
+                if overlap.max_element() > 0.0 {
+                    let outside = overlap.max(Vec2::ZERO) * offset.signum();
+                    (outside.length(), outside.normalize())
+                } else if overlap.x > overlap.y {
+                    (overlap.x, Vec2::new(offset.x.signum(), 0.0))
+                } else {
+                    (overlap.y, Vec2::new(0.0, offset.y.signum()))
+                }
+            }
+        }
+    }
 }
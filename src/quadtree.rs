@@ -1,26 +1,47 @@
-use std::borrow::BorrowMut;
+use std::{borrow::BorrowMut, fmt::Write};
 
 use glam::Vec2;
+use rand::seq::index;
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+
+use crate::{properties::RigidBody2D, simulator::Simulator};
 
 const EPSILON: f32 = 1e-3;
+const ERROR_SAMPLE_SIZE: usize = 100;
+/// Bodies below which `from_rigid_bodies_par` builds the tree on one thread
+const PARALLEL_BUILD_THRESHOLD: usize = 4096;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct QuadTree {
     pub children: Vec<Node>,
     pub boundary: BoundingBox2D,
     pub root: u32,
+    /// First body stored in each leaf of `children`, `u32::MAX` for roots
+    bodies: Vec<u32>,
+    /// Next body in the same leaf, indexed by insertion order
+    next_body: Vec<u32>,
 }
 
+/// Cell of a `QuadTree`
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Node {
+    /// Cell split into four quadrants
     Root {
+        /// Index into `QuadTree::children` of the node of every quadrant, `u32::MAX` if empty
         indices: [u32; 4],
-        mass: f32,
-        pos: Vec2,
+        center_of_mass: Vec2,
+        /// Sum of the masses of all bodies in the cell
+        total_mass: f32,
+        /// Sum of `mass * charge` of all bodies in the cell
+        charge: f32,
     },
+    /// One body, or several bodies closer than `EPSILON` that were merged
     Leaf {
-        mass: f32,
-        pos: Vec2,
+        center_of_mass: Vec2,
+        total_mass: f32,
+        charge: f32,
     },
 }
 
@@ -30,6 +51,8 @@ impl QuadTree {
             root: 0,
             boundary,
             children: Vec::new(),
+            bodies: Vec::new(),
+            next_body: Vec::new(),
         }
     }
 
@@ -38,11 +61,159 @@ impl QuadTree {
             root: 0,
             boundary,
             children: Vec::with_capacity(capacity),
+            bodies: Vec::with_capacity(capacity),
+            next_body: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Top node of the tree, `None` if it is empty
+    pub fn root_node(&self) -> Option<&Node> {
+        self.children.get(self.root as usize)
+    }
+
+    /// Node at `index` of `children`, e.g. one of `Node::child_indices`
+    pub fn node(&self, index: u32) -> Option<&Node> {
+        self.children.get(index as usize)
+    }
+
+    /// Builds a quadtree whose boundary encloses all `bodies`
+    pub fn from_rigid_bodies(bodies: &[RigidBody2D]) -> Self {
+        let mut quadtree = QuadTree::with_capacity(enclosing_boundary(bodies), bodies.len());
+
+        for rb in bodies.iter() {
+            quadtree.insert_with_charge(rb.position, rb.mass, rb.charge);
+        }
+        quadtree
+    }
+
+    /// Like `from_rigid_bodies`, but builds the subtree of each quadrant of the boundary in parallel
+    /// on the current rayon thread pool and joins them under one root.
+    ///
+    /// The tree has the same structure and insertion indices as the one of `from_rigid_bodies`.
+    pub fn from_rigid_bodies_par(bodies: &[RigidBody2D]) -> Self {
+        if !Self::builds_in_parallel(bodies.len()) {
+            return Self::from_rigid_bodies(bodies);
+        }
+
+        let boundary = enclosing_boundary(bodies);
+        let mut sections: [Vec<u32>; 4] = Default::default();
+        for (i, rb) in bodies.iter().enumerate() {
+            sections[boundary.section(&rb.position) as usize].push(i as u32);
+        }
+
+        let subtrees: Vec<(QuadTree, Vec<u32>)> = sections
+            .into_par_iter()
+            .enumerate()
+            .map(|(section, members)| {
+                let mut subtree =
+                    QuadTree::with_capacity(boundary.sub_quadrant(section as u8), members.len());
+                for i in members.iter() {
+                    let rb = &bodies[*i as usize];
+                    subtree.insert_with_charge(rb.position, rb.mass, rb.charge);
+                }
+                (subtree, members)
+            })
+            .collect();
+
+        let mut quadtree = QuadTree::with_capacity(boundary, bodies.len() * 2);
+        quadtree.next_body = vec![u32::MAX; bodies.len()];
+        quadtree
+            .children
+            .push(Node::new_root(Vec2::ZERO, 0.0, 0.0, [u32::MAX; 4]));
+        quadtree.bodies.push(u32::MAX);
+
+        let (mut root_center, mut root_mass, mut root_charge) = (Vec2::ZERO, 0.0, 0.0);
+        let mut root_indices = [u32::MAX; 4];
+        for (section, (subtree, members)) in subtrees.into_iter().enumerate() {
+            let Some(root) = subtree.children.get(subtree.root as usize) else {
+                continue;
+            };
+            root_center = weighted_center(
+                root_center,
+                root_mass,
+                root.center_of_mass(),
+                root.total_mass(),
+            );
+            root_mass += root.total_mass();
+            root_charge += root.charge();
+
+            // Move the nodes behind the ones of the previous subtrees
+            let offset = quadtree.children.len() as u32;
+            root_indices[section] = offset + subtree.root;
+            let global = |body: u32| {
+                if body == u32::MAX {
+                    u32::MAX
+                } else {
+                    members[body as usize]
+                }
+            };
+            for (node, first_body) in subtree.children.into_iter().zip(subtree.bodies) {
+                quadtree.children.push(match node {
+                    Node::Root {
+                        indices,
+                        center_of_mass,
+                        total_mass,
+                        charge,
+                    } => Node::new_root(
+                        center_of_mass,
+                        total_mass,
+                        charge,
+                        indices.map(|i| if i == u32::MAX { i } else { offset + i }),
+                    ),
+                    leaf => leaf,
+                });
+                quadtree.bodies.push(global(first_body));
+            }
+            for (body, next) in subtree.next_body.into_iter().enumerate() {
+                quadtree.next_body[members[body] as usize] = global(next);
+            }
         }
+
+        quadtree.children[0] = Node::new_root(root_center, root_mass, root_charge, root_indices);
+        quadtree
+    }
+
+    /// Rebuilds the tree for `bodies` like `from_rigid_bodies`, but reuses the allocations of the
+    /// previous build, e.g. to build a tree every simulation step without allocating
+    pub fn rebuild(&mut self, bodies: &[RigidBody2D]) {
+        self.clear();
+        self.boundary = enclosing_boundary(bodies);
+
+        for rb in bodies.iter() {
+            self.insert_with_charge(rb.position, rb.mass, rb.charge);
+        }
+    }
+
+    /// Removes all bodies and keeps the allocations
+    pub fn clear(&mut self) {
+        self.children.clear();
+        self.bodies.clear();
+        self.next_body.clear();
+        self.root = 0;
+    }
+
+    /// Number of bodies inserted since the tree was created or cleared
+    pub fn body_count(&self) -> usize {
+        self.next_body.len()
+    }
+
+    /// If `from_rigid_bodies_par` builds the tree for `body_count` bodies on more than one thread
+    pub(crate) fn builds_in_parallel(body_count: usize) -> bool {
+        body_count >= PARALLEL_BUILD_THRESHOLD
     }
 
     pub fn insert(&mut self, new_pos: Vec2, new_mass: f32) {
-        self.children.push(Node::new_leaf(new_pos, new_mass));
+        self.insert_with_charge(new_pos, new_mass, 1.0);
+    }
+
+    /// Inserts a body whose repulsion is scaled by `new_charge`
+    pub fn insert_with_charge(&mut self, new_pos: Vec2, new_mass: f32, new_charge: f32) {
+        let new_charge = new_charge * new_mass;
+        let new_body = self.next_body.len() as u32;
+        self.next_body.push(u32::MAX);
+        self.children
+            .push(Node::new_leaf(new_pos, new_mass, new_charge));
+        self.bodies.push(new_body);
         let new_index = self.children.len() as u32 - 1;
 
         // When only one node than there is no need to continue
@@ -53,12 +224,17 @@ impl QuadTree {
         let mut bb = self.boundary.clone();
         let mut root_index = self.root;
 
-        while let Node::Root { indices, mass, pos } =
-            self.children[root_index as usize].borrow_mut()
+        while let Node::Root {
+            indices,
+            center_of_mass,
+            total_mass,
+            charge,
+        } = self.children[root_index as usize].borrow_mut()
         {
-            // Update mass and Pos of root
-            *mass += new_mass;
-            *pos += new_pos * new_mass;
+            // Update mass, charge and center of mass of root
+            *center_of_mass = weighted_center(*center_of_mass, *total_mass, new_pos, new_mass);
+            *total_mass += new_mass;
+            *charge += new_charge;
 
             let section = bb.section(&new_pos);
             // If section not set: create new leaf and exit
@@ -71,23 +247,42 @@ impl QuadTree {
             bb = bb.sub_quadrant(section);
         }
 
-        // if new leaf is too close to current leaf we merge
-        // TODO: in this case we will have a "dead" leaf
-        if let Node::Leaf { mass, pos } = self.children[root_index as usize] {
+        // if new leaf is too close to current leaf we merge, the new leaf isn't referenced yet so
+        // it is removed again
+        if let Node::Leaf {
+            center_of_mass: pos,
+            total_mass: mass,
+            charge,
+        } = self.children[root_index as usize]
+        {
             if pos.distance(new_pos) < EPSILON {
-                let m: f32 = mass + new_mass;
-                self.children[root_index as usize] = Node::new_leaf(pos, m);
+                self.children.pop();
+                self.bodies.pop();
+                self.children[root_index as usize] = Node::new_leaf(
+                    weighted_center(pos, mass, new_pos, new_mass),
+                    mass + new_mass,
+                    charge + new_charge,
+                );
+                self.next_body[new_body as usize] = self.bodies[root_index as usize];
+                self.bodies[root_index as usize] = new_body;
                 return;
             }
         }
 
         // create new root until leaf and new leaf are in different sections
-        while let Node::Leaf { mass, pos } = self.children[root_index as usize] {
+        while let Node::Leaf {
+            center_of_mass: pos,
+            total_mass: mass,
+            charge,
+        } = self.children[root_index as usize]
+        {
             let mut fin = false;
 
             // Pushes the old leaf to the back of the vector and inserts its index into the index array of the new root
-            let old_node = Node::new_leaf(pos, mass);
+            let old_node = Node::new_leaf(pos, mass, charge);
             self.children.push(old_node);
+            self.bodies.push(self.bodies[root_index as usize]);
+            self.bodies[root_index as usize] = u32::MAX;
             let old_index = self.children.len() - 1;
             let section = bb.section(&pos);
             let mut ind = [u32::MAX, u32::MAX, u32::MAX, u32::MAX];
@@ -102,7 +297,12 @@ impl QuadTree {
             }
 
             // sets the old leaf index to the new root
-            let new_root = Node::new_root(pos * mass + new_pos * new_mass, mass + new_mass, ind);
+            let new_root = Node::new_root(
+                weighted_center(pos, mass, new_pos, new_mass),
+                mass + new_mass,
+                charge + new_charge,
+                ind,
+            );
             self.children[root_index as usize] = new_root;
 
             if fin {
@@ -115,90 +315,501 @@ impl QuadTree {
         }
     }
 
+    /// Nodes that act on a body at `position`, cells that are small or far away compared to
+    /// `theta` are approximated as one node
     pub fn stack<'a>(&'a self, position: &Vec2, theta: f32) -> Vec<&'a Node> {
-        let mut nodes: Vec<&Node> =
-            Vec::with_capacity((self.children.len() as f32).log2() as usize);
+        let mut nodes = vec![];
+        self.for_each_approx(position, theta, |node| nodes.push(node));
+        nodes
+    }
 
-        let mut s: f32 = self.boundary.width.max(self.boundary.height);
+    /// Like `stack`, but leaves out all nodes further than `cutoff` from `position` and doesn't
+    /// descend into branches whose boundary is further away
+    pub fn stack_within<'a>(&'a self, position: &Vec2, theta: f32, cutoff: f32) -> Vec<&'a Node> {
+        let mut nodes = vec![];
+        self.for_each_approx_within(position, theta, cutoff, |node| nodes.push(node));
+        nodes
+    }
 
+    /// Calls `f` with every node of `stack` without collecting them, the force loop runs it for
+    /// every body in every step
+    pub fn for_each_approx<'a>(&'a self, position: &Vec2, theta: f32, f: impl FnMut(&'a Node)) {
+        self.for_each_approx_within(position, theta, f32::INFINITY, f);
+    }
+
+    /// Calls `f` with every node of `stack_within` without collecting them
+    pub fn for_each_approx_within<'a>(
+        &'a self,
+        position: &Vec2,
+        theta: f32,
+        cutoff: f32,
+        mut f: impl FnMut(&'a Node),
+    ) {
+        if !self.children.is_empty() {
+            self.visit_approx(self.root, &self.boundary, position, theta, cutoff, &mut f);
+        }
+    }
+
+    /// Depth first walk of `for_each_approx_within`, recursion keeps the pending branches on the
+    /// call stack instead of the heap
+    fn visit_approx<'a>(
+        &'a self,
+        node_index: u32,
+        bb: &BoundingBox2D,
+        position: &Vec2,
+        theta: f32,
+        cutoff: f32,
+        f: &mut impl FnMut(&'a Node),
+    ) {
+        if bb.distance_to(position) > cutoff {
+            return;
+        }
+        let node = &self.children[node_index as usize];
+        let dist = node.center_of_mass().distance(*position);
+
+        match node {
+            Node::Root { indices, .. } if bb.width.max(bb.height) / dist >= theta => {
+                for (section, i) in indices.iter().enumerate() {
+                    if *i != u32::MAX {
+                        let sub_bb = bb.sub_quadrant(section as u8);
+                        self.visit_approx(*i, &sub_bb, position, theta, cutoff, f);
+                    }
+                }
+            }
+            _ => {
+                if dist <= cutoff {
+                    f(node);
+                }
+            }
+        }
+    }
+
+    /// Number of levels of the tree, `0` if it is empty
+    pub fn depth(&self) -> u32 {
         if self.children.is_empty() {
-            return vec![];
+            return 0;
+        }
+
+        let mut depth = 0;
+        let mut stack = vec![(self.root, 1)];
+        while let Some((node_index, level)) = stack.pop() {
+            depth = depth.max(level);
+            if let Node::Root { indices, .. } = &self.children[node_index as usize] {
+                for i in indices {
+                    if *i != u32::MAX {
+                        stack.push((*i, level + 1));
+                    }
+                }
+            }
         }
+        depth
+    }
 
-        let mut stack: Vec<u32> = vec![0];
-        let mut new_stack: Vec<u32> = Vec::with_capacity(2);
-        'outer: loop {
-            for node_index in stack {
-                let parent = &self.children[node_index as usize];
+    /// Boundaries of all cells of the tree
+    pub fn cells(&self) -> Vec<BoundingBox2D> {
+        let mut cells = vec![];
+        if self.children.is_empty() {
+            return cells;
+        }
 
-                if let Node::Root { indices, .. } = parent {
-                    let center_mass = parent.position();
-                    let dist = center_mass.distance(*position);
-                    if s / dist < theta {
-                        if nodes.capacity() == nodes.len() {
-                            nodes.reserve((nodes.len() as f32 * 0.1) as usize);
+        let mut stack = vec![(self.root, self.boundary.clone())];
+        while let Some((node_index, bb)) = stack.pop() {
+            if let Node::Root { indices, .. } = &self.children[node_index as usize] {
+                for (section, i) in indices.iter().enumerate() {
+                    if *i != u32::MAX {
+                        stack.push((*i, bb.sub_quadrant(section as u8)));
+                    }
+                }
+            }
+            cells.push(bb);
+        }
+        cells
+    }
+
+    /// Boundaries of the cells with more than one body that `stack` approximates as one body for
+    /// `position`
+    pub fn approximated_cells(&self, position: &Vec2, theta: f32) -> Vec<BoundingBox2D> {
+        let mut cells = vec![];
+        if self.children.is_empty() {
+            return cells;
+        }
+
+        let mut stack = vec![(self.root, self.boundary.clone())];
+        while let Some((node_index, bb)) = stack.pop() {
+            let node = &self.children[node_index as usize];
+            if let Node::Root { indices, .. } = node {
+                if bb.width.max(bb.height) / node.center_of_mass().distance(*position) < theta {
+                    cells.push(bb);
+                } else {
+                    for (section, i) in indices.iter().enumerate() {
+                        if *i != u32::MAX {
+                            stack.push((*i, bb.sub_quadrant(section as u8)));
                         }
-                        nodes.push(parent);
-                    } else {
-                        for i in indices {
-                            if *i != u32::MAX {
-                                new_stack.push(*i);
-                            }
+                    }
+                }
+            }
+        }
+        cells
+    }
+
+    /// Returns the insertion indices of all bodies inside `range`
+    pub fn query_range(&self, range: &BoundingBox2D) -> Vec<usize> {
+        let mut result = vec![];
+        if self.children.is_empty() {
+            return result;
+        }
+
+        let mut stack = vec![(self.root, self.boundary.clone())];
+        while let Some((node_index, bb)) = stack.pop() {
+            match &self.children[node_index as usize] {
+                Node::Root { indices, .. } => {
+                    for (section, i) in indices.iter().enumerate() {
+                        let sub_bb = bb.sub_quadrant(section as u8);
+                        if *i != u32::MAX && sub_bb.intersects(range) {
+                            stack.push((*i, sub_bb));
+                        }
+                    }
+                }
+                Node::Leaf {
+                    center_of_mass: pos,
+                    ..
+                } => {
+                    if range.contains(pos) {
+                        let mut body = self.bodies[node_index as usize];
+                        while body != u32::MAX {
+                            result.push(body as usize);
+                            body = self.next_body[body as usize];
                         }
                     }
                 }
+            }
+        }
+        result
+    }
+
+    /// Insertion index of the body closest to `position`, `None` if the tree is empty
+    pub fn nearest(&self, position: &Vec2) -> Option<usize> {
+        self.nearest_where(position, |_| true)
+    }
+
+    /// Like `nearest`, but only considers bodies whose insertion index is accepted by `accept`
+    pub fn nearest_where(&self, position: &Vec2, accept: impl Fn(usize) -> bool) -> Option<usize> {
+        let mut nearest = None;
+        let mut nearest_distance = f32::INFINITY;
+        if !self.children.is_empty() {
+            self.visit_nearest(
+                self.root,
+                &self.boundary,
+                position,
+                &accept,
+                &mut nearest,
+                &mut nearest_distance,
+            );
+        }
+        nearest
+    }
 
-                if let Node::Leaf { .. } = parent {
-                    if nodes.capacity() == nodes.len() {
-                        nodes.reserve((nodes.len() as f32 * 0.1) as usize);
+    /// Branch and bound search of `nearest_where`, quadrants closer to `position` are searched
+    /// first so further ones can be skipped
+    fn visit_nearest(
+        &self,
+        node_index: u32,
+        bb: &BoundingBox2D,
+        position: &Vec2,
+        accept: &impl Fn(usize) -> bool,
+        nearest: &mut Option<usize>,
+        nearest_distance: &mut f32,
+    ) {
+        match &self.children[node_index as usize] {
+            Node::Root { indices, .. } => {
+                let mut quadrants: Vec<(f32, u32, BoundingBox2D)> = indices
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, i)| **i != u32::MAX)
+                    .map(|(section, i)| {
+                        let sub_bb = bb.sub_quadrant(section as u8);
+                        (sub_bb.distance_to(position), *i, sub_bb)
+                    })
+                    .collect();
+                quadrants.sort_by(|a, b| a.0.total_cmp(&b.0));
+                for (distance, i, sub_bb) in quadrants {
+                    if distance < *nearest_distance {
+                        self.visit_nearest(i, &sub_bb, position, accept, nearest, nearest_distance);
                     }
-                    nodes.push(parent);
                 }
             }
-            if new_stack.is_empty() {
-                break 'outer;
+            Node::Leaf { center_of_mass, .. } => {
+                let distance = center_of_mass.distance(*position);
+                if distance >= *nearest_distance {
+                    return;
+                }
+                let mut body = self.bodies[node_index as usize];
+                while body != u32::MAX {
+                    if accept(body as usize) {
+                        *nearest = Some(body as usize);
+                        *nearest_distance = distance;
+                        return;
+                    }
+                    body = self.next_body[body as usize];
+                }
             }
-            s *= 0.5;
-            stack = new_stack;
-            new_stack = Vec::with_capacity(stack.len() * 4);
         }
-        nodes
+    }
+}
+
+impl QuadTree {
+    /// Dumps the tree structure in Graphviz DOT format
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph quadtree {\n");
+
+        for (i, node) in self.children.iter().enumerate() {
+            let pos = node.center_of_mass();
+            let shape = if node.is_leaf() { "ellipse" } else { "box" };
+            writeln!(
+                dot,
+                "    {} [shape={}, label=\"{}\\nmass={}\\npos=({}, {})\"];",
+                i,
+                shape,
+                i,
+                node.total_mass(),
+                pos.x,
+                pos.y
+            )
+            .unwrap();
+
+            if let Node::Root { indices, .. } = node {
+                for (section, index) in indices.iter().enumerate() {
+                    if *index != u32::MAX {
+                        writeln!(dot, "    {} -> {} [label=\"{}\"];", i, index, section).unwrap();
+                    }
+                }
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Dumps the tree as JSON.
+    ///
+    /// With the `serde` feature this is the serialized `QuadTree`. Otherwise nodes are listed in
+    /// storage order; `children` of a root are indices into `nodes` with `null` marking an empty
+    /// section. Non-finite numbers, like the center of mass of a massless node, are `null`.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Dumps the tree as JSON.
+    ///
+    /// With the `serde` feature this is the serialized `QuadTree`. Otherwise nodes are listed in
+    /// storage order; `children` of a root are indices into `nodes` with `null` marking an empty
+    /// section. Non-finite numbers, like the center of mass of a massless node, are `null`.
+    #[cfg(not(feature = "serde"))]
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        write!(
+            json,
+            "{{\"root\":{},\"boundary\":{{\"center\":[{},{}],\"width\":{},\"height\":{}}},\"nodes\":[",
+            self.root,
+            json_number(self.boundary.center.x),
+            json_number(self.boundary.center.y),
+            json_number(self.boundary.width),
+            json_number(self.boundary.height)
+        )
+        .unwrap();
+
+        for (i, node) in self.children.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            let pos = node.center_of_mass();
+            write!(
+                json,
+                "{{\"mass\":{},\"charge\":{},\"position\":[{},{}]",
+                json_number(node.total_mass()),
+                json_number(node.charge()),
+                json_number(pos.x),
+                json_number(pos.y)
+            )
+            .unwrap();
+
+            if let Node::Root { indices, .. } = node {
+                let children: Vec<String> = indices
+                    .iter()
+                    .map(|i| {
+                        if *i == u32::MAX {
+                            "null".to_string()
+                        } else {
+                            i.to_string()
+                        }
+                    })
+                    .collect();
+                write!(json, ",\"children\":[{}]", children.join(",")).unwrap();
+            }
+            json.push('}');
+        }
+
+        json.push_str("]}");
+        json
+    }
+}
+
+/// `value` as a JSON number, `null` if it is not finite
+#[cfg(not(feature = "serde"))]
+fn json_number(value: f32) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
     }
 }
 
 impl Node {
-    fn new_leaf(pos: Vec2, mass: f32) -> Self {
-        Self::Leaf { mass, pos }
+    fn new_leaf(center_of_mass: Vec2, total_mass: f32, charge: f32) -> Self {
+        Self::Leaf {
+            center_of_mass,
+            total_mass,
+            charge,
+        }
     }
-    fn new_root(pos: Vec2, mass: f32, indices: [u32; 4]) -> Self {
-        Self::Root { indices, mass, pos }
+
+    fn new_root(center_of_mass: Vec2, total_mass: f32, charge: f32, indices: [u32; 4]) -> Self {
+        Self::Root {
+            indices,
+            center_of_mass,
+            total_mass,
+            charge,
+        }
     }
-    #[allow(dead_code)]
+
     pub fn is_leaf(&self) -> bool {
         matches!(self, Node::Leaf { .. })
     }
 
-    #[allow(dead_code)]
     pub fn is_root(&self) -> bool {
         matches!(self, Node::Root { .. })
     }
 
-    pub fn position(&self) -> Vec2 {
+    /// Mass weighted average position of all bodies in this node
+    pub fn center_of_mass(&self) -> Vec2 {
+        match self {
+            Node::Root { center_of_mass, .. } | Node::Leaf { center_of_mass, .. } => {
+                *center_of_mass
+            }
+        }
+    }
+
+    /// Sum of the masses of all bodies in this node
+    pub fn total_mass(&self) -> f32 {
+        match self {
+            Node::Root { total_mass, .. } | Node::Leaf { total_mass, .. } => *total_mass,
+        }
+    }
+
+    /// Sum of `mass * charge` of all bodies in this node
+    pub fn charge(&self) -> f32 {
         match self {
-            Node::Root { pos, mass, .. } => pos / mass,
-            Node::Leaf { pos, .. } => *pos,
+            Node::Root { charge, .. } | Node::Leaf { charge, .. } => *charge,
         }
     }
 
-    pub fn mass(&self) -> f32 {
+    /// Index into `QuadTree::children` of the node of every quadrant, see
+    /// `BoundingBox2D::sub_quadrant`. All `None` for a leaf.
+    pub fn child_indices(&self) -> [Option<u32>; 4] {
         match self {
-            Node::Root { mass, .. } => *mass,
-            Node::Leaf { mass, .. } => *mass,
+            Node::Root { indices, .. } => indices.map(|i| (i != u32::MAX).then_some(i)),
+            Node::Leaf { .. } => [None; 4],
+        }
+    }
+}
+
+/// Center of mass of two bodies, `a` if both have no mass
+fn weighted_center(a: Vec2, a_mass: f32, b: Vec2, b_mass: f32) -> Vec2 {
+    let total = a_mass + b_mass;
+    if total == 0.0 {
+        return a;
+    }
+    (a * a_mass + b * b_mass) / total
+}
+
+/// Position of `loc` along a Z-order curve through `boundary`, close positions get close codes
+pub(crate) fn morton_code(loc: Vec2, boundary: &BoundingBox2D) -> u32 {
+    let size = Vec2::new(boundary.width, boundary.height).max(Vec2::splat(f32::EPSILON));
+    let normalized = ((loc - boundary.center) / size + 0.5).clamp(Vec2::ZERO, Vec2::ONE);
+    let x = (normalized.x * u16::MAX as f32) as u16;
+    let y = (normalized.y * u16::MAX as f32) as u16;
+    spread_bits(x) | (spread_bits(y) << 1)
+}
+
+/// Moves the bits of `v` to the even bits of the result
+fn spread_bits(v: u16) -> u32 {
+    let mut v = v as u32;
+    v = (v | (v << 8)) & 0x00FF_00FF;
+    v = (v | (v << 4)) & 0x0F0F_0F0F;
+    v = (v | (v << 2)) & 0x3333_3333;
+    (v | (v << 1)) & 0x5555_5555
+}
+
+/// Smallest boundary that encloses all `bodies`
+pub(crate) fn enclosing_boundary(bodies: &[RigidBody2D]) -> BoundingBox2D {
+    let mut min = Vec2::INFINITY;
+    let mut max = Vec2::NEG_INFINITY;
+
+    for rb in bodies.iter() {
+        min = min.min(rb.position);
+        max = max.max(rb.position);
+    }
+    let dir = max - min;
+
+    BoundingBox2D::new((dir / 2.0) + min, dir[0], dir[1])
+}
+
+/// Estimates the relative error of the Barnes-Hut approximation for a given `theta`.
+///
+/// Compares the approximated repel force against the exact `n^2` force for a random sample of bodies
+/// and returns `sum(|approx - exact|) / sum(|exact|)`.
+pub fn estimate_error(bodies: &[RigidBody2D], theta: f32) -> f32 {
+    if bodies.len() < 2 {
+        return 0.0;
+    }
+
+    let quadtree = QuadTree::from_rigid_bodies(bodies);
+    let sample_size = ERROR_SAMPLE_SIZE.min(bodies.len());
+
+    let mut error_sum = 0.0;
+    let mut exact_sum = 0.0;
+
+    for i in index::sample(&mut rand::thread_rng(), bodies.len(), sample_size) {
+        let rb = &bodies[i];
+
+        let mut exact = Vec2::ZERO;
+        for other in bodies.iter() {
+            exact += Simulator::repel_force(1.0, 0.0, rb, other);
         }
+
+        let mut approx = Vec2::ZERO;
+        quadtree.for_each_approx(&rb.position, theta, |node| {
+            let particle = RigidBody2D::with_charge(
+                node.center_of_mass(),
+                node.total_mass(),
+                node.charge() / node.total_mass(),
+            );
+            approx += Simulator::repel_force(1.0, 0.0, rb, &particle);
+        });
+
+        error_sum += (approx - exact).length();
+        exact_sum += exact.length();
+    }
+
+    if exact_sum == 0.0 {
+        return 0.0;
     }
+    error_sum / exact_sum
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BoundingBox2D {
     pub center: Vec2,
     pub width: f32,
@@ -213,6 +824,26 @@ impl BoundingBox2D {
             height,
         }
     }
+    /// `true` if `loc` lies inside the box or on its border
+    pub fn contains(&self, loc: &Vec2) -> bool {
+        (loc[0] - self.center[0]).abs() <= self.width * 0.5
+            && (loc[1] - self.center[1]).abs() <= self.height * 0.5
+    }
+
+    /// Distance from `loc` to the closest point of the box, `0.0` inside
+    pub fn distance_to(&self, loc: &Vec2) -> f32 {
+        let half_size = Vec2::new(self.width, self.height) * 0.5;
+        ((*loc - self.center).abs() - half_size)
+            .max(Vec2::ZERO)
+            .length()
+    }
+
+    /// `true` if the two boxes overlap
+    pub fn intersects(&self, other: &BoundingBox2D) -> bool {
+        (self.center[0] - other.center[0]).abs() <= (self.width + other.width) * 0.5
+            && (self.center[1] - other.center[1]).abs() <= (self.height + other.height) * 0.5
+    }
+
     pub fn section(&self, loc: &Vec2) -> u8 {
         let mut section = 0x00;
 
@@ -289,8 +920,8 @@ mod test {
         let n1_mass = 5.0;
         qt.insert(Vec2::new(-1.0, -1.0), n1_mass);
         assert!(qt.children[0].is_leaf());
-        if let Node::Leaf { mass, .. } = qt.children[0] {
-            assert_eq!(mass, n1_mass);
+        if let Node::Leaf { total_mass, .. } = qt.children[0] {
+            assert_eq!(total_mass, n1_mass);
         }
 
         // Insert second node in in the same quadrant but different sub quadrant
@@ -299,8 +930,13 @@ mod test {
         qt.insert(Vec2::new(1.0, 1.0), n2_mass);
         // check root node
         assert!(qt.children[0].is_root());
-        if let Node::Root { indices, mass, .. } = qt.children[0] {
-            assert_eq!(mass, n1_mass + n2_mass);
+        if let Node::Root {
+            indices,
+            total_mass,
+            ..
+        } = qt.children[0]
+        {
+            assert_eq!(total_mass, n1_mass + n2_mass);
 
             // check node0
             assert_eq!(indices[0], 2);
@@ -311,4 +947,379 @@ mod test {
             assert!(qt.children[2].is_leaf());
         }
     }
+
+    #[test]
+    fn test_quadtree_insert_multi_level() {
+        let mut qt = QuadTree::new(BoundingBox2D::new(Vec2::ZERO, 16.0, 16.0));
+        qt.insert(Vec2::new(1.0, 1.0), 1.0);
+        qt.insert(Vec2::new(1.5, 1.5), 3.0);
+        qt.insert(Vec2::new(-4.0, 4.0), 4.0);
+
+        let root = qt.root_node().unwrap();
+        assert_eq!(root.total_mass(), 8.0);
+        assert_eq!(root.center_of_mass(), Vec2::new(-1.3125, 2.6875));
+
+        // The two close bodies are only split apart three levels below the root
+        let mut levels = 0;
+        let mut node = root;
+        while let Some(child) = node
+            .child_indices()
+            .into_iter()
+            .flatten()
+            .map(|i| qt.node(i).unwrap())
+            .find(|child| child.is_root())
+        {
+            node = child;
+            levels += 1;
+        }
+        assert_eq!(levels, 3);
+        assert_eq!(node.total_mass(), 4.0);
+        assert_eq!(node.center_of_mass(), Vec2::new(1.375, 1.375));
+        assert_eq!(qt.depth(), 5);
+    }
+
+    #[test]
+    fn test_quadtree_merge_keeps_mass() {
+        let mut qt = QuadTree::new(BoundingBox2D::new(Vec2::ZERO, 10.0, 10.0));
+        qt.insert(Vec2::new(1.0, 1.0), 1.0);
+        qt.insert(Vec2::new(-1.0, -1.0), 1.0);
+        qt.insert(Vec2::new(1.0, 1.0 + EPSILON / 2.0), 3.0);
+
+        // The merged body doesn't leave an unreachable leaf behind
+        assert_eq!(qt.children.len(), 3);
+        let total: f32 = qt
+            .children
+            .iter()
+            .filter(|n| n.is_leaf())
+            .map(Node::total_mass)
+            .sum();
+        assert_eq!(total, 5.0);
+        assert_eq!(qt.root_node().unwrap().total_mass(), 5.0);
+        assert_eq!(
+            qt.query_range(&BoundingBox2D::new(Vec2::ONE, 1.0, 1.0))
+                .len(),
+            2
+        );
+
+        let merged = qt.node(qt.root_node().unwrap().child_indices()[3].unwrap());
+        let merged = merged.unwrap();
+        assert!(merged.is_leaf());
+        assert_eq!(merged.total_mass(), 4.0);
+        assert!(merged.center_of_mass().y > 1.0);
+        assert_eq!(merged.child_indices(), [None; 4]);
+    }
+
+    #[test]
+    fn test_quadtree_to_dot() {
+        let mut qt: QuadTree = QuadTree::new(BoundingBox2D::new(Vec2::ZERO, 10.0, 10.0));
+        qt.insert(Vec2::new(-1.0, -1.0), 1.0);
+        qt.insert(Vec2::new(1.0, 1.0), 1.0);
+
+        let dot = qt.to_dot();
+        assert!(dot.starts_with("digraph quadtree {"));
+        assert!(dot.contains("0 -> 2"));
+        assert!(dot.contains("0 -> 1"));
+    }
+
+    #[test]
+    fn test_stack_within_cutoff() {
+        let positions = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(50.0, 50.0),
+            Vec2::new(-40.0, 30.0),
+        ];
+        let mut quadtree = QuadTree::new(BoundingBox2D::new(Vec2::ZERO, 200.0, 200.0));
+        for position in positions {
+            quadtree.insert(position, 1.0);
+        }
+
+        let mass = |nodes: Vec<&Node>| nodes.iter().map(|n| n.total_mass()).sum::<f32>();
+        assert_eq!(mass(quadtree.stack_within(&Vec2::ZERO, 0.0, 5.0)), 2.0);
+        assert_eq!(
+            mass(quadtree.stack_within(&Vec2::ZERO, 0.5, f32::INFINITY)),
+            mass(quadtree.stack(&Vec2::ZERO, 0.5))
+        );
+        assert_eq!(
+            BoundingBox2D::new(Vec2::ZERO, 2.0, 2.0).distance_to(&Vec2::new(4.0, 5.0)),
+            5.0
+        );
+    }
+
+    #[test]
+    fn test_for_each_approx_keeps_mass() {
+        let bodies: Vec<RigidBody2D> = (0..50)
+            .map(|i| RigidBody2D::new(Vec2::new((i * 7 % 13) as f32, (i * 5 % 11) as f32), 1.0))
+            .collect();
+        let quadtree = QuadTree::from_rigid_bodies(&bodies);
+        let position = Vec2::new(3.0, 4.0);
+
+        let visit = |theta: f32| {
+            let (mut count, mut mass) = (0, 0.0);
+            quadtree.for_each_approx(&position, theta, |node| {
+                count += 1;
+                mass += node.total_mass();
+            });
+            (count, mass)
+        };
+        let (exact_count, exact_mass) = visit(0.0);
+        let (approx_count, approx_mass) = visit(2.0);
+        assert_eq!(exact_mass, 50.0);
+        assert!((approx_mass - 50.0).abs() < 1e-3);
+        assert!(approx_count < exact_count);
+        assert_eq!(quadtree.stack(&position, 2.0).len(), approx_count);
+    }
+
+    #[test]
+    fn test_parallel_build_matches_sequential() {
+        use rand::Rng;
+
+        let mut rng = rand::thread_rng();
+        let bodies: Vec<RigidBody2D> = (0..PARALLEL_BUILD_THRESHOLD + 100)
+            .map(|_| {
+                let position = Vec2::new(rng.gen_range(-100.0..100.0), rng.gen_range(-50.0..50.0));
+                RigidBody2D::new(position, rng.gen_range(1.0..5.0))
+            })
+            .collect();
+
+        let sequential = QuadTree::from_rigid_bodies(&bodies);
+        let parallel = QuadTree::from_rigid_bodies_par(&bodies);
+        let root = |tree: &QuadTree| {
+            let root = &tree.children[tree.root as usize];
+            (root.center_of_mass(), root.total_mass())
+        };
+        assert!(root(&sequential).0.distance(root(&parallel).0) < 1e-2);
+        assert!((root(&sequential).1 - root(&parallel).1).abs() / root(&sequential).1 < 1e-4);
+        assert_eq!(sequential.children.len(), parallel.children.len());
+
+        let range = BoundingBox2D::new(Vec2::new(10.0, -5.0), 30.0, 20.0);
+        let mut expected = sequential.query_range(&range);
+        let mut found = parallel.query_range(&range);
+        expected.sort_unstable();
+        found.sort_unstable();
+        assert_eq!(expected, found);
+
+        let position = Vec2::new(3.0, 4.0);
+        let mass = |nodes: Vec<&Node>| nodes.iter().map(|n| n.total_mass()).sum::<f32>();
+        let total = mass(sequential.stack(&position, 0.7));
+        assert!((total - mass(parallel.stack(&position, 0.7))).abs() / total < 1e-4);
+    }
+
+    #[test]
+    fn test_rebuild_reuses_allocation() {
+        let bodies: Vec<RigidBody2D> = (0..50)
+            .map(|i| RigidBody2D::new(Vec2::new((i * 7 % 13) as f32, (i * 3 % 11) as f32), 1.0))
+            .collect();
+        let mut qt = QuadTree::from_rigid_bodies(&bodies);
+        let children = qt.children.as_ptr();
+
+        let moved: Vec<RigidBody2D> = bodies
+            .iter()
+            .map(|rb| RigidBody2D::new(rb.position * 2.0 + Vec2::ONE, rb.mass))
+            .collect();
+        qt.rebuild(&moved);
+        assert_eq!(qt.children.as_ptr(), children);
+
+        let fresh = QuadTree::from_rigid_bodies(&moved);
+        assert_eq!(qt.boundary, fresh.boundary);
+        assert_eq!(qt.children.len(), fresh.children.len());
+        assert_eq!(qt.depth(), fresh.depth());
+        let root = &qt.children[qt.root as usize];
+        assert_eq!(root.total_mass(), 50.0);
+        let fresh_root = &fresh.children[fresh.root as usize];
+        assert!(root.center_of_mass().distance(fresh_root.center_of_mass()) < 1e-4);
+    }
+
+    #[test]
+    fn test_cells() {
+        let mut qt = QuadTree::new(BoundingBox2D::new(Vec2::ZERO, 10.0, 10.0));
+        qt.insert(Vec2::new(-4.0, -4.0), 1.0);
+        qt.insert(Vec2::new(4.0, 4.0), 1.0);
+        qt.insert(Vec2::new(3.0, 4.0), 1.0);
+
+        // The root, its lower left and upper right quadrant, the upper right quadrant of that and
+        // the two cells that separate the close bodies
+        assert_eq!(qt.cells().len(), 6);
+        assert_eq!(qt.depth(), 4);
+        assert_eq!(QuadTree::new(qt.boundary.clone()).depth(), 0);
+
+        // Seen from far away the whole tree is one body, close up nothing is approximated
+        let far = qt.approximated_cells(&Vec2::new(1000.0, 0.0), 0.5);
+        assert_eq!(far.len(), 1);
+        assert_eq!(far[0].center, Vec2::ZERO);
+        assert!(qt.approximated_cells(&Vec2::new(4.0, 4.0), 0.5).is_empty());
+    }
+
+    #[test]
+    fn test_morton_code() {
+        let boundary = BoundingBox2D::new(Vec2::ZERO, 2.0, 2.0);
+        assert_eq!(spread_bits(0b1011), 0b1000101);
+        assert_eq!(morton_code(Vec2::new(-1.0, -1.0), &boundary), 0);
+        assert_eq!(morton_code(Vec2::new(1.0, 1.0), &boundary), u32::MAX);
+
+        // The lower left quadrant comes before the lower right one
+        let lower_left = morton_code(Vec2::new(-0.1, -0.9), &boundary);
+        let lower_right = morton_code(Vec2::new(0.1, -0.9), &boundary);
+        assert!(lower_left < lower_right);
+    }
+
+    #[test]
+    fn test_estimate_error_without_approximation() {
+        let bodies: Vec<RigidBody2D> = (0..50)
+            .map(|i| RigidBody2D::new(Vec2::new((i % 7) as f32 * 3.0, (i / 7) as f32 * 2.0), 1.0))
+            .collect();
+
+        assert!(estimate_error(&bodies, 0.0) < 1e-4);
+        assert!(estimate_error(&bodies, 0.75) > 0.0);
+    }
+
+    mod proptests {
+        use super::*;
+        use proptest::prelude::*;
+
+        fn bodies_strategy() -> impl Strategy<Value = Vec<(f32, f32, f32)>> {
+            prop::collection::vec((-500.0f32..500.0, -500.0f32..500.0, 0.1f32..100.0), 1..200)
+        }
+
+        fn build(bodies: &[(f32, f32, f32)]) -> QuadTree {
+            let rbs: Vec<RigidBody2D> = bodies
+                .iter()
+                .map(|(x, y, m)| RigidBody2D::new(Vec2::new(*x, *y), *m))
+                .collect();
+            QuadTree::from_rigid_bodies(&rbs)
+        }
+
+        /// Walks all nodes reachable from the root together with their bounding box
+        fn reachable(qt: &QuadTree) -> Vec<(&Node, BoundingBox2D)> {
+            let mut out = vec![];
+            let mut stack = vec![(qt.root, qt.boundary.clone())];
+            while let Some((index, bb)) = stack.pop() {
+                let node = &qt.children[index as usize];
+                if let Node::Root { indices, .. } = node {
+                    for (section, i) in indices.iter().enumerate() {
+                        if *i != u32::MAX {
+                            stack.push((*i, bb.sub_quadrant(section as u8)));
+                        }
+                    }
+                }
+                out.push((node, bb));
+            }
+            out
+        }
+
+        fn close(a: f32, b: f32, scale: f32) -> bool {
+            (a - b).abs() <= 1e-3 * scale.max(1.0)
+        }
+
+        proptest! {
+            #[test]
+            fn total_mass_equals_sum_of_inserts(bodies in bodies_strategy()) {
+                let qt = build(&bodies);
+                let total: f32 = bodies.iter().map(|b| b.2).sum();
+                prop_assert!(close(qt.children[qt.root as usize].total_mass(), total, total));
+            }
+
+            #[test]
+            fn center_of_mass_matches_brute_force(bodies in bodies_strategy()) {
+                let qt = build(&bodies);
+                let total: f32 = bodies.iter().map(|b| b.2).sum();
+                let com = bodies
+                    .iter()
+                    .fold(Vec2::ZERO, |acc, b| acc + Vec2::new(b.0, b.1) * b.2)
+                    / total;
+                let root = qt.children[qt.root as usize].center_of_mass();
+                prop_assert!(close(root.x, com.x, 500.0));
+                prop_assert!(close(root.y, com.y, 500.0));
+            }
+
+            #[test]
+            fn every_body_reachable_from_root(bodies in bodies_strategy()) {
+                let qt = build(&bodies);
+                let total: f32 = bodies.iter().map(|b| b.2).sum();
+                let leaf_mass: f32 = reachable(&qt)
+                    .iter()
+                    .filter(|(n, _)| n.is_leaf())
+                    .map(|(n, _)| n.total_mass())
+                    .sum();
+                prop_assert!(close(leaf_mass, total, total));
+            }
+
+            #[test]
+            fn bounding_boxes_contain_subtrees(bodies in bodies_strategy()) {
+                let qt = build(&bodies);
+                for (node, bb) in reachable(&qt) {
+                    let pos = node.center_of_mass();
+                    prop_assert!((pos.x - bb.center.x).abs() <= bb.width / 2.0 + EPSILON);
+                    prop_assert!((pos.y - bb.center.y).abs() <= bb.height / 2.0 + EPSILON);
+                }
+            }
+
+            #[test]
+            fn nearest_matches_brute_force(
+                bodies in bodies_strategy(),
+                (x, y) in (-600.0f32..600.0, -600.0f32..600.0),
+            ) {
+                let qt = build(&bodies);
+                let position = Vec2::new(x, y);
+                let distance = |i: usize| Vec2::new(bodies[i].0, bodies[i].1).distance(position);
+                let closest = (0..bodies.len())
+                    .map(distance)
+                    .fold(f32::INFINITY, f32::min);
+
+                let found = qt.nearest(&position).unwrap();
+                prop_assert!(close(distance(found), closest, 1.0));
+
+                let odd = qt.nearest_where(&position, |i| i % 2 == 1);
+                prop_assert_eq!(odd.is_some(), bodies.len() > 1);
+                if let Some(odd) = odd {
+                    prop_assert!(odd % 2 == 1);
+                    let closest_odd = (1..bodies.len())
+                        .step_by(2)
+                        .map(distance)
+                        .fold(f32::INFINITY, f32::min);
+                    prop_assert!(close(distance(odd), closest_odd, 1.0));
+                }
+            }
+
+            #[test]
+            fn query_range_matches_brute_force(
+                bodies in bodies_strategy(),
+                (x, y, w, h) in (-500.0f32..500.0, -500.0f32..500.0, 0.0f32..500.0, 0.0f32..500.0),
+            ) {
+                let qt = build(&bodies);
+                let range = BoundingBox2D::new(Vec2::new(x, y), w, h);
+                let inner = BoundingBox2D::new(range.center, w - EPSILON, h - EPSILON);
+                let outer = BoundingBox2D::new(range.center, w + EPSILON, h + EPSILON);
+
+                let found = qt.query_range(&range);
+                for (i, b) in bodies.iter().enumerate() {
+                    let pos = Vec2::new(b.0, b.1);
+                    if found.contains(&i) {
+                        prop_assert!(outer.contains(&pos));
+                    } else {
+                        prop_assert!(!inner.contains(&pos));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_to_json_is_valid_for_degenerate_trees() {
+        let massless = [
+            RigidBody2D::new(Vec2::ZERO, 0.0),
+            RigidBody2D::new(Vec2::new(1.0, 1.0), 0.0),
+        ];
+        for tree in [
+            QuadTree::from_rigid_bodies(&[]),
+            QuadTree::from_rigid_bodies(&massless),
+        ] {
+            let json = tree.to_json();
+            assert!(
+                serde_json::from_str::<serde_json::Value>(&json).is_ok(),
+                "{}",
+                json
+            );
+        }
+    }
 }
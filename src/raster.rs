@@ -0,0 +1,190 @@
+//! Renders the layout to an image on the CPU, without a window or event loop, e.g. to show it
+//! inline in a Jupyter notebook with evcxr.
+//!
+//! # Example
+//! ```no_run
+//!use grapher::prelude::*;
+//!use grapher::raster::{render_to_png_bytes, RenderStyle};
+//!
+//!let mut rng = rand::thread_rng();
+//!let graph: petgraph::Graph<(), (), petgraph::Directed> =
+//!    petgraph_gen::barabasi_albert_graph(&mut rng, 200, 1, None);
+//!
+//!let simulator = SimulatorBuilder::new().build(graph.into());
+//!simulator.step_n(500);
+//!let png = render_to_png_bytes(&simulator, &RenderStyle::default());
+//!std::fs::write("layout.png", png).unwrap();
+//! ```
+
+use glam::Vec2;
+use tiny_skia::{Color, FillRule, Paint, PathBuilder, Pixmap, Stroke, Transform};
+
+use crate::{analysis, renderer::ColorScheme, simulator::Simulator};
+
+/// Size and colors of an image rendered with `render_to_png_bytes`
+#[derive(Clone)]
+pub struct RenderStyle {
+    /// Width of the image in pixels
+    pub width: u32,
+    /// Height of the image in pixels
+    pub height: u32,
+    pub background_color: [f32; 4],
+    /// `ColorScheme::Community` and `ColorScheme::Palette` group the nodes by
+    /// `analysis::communities`
+    pub color_scheme: ColorScheme,
+    pub edge_color: [f32; 4],
+    /// Width of the edges in pixels
+    pub edge_width: f32,
+    /// Radius of the nodes in world units, at least one pixel
+    pub node_radius: f32,
+    /// Space around the graph in pixels
+    pub padding: f32,
+}
+
+impl Default for RenderStyle {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            height: 600,
+            background_color: [0.0, 0.0, 0.0, 1.0],
+            color_scheme: ColorScheme::default(),
+            edge_color: [0.5, 0.5, 0.5, 1.0],
+            edge_width: 1.0,
+            node_radius: 0.5,
+            padding: 20.0,
+        }
+    }
+}
+
+/// Renders the current layout of `simulator` into a PNG image that fits the whole graph
+pub fn render_to_png_bytes(simulator: &Simulator, style: &RenderStyle) -> Vec<u8> {
+    render(simulator, style)
+        .encode_png()
+        .expect("encoding a pixmap as PNG can't fail")
+}
+
+/// Renders the current layout of `simulator` into an image of `style.width` x `style.height`
+pub(crate) fn render(simulator: &Simulator, style: &RenderStyle) -> Pixmap {
+    let mut pixmap = Pixmap::new(style.width.max(1), style.height.max(1)).unwrap();
+    pixmap.fill(color(style.background_color));
+
+    let Some(bounds) = simulator.bounding_box() else {
+        return pixmap;
+    };
+    let image_size = Vec2::new(pixmap.width() as f32, pixmap.height() as f32);
+    let available = (image_size - 2.0 * style.padding).max(Vec2::ONE);
+    let graph_size = Vec2::new(bounds.width, bounds.height) + 2.0 * style.node_radius;
+    let scale = (available / graph_size.max(Vec2::splat(f32::EPSILON))).min_element();
+    // The world y axis points up, the image y axis down
+    let to_pixel = |p: Vec2| {
+        let offset = (p - bounds.center) * scale;
+        image_size / 2.0 + Vec2::new(offset.x, -offset.y)
+    };
+
+    let partition = matches!(
+        style.color_scheme,
+        ColorScheme::Community | ColorScheme::Palette(_)
+    )
+    .then(|| analysis::communities(simulator));
+    let forces = if matches!(style.color_scheme, ColorScheme::Force) {
+        simulator.net_forces()
+    } else {
+        vec![]
+    };
+
+    let rb_read = simulator.rigid_bodies.read().unwrap();
+    let springs = simulator.springs.read().unwrap();
+
+    let mut edges = PathBuilder::new();
+    for spring in springs.iter() {
+        let (a, b) = (
+            to_pixel(rb_read[spring.rb1].position),
+            to_pixel(rb_read[spring.rb2].position),
+        );
+        edges.move_to(a.x, a.y);
+        edges.line_to(b.x, b.y);
+    }
+    if let Some(edges) = edges.finish() {
+        let stroke = Stroke {
+            width: style.edge_width,
+            ..Default::default()
+        };
+        pixmap.stroke_path(
+            &edges,
+            &paint(style.edge_color),
+            &stroke,
+            Transform::identity(),
+            None,
+        );
+    }
+
+    let colors = style
+        .color_scheme
+        .colors(&rb_read, &springs, partition.as_deref(), &forces);
+    let radius = (style.node_radius * scale).max(1.0);
+    for (rb, node_color) in rb_read.iter().zip(colors).filter(|(rb, _)| !rb.rope) {
+        let p = to_pixel(rb.position);
+        if let Some(circle) = PathBuilder::from_circle(p.x, p.y, radius) {
+            pixmap.fill_path(
+                &circle,
+                &paint(node_color),
+                FillRule::Winding,
+                Transform::identity(),
+                None,
+            );
+        }
+    }
+
+    pixmap
+}
+
+fn color([r, g, b, a]: [f32; 4]) -> Color {
+    Color::from_rgba(r, g, b, a).unwrap_or(Color::WHITE)
+}
+
+fn paint(rgba: [f32; 4]) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color(color(rgba));
+    paint.anti_alias = true;
+    paint
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::simulator::SimulatorBuilder;
+    use glam::Vec3;
+    use petgraph::{prelude::StableGraph, Directed};
+
+    #[test]
+    fn test_render_fits_graph() {
+        let mut graph: StableGraph<(), (), Directed> = StableGraph::new();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        graph.add_edge(a, b, ());
+        let simulator = SimulatorBuilder::new().build(graph);
+        simulator.set_node_location_by_index(Vec3::new(-10.0, 0.0, 0.0), 0);
+        simulator.set_node_location_by_index(Vec3::new(10.0, 0.0, 0.0), 1);
+
+        let style = RenderStyle {
+            width: 100,
+            height: 50,
+            color_scheme: ColorScheme::Palette(vec![[1.0, 0.0, 0.0, 1.0]]),
+            padding: 10.0,
+            ..Default::default()
+        };
+        let pixmap = render(&simulator, &style);
+
+        let pixel = |x: u32, y: u32| pixmap.pixel(x, y).unwrap();
+        // The nodes touch the padding on the left and right
+        assert_eq!((pixel(12, 25).red(), pixel(12, 25).green()), (255, 0));
+        assert_eq!((pixel(87, 25).red(), pixel(87, 25).green()), (255, 0));
+        // The edge between them is gray, the corners are background
+        assert_eq!(pixel(50, 25).red(), pixel(50, 25).green());
+        assert!(pixel(50, 25).red() > 0);
+        assert_eq!(pixel(0, 0).red(), 0);
+
+        let png = render_to_png_bytes(&simulator, &style);
+        assert_eq!(&png[1..4], b"PNG");
+    }
+}
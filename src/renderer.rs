@@ -1,54 +1,383 @@
 use core::f32;
 use std::{
+    collections::{HashMap, VecDeque},
+    path::{Path, PathBuf},
     rc::Rc,
-    sync::{Arc, Mutex, RwLock},
+    sync::{mpsc::Receiver, Arc, Mutex},
     thread,
-    time::Instant,
+    time::{Duration, Instant},
 };
 
-use crate::simulator::Simulator;
+use crate::{
+    analysis::{self, DynamicMetric},
+    edge_grid::EdgeGrid,
+    io::FormatRegistry,
+    properties::{RigidBody2D, Spring},
+    simulator::{remap, GraphChange, Simulator, SimulatorBuilder},
+    stream::GraphStream,
+};
+pub use annotation::Annotation;
 use camera::Camera;
+pub use color::{ColorScheme, NodeColorFn};
+pub use controls::KeyBindings;
 use event::EventManager;
-use glam::{Mat4, Vec2, Vec3, Vec4, Vec4Swizzles};
-use glium::{glutin::surface::WindowSurface, uniform, Display, Surface};
+use glam::{Mat4, Vec2, Vec3, Vec3Swizzles, Vec4, Vec4Swizzles};
+use glium::{glutin::surface::WindowSurface, uniform, Display, Frame, Surface};
+use hud::FrameCounter;
+#[cfg(feature = "serde")]
+pub use input_log::{InputEvent, RecordedInput};
+#[cfg(feature = "serde")]
+use input_log::{InputRecorder, InputReplay};
+use inspector::Inspector;
+pub use layer::Layer;
+use layer::Layers;
+use loading::{Loaded, Loading};
+use metric::MetricColors;
+use minimap::Minimap;
+use order::DrawOrder;
+pub use order::{NodeCategory, NodeOrder, NodeOrderFn};
+pub use pass::{DrawContext, DrawPass};
+use petgraph::{prelude::StableGraph, Directed};
+pub use recorder::Recorder;
+use render_state::RenderState;
+pub use session::Session;
+pub use window::WindowMode;
+pub use winit::event::VirtualKeyCode;
 
 use rand::Rng;
+use selection::Selection;
+use semantic::SemanticZoom;
 use winit::{
     event::{ElementState, Event, WindowEvent},
-    event_loop::{ControlFlow, EventLoop},
+    event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
     window::Window,
 };
 
+mod annotation;
 mod camera;
+mod color;
+mod controls;
 mod draw;
 mod event;
+mod font;
+#[cfg(feature = "raster")]
+mod headless;
+mod hud;
+mod hull;
+#[cfg(feature = "serde")]
+mod input_log;
+mod inspector;
+mod layer;
+mod loading;
+mod metric;
+mod minimap;
+mod order;
+#[cfg(feature = "egui")]
+mod panel;
+mod pass;
+mod recorder;
+mod render_state;
+mod screenshot;
+mod selection;
+mod semantic;
+mod session;
+mod shader;
 mod shapes;
+mod window;
 
-const SCROLL_SENSITIVITY: f32 = 2.0;
 const CAMERA_MOVEMENT_SENSITIVITY: f32 = 40.0;
+const EXPLODE_RADIUS: f32 = 20.0;
+const EXPLODE_STRENGTH: f32 = 200.0;
+const ENERGY_HISTORY_LEN: usize = 100;
+const ENERGY_SAMPLE_INTERVAL_MS: u128 = 100;
+const EDGE_PICK_DISTANCE_PX: f32 = 6.0;
+const FOV: f32 = 0.8;
+/// How long a simulation thread sleeps at most while its simulation is paused
+const PAUSED_WAIT: Duration = Duration::from_millis(100);
+/// Orbit rotation in radians per dragged pixel
+const ORBIT_SENSITIVITY: f32 = 0.005;
+
+/// Maps a node index and its rigid body to the radius the node is drawn with
+pub type NodeSizeFn = dyn Fn(u32, &RigidBody2D) -> f32 + Send + Sync;
 
 /// Renders a petgraph `StableGraph`
+#[derive(Clone)]
 pub struct Renderer {
-    scene_context: Arc<Mutex<SceneContext>>,
+    scene_contexts: Vec<Arc<Mutex<SceneContext>>>,
+    frame_requests: Arc<Mutex<Vec<PathBuf>>>,
+    /// Mode requested with `set_window_mode`, applied by the render loop
+    window_mode_request: Arc<Mutex<Option<WindowMode>>>,
+    recorder: Arc<Mutex<Option<Recorder>>>,
+    label_budget: usize,
+    settings: RendererBuilder,
 }
 
 impl Renderer {
     /// Creates a instance of the `Renderer`
     pub fn new(simulator: Simulator) -> Self {
-        let scene_context = SceneContext::new(simulator);
-        Self {
-            scene_context: Arc::new(Mutex::new(scene_context)),
+        RendererBuilder::new().build(simulator)
+    }
+
+    pub fn builder() -> RendererBuilder {
+        RendererBuilder::default()
+    }
+
+    /// Sets how the nodes of the most recently added tab are colored.
+    ///
+    /// Default: `ColorScheme::Community`
+    pub fn set_color_scheme(&mut self, color_scheme: ColorScheme) {
+        if let Some(scene_context) = self.scene_contexts.last() {
+            let mut scene_context = scene_context.lock().unwrap();
+            scene_context.color_scheme = color_scheme;
+            scene_context.metric_colors = None;
         }
     }
 
-    /// Creates a window and renders all the nodes and edges of given stable graph
-    pub fn create_window(self) {
-        let event_loop = winit::event_loop::EventLoopBuilder::new().build();
+    /// Colors the nodes of the most recently added tab from blue to red by `metric`, e.g.
+    /// `analysis::DynamicPageRank`.
+    ///
+    /// The metric is updated when the graph changes and the colors fade to the new values.
+    /// Replaces the color scheme until `set_color_scheme` is called.
+    pub fn set_dynamic_metric(&mut self, metric: Box<dyn DynamicMetric>) {
+        if let Some(scene_context) = self.scene_contexts.last() {
+            let mut scene_context = scene_context.lock().unwrap();
+            scene_context.metric_colors = Some(MetricColors::new(metric, &scene_context.simulator));
+        }
+    }
+
+    /// Colors the nodes of the most recently added tab by their index and rigid body
+    pub fn set_node_color_fn<F>(&mut self, color_fn: F)
+    where
+        F: Fn(u32, &RigidBody2D) -> [f32; 4] + Send + Sync + 'static,
+    {
+        self.set_color_scheme(ColorScheme::Custom(Arc::new(color_fn)));
+    }
+
+    /// Sizes the nodes of the most recently added tab by their index and rigid body, e.g. by a
+    /// centrality from `analysis`, without changing their mass.
+    ///
+    /// Default: the radius of a circle with the area of the node mass
+    pub fn set_node_size_fn<F>(&mut self, size_fn: F)
+    where
+        F: Fn(u32, &RigidBody2D) -> f32 + Send + Sync + 'static,
+    {
+        if let Some(scene_context) = self.scene_contexts.last() {
+            scene_context.lock().unwrap().node_size_fn = Some(Arc::new(size_fn));
+        }
+    }
+
+    /// Sets the order the nodes of the most recently added tab are drawn in within a `NodeCategory`.
+    ///
+    /// Default: `NodeOrder::SizeDescending`
+    pub fn set_node_order(&mut self, order: NodeOrder) {
+        if let Some(scene_context) = self.scene_contexts.last() {
+            scene_context.lock().unwrap().draw_order.order = order;
+        }
+    }
+
+    /// Moves the nodes of a category of the most recently added tab towards the camera.
+    ///
+    /// Categories with a bigger offset are drawn on top, also of edges and other overlays.
+    ///
+    /// Default: `0.0`
+    pub fn set_z_offset(&mut self, category: NodeCategory, z_offset: f32) {
+        if let Some(scene_context) = self.scene_contexts.last() {
+            scene_context
+                .lock()
+                .unwrap()
+                .draw_order
+                .z_offsets
+                .insert(category, z_offset);
+        }
+    }
+
+    /// Sets the color the frame of the most recently added tab is cleared with.
+    ///
+    /// Default: `[0.0, 0.0, 0.0, 1.0]`
+    pub fn set_clear_color(&mut self, color: [f32; 4]) {
+        if let Some(scene_context) = self.scene_contexts.last() {
+            scene_context.lock().unwrap().layers.clear_color = color;
+        }
+    }
+
+    /// Moves a layer of the most recently added tab to `z`, layers with a higher z are drawn on
+    /// top. See `Layer` for the depth strategy.
+    ///
+    /// Default: `Layer::default_z`
+    pub fn set_layer_z(&mut self, layer: Layer, z: f32) {
+        if let Some(scene_context) = self.scene_contexts.last() {
+            scene_context.lock().unwrap().layers.z.insert(layer, z);
+        }
+    }
+
+    /// Draws the edges of the most recently added tab on top of the nodes instead of below them
+    ///
+    /// Default: `false`
+    pub fn set_edges_over_nodes(&mut self, edges_over_nodes: bool) {
+        if let Some(scene_context) = self.scene_contexts.last() {
+            scene_context
+                .lock()
+                .unwrap()
+                .layers
+                .set_edges_over_nodes(edges_over_nodes);
+        }
+    }
+
+    /// Runs `pass` every frame of the most recently added tab, after the graph is drawn and before
+    /// the screen space overlays. Passes run in the order they were added.
+    pub fn add_draw_pass(&mut self, pass: Box<dyn DrawPass>) {
+        if let Some(scene_context) = self.scene_contexts.last() {
+            scene_context.lock().unwrap().draw_passes.push(pass);
+        }
+    }
+
+    /// Pins a note or marker to the world of the most recently added tab.
+    ///
+    /// Press `N` to add a note, `J` twice to add an arrow and `M` to mark the box selection.
+    pub fn add_annotation(&mut self, annotation: Annotation) {
+        if let Some(scene_context) = self.scene_contexts.last() {
+            scene_context.lock().unwrap().annotations.push(annotation);
+        }
+    }
+
+    /// Annotations of the most recently added tab, including the ones added in the window
+    pub fn annotations(&self) -> Vec<Annotation> {
+        self.scene_contexts
+            .last()
+            .map_or(vec![], |s| s.lock().unwrap().annotations.clone())
+    }
+
+    /// Captures the simulation and the annotations of the most recently added tab.
+    ///
+    /// Clone the `Renderer` before calling `create_window` to save sessions from another thread.
+    pub fn session(&self) -> Option<Session> {
+        self.scene_contexts.last().map(|scene_context| {
+            let scene_context = scene_context.lock().unwrap();
+            Session {
+                snapshot: scene_context.simulator.snapshot(),
+                annotations: scene_context.annotations.clone(),
+            }
+        })
+    }
+
+    /// Resumes a session saved with `session`, using `builder` for the simulation settings that
+    /// are not part of the snapshot
+    pub fn from_session(session: Session, builder: SimulatorBuilder) -> Self {
+        let mut renderer = Self::new(builder.build_from_snapshot(session.snapshot));
+        for annotation in session.annotations {
+            renderer.add_annotation(annotation);
+        }
+        renderer
+    }
+
+    /// Labels every node of the most recently added tab
+    pub fn set_labels(&mut self, labels: Vec<String>) {
+        if let Some(scene_context) = self.scene_contexts.last() {
+            scene_context.lock().unwrap().labels = Some(labels);
+        }
+    }
+
+    /// Maximum number of labels drawn per frame.
+    ///
+    /// The selected node is labeled first, then nodes with a high degree close to the cursor.
+    ///
+    /// Default: `50`
+    pub fn set_label_budget(&mut self, budget: usize) {
+        self.label_budget = budget;
+    }
+
+    /// Sets the `Recorder` that is started and stopped with `R`.
+    ///
+    /// Default: `Recorder::new(Recorder::default_path())`
+    pub fn set_recorder(&mut self, recorder: Recorder) {
+        *self.recorder.lock().unwrap() = Some(recorder);
+    }
+
+    /// Saves the next rendered frame as a PNG at `path`.
+    ///
+    /// Clone the `Renderer` before calling `create_window` to request frames from another thread.
+    /// Press `F12` to save a screenshot to the working directory. The window title shows where
+    /// the frame was saved.
+    pub fn save_frame<P: AsRef<Path>>(&self, path: P) {
+        self.frame_requests
+            .lock()
+            .unwrap()
+            .push(path.as_ref().to_path_buf());
+    }
+
+    /// Switches the window between windowed and fullscreen.
+    ///
+    /// Clone the `Renderer` before calling `create_window` to switch from another thread.
+    /// Press `F11` to switch between the window and `RendererBuilder::fullscreen_mode`.
+    pub fn set_window_mode(&self, mode: WindowMode) {
+        *self.window_mode_request.lock().unwrap() = Some(mode);
+    }
+
+    /// Opens the window right away and loads the graph file at `path` in the background.
+    ///
+    /// A progress bar is shown while loading. The simulation starts once the graph is loaded.
+    /// See `io::load_file` for the supported formats.
+    pub fn from_file<P: AsRef<Path>>(path: P, builder: SimulatorBuilder) -> Self {
+        RendererBuilder::new().build_from_file(path, builder)
+    }
+
+    /// Adds another simulation as a tab with its own camera and settings.
+    ///
+    /// Press `Tab` to switch between tabs.
+    pub fn add_tab(&mut self, simulator: Simulator) {
+        let scene_context = self.settings.scene_context(simulator);
+        self.scene_contexts
+            .push(Arc::new(Mutex::new(scene_context)));
+    }
+
+    /// Assigns every node of the most recently added tab to a group, e.g. a community.
+    ///
+    /// Press `G` to replace all edges between two groups with a single edge labeled with their count.
+    ///
+    /// Default: the communities found by `analysis::communities`
+    pub fn set_partition(&mut self, partition: Vec<u32>) {
+        if let Some(scene_context) = self.scene_contexts.last() {
+            scene_context.lock().unwrap().partition = Some(partition);
+        }
+    }
+
+    /// Applies the updates of `stream` to the most recently added tab every frame, e.g. to show a
+    /// graph arriving over the network. See `stream::GraphStream`.
+    pub fn set_stream<S: GraphStream + Send + 'static>(&mut self, stream: S) {
+        if let Some(scene_context) = self.scene_contexts.last() {
+            scene_context.lock().unwrap().stream = Some(Box::new(stream));
+        }
+    }
 
-        let (window, display) =
-            glium::backend::glutin::SimpleWindowBuilder::new().build(&event_loop);
+    /// Creates a window and renders all the nodes and edges of given stable graph.
+    ///
+    /// Without a display server or OpenGL, e.g. on a headless server or in CI, the layout is
+    /// rendered on the CPU instead if the `raster` feature is enabled: the simulation runs until
+    /// it is paused and the final layout is saved as `screenshot-<unix time>.png` in the working
+    /// directory. Without the feature the error is printed to stderr and the function returns.
+    pub fn create_window(self) {
+        let window = window::event_loop().and_then(|event_loop| {
+            window::build(
+                &event_loop,
+                &window_title(&self.settings.title, 0, self.scene_contexts.len()),
+                self.settings.window_size,
+                self.settings.vsync,
+            )
+            .map(|(window, display)| (event_loop, window, display))
+        });
 
-        self.run_render_loop(event_loop, display, window);
+        match window {
+            Ok((event_loop, window, display)) => {
+                self.run_render_loop(event_loop, display, window)
+            }
+            #[cfg(feature = "raster")]
+            Err(e) => headless::run(self, e),
+            #[cfg(not(feature = "raster"))]
+            Err(e) => eprintln!(
+                "Failed to create a window: {}. Enable the `raster` feature to render on the CPU instead.",
+                e
+            ),
+        }
     }
 
     #[allow(unused_variables)]
@@ -61,26 +390,136 @@ impl Renderer {
         // Timing
         let mut last_redraw = Instant::now();
         let mut last_event_cycle = Instant::now();
+        let mut last_error_estimate = Instant::now();
+        let mut last_energy_sample = Instant::now();
+        let mut frame_counter = FrameCounter::new();
+
+        let scene_contexts = self.scene_contexts.clone();
+        let frame_requests = Arc::clone(&self.frame_requests);
+        let window_mode_request = Arc::clone(&self.window_mode_request);
+        let mut window_mode = self.settings.window_mode;
+        let fullscreen_mode = self.settings.fullscreen_mode;
+        window::set_mode(&window, window_mode);
+        let recorder = Arc::clone(&self.recorder);
+        let label_budget = self.label_budget;
+        let scroll_sensitivity = self.settings.scroll_sensitivity;
+        let key_bindings = self.settings.key_bindings.clone();
+        let title = self.settings.title.clone();
+        let frame_interval = Duration::from_secs_f32(1.0 / self.settings.target_fps.max(1) as f32);
+        let mut active_tab = 0;
 
-        let scene_context_arc: Arc<Mutex<SceneContext>> = Arc::clone(&self.scene_context);
-        self.spawn_simulation_thread();
+        self.spawn_simulation_threads();
 
+        #[cfg(feature = "egui")]
+        let mut egui = egui_glium::EguiGlium::new(&display, &window, &event_loop);
+
+        // Kept outside of the `SceneContext`s, the GPU resources can't be sent to other threads
+        let mut render_states: Vec<RenderState> = scene_contexts
+            .iter()
+            .map(|_| RenderState::new(&display))
+            .collect();
         let display_rc = Rc::new(display);
+        let mut inspector = self
+            .settings
+            .inspector
+            .then(|| open_inspector(&event_loop, &title))
+            .flatten();
+        #[cfg(feature = "serde")]
+        let mut input_recorder = self.settings.record_input.as_ref().and_then(|path| {
+            InputRecorder::create(path)
+                .map_err(|e| eprintln!("Failed to record input to {}: {}", path.display(), e))
+                .ok()
+        });
+        #[cfg(feature = "serde")]
+        let mut input_replay = self.settings.replay_input.as_ref().and_then(|path| {
+            InputReplay::load(path)
+                .map_err(|e| eprintln!("Failed to replay input from {}: {}", path.display(), e))
+                .ok()
+        });
 
-        event_loop.run(move |event, _, control_flow| {
+        event_loop.run(move |event, target, control_flow| {
             *control_flow = ControlFlow::Poll;
 
+            for scene_context in scene_contexts.iter() {
+                let mut scene_context = scene_context.lock().unwrap();
+                finish_loading(&mut scene_context);
+                poll_stream(&mut scene_context);
+            }
+
+            let scene_context_arc: Arc<Mutex<SceneContext>> =
+                Arc::clone(&scene_contexts[active_tab]);
+
+            // Events of the inspector window, or of it after it was closed, don't reach the graph
+            if let Event::WindowEvent { event, window_id } = &event {
+                if *window_id != window.id() {
+                    if let Some(open) = inspector.as_mut().map(|i| i.on_event(event)) {
+                        if !open {
+                            inspector = None;
+                        }
+                    }
+                    return;
+                }
+            }
+
+            #[cfg(feature = "serde")]
+            if let (Some(input_recorder), Event::WindowEvent { event, .. }) =
+                (&mut input_recorder, &event)
+            {
+                input_recorder.record(event);
+            }
+            #[cfg(feature = "serde")]
+            if let Some(input_replay) = &mut input_replay {
+                for recorded in input_replay.due() {
+                    let event = recorded.event.to_window_event();
+                    if event == WindowEvent::CloseRequested {
+                        *control_flow = ControlFlow::Exit;
+                    }
+                    events(
+                        &Event::WindowEvent {
+                            window_id: window.id(),
+                            event,
+                        },
+                        Arc::clone(&scene_context_arc),
+                        &window,
+                        scroll_sensitivity,
+                        &key_bindings,
+                    );
+                }
+            }
+
             #[allow(clippy::collapsible_match)]
             if let Event::WindowEvent { event, .. } = &event {
                 match event {
                     WindowEvent::CloseRequested | WindowEvent::Destroyed => {
                         *control_flow = ControlFlow::Exit;
                     }
+                    // The projection is calculated from the window size every frame
+                    WindowEvent::Resized(size) => display_rc.resize((*size).into()),
+                    WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                        display_rc.resize((**new_inner_size).into())
+                    }
                     _ => (),
                 }
             }
 
-            events(&event, Arc::clone(&scene_context_arc));
+            // Input used by the panel doesn't reach the graph
+            #[cfg(feature = "egui")]
+            let consumed = match &event {
+                Event::WindowEvent { event, .. } => egui.on_event(event).consumed,
+                _ => false,
+            };
+            #[cfg(not(feature = "egui"))]
+            let consumed = false;
+
+            if !consumed {
+                events(
+                    &event,
+                    Arc::clone(&scene_context_arc),
+                    &window,
+                    scroll_sensitivity,
+                    &key_bindings,
+                );
+            }
 
             let mut scene_context = scene_context_arc.lock().unwrap();
 
@@ -89,17 +528,343 @@ impl Renderer {
 
             let mut highlight_index = vec![];
 
-            camera_movement(&mut scene_context, delta_time);
+            camera_movement(&mut scene_context, delta_time, &key_bindings);
+            if let Some(metric_colors) = &mut scene_context.metric_colors {
+                metric_colors.update(delta_time);
+            }
+            follow_node_changes(&mut scene_context);
 
             if let Some(event) = scene_context
                 .event_manager
-                .get_key_event_mut(&winit::event::VirtualKeyCode::P)
+                .get_key_event_mut(&key_bindings.place_mode)
             {
                 if event.is_initial_check() {
                     scene_context.place_mode = !scene_context.place_mode;
                 }
             }
 
+            if let Some(event) = scene_context
+                .event_manager
+                .get_key_event_mut(&key_bindings.next_tab)
+            {
+                if event.is_initial_check() && scene_contexts.len() > 1 {
+                    scene_context.event_manager.clear();
+                    active_tab = (active_tab + 1) % scene_contexts.len();
+                    window.set_title(&window_title(&title, active_tab, scene_contexts.len()));
+                    return;
+                }
+            }
+
+            if let Some(event) = scene_context
+                .event_manager
+                .get_key_event_mut(&key_bindings.quadtree_error)
+            {
+                if event.is_initial_check() {
+                    scene_context.show_quadtree_error = !scene_context.show_quadtree_error;
+                    if !scene_context.show_quadtree_error {
+                        window.set_title(&window_title(&title, active_tab, scene_contexts.len()));
+                    }
+                }
+            }
+
+            if let Some(event) = scene_context
+                .event_manager
+                .get_key_event_mut(&key_bindings.quadtree)
+            {
+                if event.is_initial_check() {
+                    scene_context.show_quadtree = !scene_context.show_quadtree;
+                }
+            }
+
+            if let Some(event) = scene_context
+                .event_manager
+                .get_key_event_mut(&key_bindings.hud)
+            {
+                if event.is_initial_check() {
+                    scene_context.show_hud = !scene_context.show_hud;
+                }
+            }
+
+            if let Some(event) = scene_context
+                .event_manager
+                .get_key_event_mut(&key_bindings.heatmap)
+            {
+                if event.is_initial_check() {
+                    scene_context.heatmap = match scene_context.heatmap {
+                        None => Some(ColorScheme::Velocity),
+                        Some(ColorScheme::Velocity) => Some(ColorScheme::Force),
+                        _ => None,
+                    };
+                }
+            }
+
+            if let Some(event) = scene_context
+                .event_manager
+                .get_key_event_mut(&key_bindings.screenshot)
+            {
+                if event.is_initial_check() {
+                    frame_requests
+                        .lock()
+                        .unwrap()
+                        .push(screenshot::default_path());
+                }
+            }
+
+            if let Some(event) = scene_context
+                .event_manager
+                .get_key_event_mut(&key_bindings.inspector)
+            {
+                if event.is_initial_check() {
+                    inspector = match inspector.take() {
+                        Some(_) => None,
+                        None => open_inspector(target, &title),
+                    };
+                }
+            }
+
+            if let Some(event) = scene_context
+                .event_manager
+                .get_key_event_mut(&key_bindings.fullscreen)
+            {
+                if event.is_initial_check() {
+                    let mode = if window_mode == WindowMode::Windowed {
+                        fullscreen_mode
+                    } else {
+                        WindowMode::Windowed
+                    };
+                    window_mode_request.lock().unwrap().get_or_insert(mode);
+                }
+            }
+            if let Some(mode) = window_mode_request.lock().unwrap().take() {
+                window::set_mode(&window, mode);
+                window_mode = mode;
+            }
+
+            if let Some(event) = scene_context
+                .event_manager
+                .get_key_event_mut(&key_bindings.record)
+            {
+                if event.is_initial_check() {
+                    let mut recorder = recorder.lock().unwrap();
+                    let recorder =
+                        recorder.get_or_insert_with(|| Recorder::new(Recorder::default_path()));
+                    if recorder.is_recording() {
+                        recorder.stop();
+                        window.set_title(&window_title(&title, active_tab, scene_contexts.len()));
+                    } else {
+                        recorder.start();
+                        window.set_title(&format!(
+                            "{} - Recording to {}",
+                            window_title(&title, active_tab, scene_contexts.len()),
+                            recorder.output().display()
+                        ));
+                    }
+                }
+            }
+
+            if let Some(event) = scene_context
+                .event_manager
+                .get_key_event_mut(&key_bindings.bundle_edges)
+            {
+                if event.is_initial_check() {
+                    scene_context.bundle_edges = !scene_context.bundle_edges;
+                }
+            }
+
+            if let Some(event) = scene_context
+                .event_manager
+                .get_key_event_mut(&key_bindings.aggregate_edges)
+            {
+                if event.is_initial_check() {
+                    scene_context.aggregate_edges = !scene_context.aggregate_edges;
+                }
+            }
+
+            if let Some(event) = scene_context
+                .event_manager
+                .get_key_event_mut(&key_bindings.semantic_zoom)
+            {
+                if event.is_initial_check() {
+                    scene_context.semantic_zoom.enabled = !scene_context.semantic_zoom.enabled;
+                }
+            }
+
+            if let Some(event) = scene_context
+                .event_manager
+                .get_key_event_mut(&key_bindings.hulls)
+            {
+                if event.is_initial_check() {
+                    scene_context.show_hulls = !scene_context.show_hulls;
+                }
+            }
+
+            if let Some(event) = scene_context
+                .event_manager
+                .get_key_event_mut(&key_bindings.minimap)
+            {
+                if event.is_initial_check() {
+                    scene_context.show_minimap = !scene_context.show_minimap;
+                }
+            }
+
+            if let Some(event) = scene_context
+                .event_manager
+                .get_key_event_mut(&key_bindings.energy_graph)
+            {
+                if event.is_initial_check() {
+                    scene_context.show_energy_graph = !scene_context.show_energy_graph;
+                }
+            }
+
+            if last_energy_sample.elapsed().as_millis() >= ENERGY_SAMPLE_INTERVAL_MS {
+                last_energy_sample = Instant::now();
+                let energy = scene_context.simulator.total_kinetic_energy();
+                let history = &mut scene_context.energy_history;
+                if history.len() == ENERGY_HISTORY_LEN {
+                    history.pop_front();
+                }
+                history.push_back(energy);
+            }
+
+            if let Some(event) = scene_context
+                .event_manager
+                .get_key_event_mut(&key_bindings.explode)
+            {
+                if event.is_initial_check() {
+                    let vector = cursor_pos_to_world_vec(
+                        &window,
+                        &scene_context.camera,
+                        &scene_context.cursor_pos,
+                    );
+                    let intersection_point = vector_plane_intersection(
+                        vector,
+                        scene_context.camera.position,
+                        Vec4::new(0.0, 0.0, 1.0, 0.0),
+                        2,
+                    );
+                    scene_context.simulator.explode(
+                        intersection_point,
+                        EXPLODE_RADIUS,
+                        EXPLODE_STRENGTH,
+                    );
+                }
+            }
+
+            if let Some(event) = scene_context
+                .event_manager
+                .get_key_event_mut(&key_bindings.note)
+            {
+                if event.is_initial_check() {
+                    let point = cursor_world_position(&scene_context, &window);
+                    scene_context.annotations.push(Annotation::note(point, ""));
+                    scene_context.editing_note = Some(NoteEdit {
+                        index: scene_context.annotations.len() - 1,
+                        skip_char: true,
+                    });
+                    // Keys are typed into the note until it is finished
+                    scene_context.event_manager.clear();
+                }
+            }
+
+            if let Some(event) = scene_context
+                .event_manager
+                .get_key_event_mut(&key_bindings.arrow)
+            {
+                if event.is_initial_check() {
+                    let point = cursor_world_position(&scene_context, &window);
+                    match scene_context.arrow_start.take() {
+                        Some(start) => scene_context
+                            .annotations
+                            .push(Annotation::arrow(start, point)),
+                        None => scene_context.arrow_start = Some(point),
+                    }
+                }
+            }
+
+            if let Some(event) = scene_context
+                .event_manager
+                .get_key_event_mut(&key_bindings.region)
+            {
+                if event.is_initial_check() {
+                    if let Some(region) = selection_region(&scene_context) {
+                        scene_context.annotations.push(region);
+                    }
+                }
+            }
+
+            if let Some(event) = scene_context
+                .event_manager
+                .get_key_event_mut(&key_bindings.delete)
+            {
+                if event.is_initial_check() {
+                    if !scene_context.selection.is_empty() {
+                        remove_selected_nodes(&mut scene_context);
+                    } else if scene_context.place_mode
+                        && scene_context.selected_edge_index.is_some()
+                    {
+                        if let Some(index) = scene_context.selected_edge_index.take() {
+                            scene_context.simulator.remove_spring(index);
+                            scene_context.hovered_edge_index = None;
+                            scene_context.edge_grid = None;
+                        }
+                    } else {
+                        remove_annotation_under_cursor(&mut scene_context, &window);
+                    }
+                }
+            }
+
+            if scene_context.show_quadtree_error && last_error_estimate.elapsed().as_secs() >= 1 {
+                last_error_estimate = Instant::now();
+                let error = scene_context.simulator.estimate_quadtree_error();
+                window.set_title(&format!(
+                    "{} - Barnes-Hut error: {:.3}%",
+                    window_title(&title, active_tab, scene_contexts.len()),
+                    error * 100.0
+                ));
+            }
+
+            if let Some(event) = scene_context
+                .event_manager
+                .get_mouse_button_event_mut(&winit::event::MouseButton::Right)
+            {
+                if event.is_initial_check() {
+                    toggle_pin(&scene_context, &window);
+                }
+            }
+
+            if !scene_context
+                .event_manager
+                .contains_mouse_button(&winit::event::MouseButton::Left)
+            {
+                if scene_context.selection.is_dragging_box() {
+                    let point = cursor_world_position(&scene_context, &window);
+                    let rb_read = scene_context.simulator.rigid_bodies.read().unwrap();
+                    let positions: Vec<Vec2> = rb_read.iter().map(|rb| rb.position).collect();
+                    let rope: Vec<bool> = rb_read.iter().map(|rb| rb.rope).collect();
+                    drop(rb_read);
+                    scene_context
+                        .selection
+                        .finish_box(point, &positions, |i| rope[i]);
+                }
+
+                if let Some(source) = scene_context.edge_source.take() {
+                    let point = cursor_world_position(&scene_context, &window);
+                    if let Some(target) = node_under(&scene_context, point) {
+                        if target != source {
+                            scene_context.simulator.insert_edge(source, target, None);
+                        }
+                    }
+                }
+
+                if let Some(drag) = scene_context.group_drag.take() {
+                    for (index, fixed) in drag.members.iter().zip(drag.was_fixed) {
+                        scene_context
+                            .simulator
+                            .set_node_fixed_by_index(fixed, *index);
+                    }
+                }
+            }
+
             if scene_context
                 .event_manager
                 .contains_mouse_button(&winit::event::MouseButton::Left)
@@ -129,19 +894,34 @@ impl Renderer {
                     time_engaged = event.time_engaged();
                 }
 
-                if !scene_context.place_mode {
-                    let selected_node = &mut scene_context.selected_node_index;
-
-                    if is_initial {
-                        *selected_node = sim.find_closest_node_index(intersection_point);
-                    }
+                let shift = is_shift_held(&scene_context.event_manager);
+                let orbiting = is_orbiting(&scene_context.event_manager);
+                if is_initial && shift {
+                    scene_context.selection.start_box(intersection_point.xy());
+                } else if is_initial && !orbiting {
+                    select(&mut scene_context, &window, intersection_point.xy());
+                }
 
-                    if let Some(index) = *selected_node {
+                if scene_context.selection.is_dragging_box() || orbiting {
+                    // The selection is updated on release, orbiting is handled with the cursor movement
+                } else if let Some(drag) = &mut scene_context.group_drag {
+                    sim.translate_nodes_by_index(
+                        intersection_point.xy() - drag.last,
+                        &drag.members,
+                    );
+                    drag.last = intersection_point.xy();
+                    highlight_index.extend(&drag.members);
+                    scene_context.edge_grid = None;
+                } else if !scene_context.place_mode {
+                    if let Some(index) = scene_context.selected_node_index {
                         sim.set_node_location_by_index(intersection_point, index);
                         highlight_index.push(index);
+                        scene_context.edge_grid = None;
                     }
-                } else if (is_initial || time_engaged.as_secs_f32() > 0.5)
-                    && !*scene_context.toggle_sim.read().unwrap()
+                } else if scene_context.selected_edge_index.is_none()
+                    && scene_context.edge_source.is_none()
+                    && (is_initial || time_engaged.as_secs_f32() > 0.5)
+                    && !scene_context.simulator.is_running()
                 {
                     scene_context
                         .event_manager
@@ -152,38 +932,698 @@ impl Renderer {
                 }
             }
 
+            highlight_index.extend(scene_context.selection.nodes());
             drop(scene_context);
 
-            if last_redraw.elapsed().as_millis() >= 34 {
+            if last_redraw.elapsed() >= frame_interval {
                 last_redraw = Instant::now();
-                draw_graph(
+                update_hover(&mut scene_context_arc.lock().unwrap(), &window);
+                #[allow(unused_mut)]
+                let mut target = draw_graph(
                     Arc::clone(&scene_context_arc),
+                    &mut render_states[active_tab],
                     &display_rc,
                     &window,
                     &highlight_index,
+                    label_budget,
                 );
+
+                frame_counter.frame();
+                {
+                    let scene_context = scene_context_arc.lock().unwrap();
+                    if scene_context.show_hud {
+                        let size = window.inner_size();
+                        draw::draw_hud(
+                            &scene_context,
+                            frame_counter.fps(),
+                            &mut target,
+                            &display_rc,
+                            Vec2::new(size.width as f32, size.height as f32),
+                        );
+                    }
+                }
+
+                #[cfg(feature = "egui")]
+                {
+                    let simulator = Arc::clone(&scene_context_arc.lock().unwrap().simulator);
+                    // The panel moves into the inspector while it is open
+                    let detached = inspector.is_some();
+                    egui.run(&window, |ctx| {
+                        if !detached {
+                            panel::parameter_panel(ctx, &simulator);
+                        }
+                    });
+                    egui.paint(&display_rc, &mut target);
+                }
+                target.finish().unwrap();
+
+                if let Some(recorder) = recorder.lock().unwrap().as_mut() {
+                    recorder.capture(&display_rc);
+                }
+
+                for path in frame_requests.lock().unwrap().drain(..) {
+                    match screenshot::save_front_buffer(&display_rc, &path) {
+                        Ok(()) => window.set_title(&format!(
+                            "{} - Saved frame to {}",
+                            window_title(&title, active_tab, scene_contexts.len()),
+                            path.display()
+                        )),
+                        Err(e) => eprintln!("Failed to save frame to {}: {}", path.display(), e),
+                    }
+                }
+
+                if let Some(inspector) = &mut inspector {
+                    inspector.draw(&scene_context_arc.lock().unwrap(), frame_counter.fps());
+                }
             }
         });
     }
 
-    fn spawn_simulation_thread(&self) {
-        let sim;
-        let toggle_sim;
-        {
-            let scene_context = self.scene_context.lock().unwrap();
-            sim = Arc::clone(&scene_context.simulator);
-            toggle_sim = Arc::clone(&scene_context.toggle_sim);
+    /// Applies `RendererBuilder::start_paused` and steps every tab in its own thread
+    fn spawn_simulation_threads(&self) {
+        if let Some(paused) = self.settings.start_paused {
+            for scene_context in self.scene_contexts.iter() {
+                let simulator = &scene_context.lock().unwrap().simulator;
+                if paused {
+                    simulator.pause();
+                } else {
+                    simulator.resume();
+                }
+            }
+        }
+        for scene_context in self.scene_contexts.iter() {
+            spawn_simulation_thread(&scene_context.lock().unwrap().simulator);
+        }
+    }
+}
+
+/// Builder for `Renderer`
+#[derive(Clone, Debug)]
+pub struct RendererBuilder {
+    title: String,
+    window_size: (u32, u32),
+    background_color: [f32; 4],
+    target_fps: u32,
+    vsync: bool,
+    window_mode: WindowMode,
+    fullscreen_mode: WindowMode,
+    inspector: bool,
+    #[cfg(feature = "serde")]
+    record_input: Option<PathBuf>,
+    #[cfg(feature = "serde")]
+    replay_input: Option<PathBuf>,
+    camera_position: Vec3,
+    key_bindings: KeyBindings,
+    formats: Arc<FormatRegistry>,
+    start_paused: Option<bool>,
+    scroll_sensitivity: f32,
+}
+
+impl RendererBuilder {
+    /// Get a Instance of `RendererBuilder` with default values
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Title of the window, the active tab is appended when there are multiple tabs
+    ///
+    /// Default: `"RustGrapher"`
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    /// Initial size of the window in pixels
+    ///
+    /// Default: `(800, 480)`
+    pub fn window_size(mut self, width: u32, height: u32) -> Self {
+        self.window_size = (width, height);
+        self
+    }
+
+    /// Color every tab is cleared with, see `Renderer::set_clear_color` to change it for one tab
+    ///
+    /// Default: `[0.0, 0.0, 0.0, 1.0]`
+    pub fn background_color(mut self, color: [f32; 4]) -> Self {
+        self.background_color = color;
+        self
+    }
+
+    /// How often the graph is redrawn per second
+    ///
+    /// Default: `30`
+    pub fn target_fps(mut self, target_fps: u32) -> Self {
+        self.target_fps = target_fps;
+        self
+    }
+
+    /// If the buffer swaps wait for the vertical sync of the display
+    ///
+    /// Default: `true`
+    pub fn vsync(mut self, vsync: bool) -> Self {
+        self.vsync = vsync;
+        self
+    }
+
+    /// How the window is shown when it is opened
+    ///
+    /// Default: `WindowMode::Windowed`
+    pub fn window_mode(mut self, window_mode: WindowMode) -> Self {
+        self.window_mode = window_mode;
+        self
+    }
+
+    /// If the inspector window with the details of the selected node, the simulation telemetry
+    /// and the parameters is open from the start. Press `O` to open or close it.
+    ///
+    /// Default: `false`
+    pub fn inspector(mut self, inspector: bool) -> Self {
+        self.inspector = inspector;
+        self
+    }
+
+    /// Writes all input of the graph window to `path` as JSON lines of `RecordedInput`, e.g. to
+    /// reproduce a demo or a bug with `replay_input`
+    ///
+    /// Default: `None`
+    #[cfg(feature = "serde")]
+    pub fn record_input<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.record_input = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Replays the input recorded with `record_input` at its recorded pace, in addition to the
+    /// input of the user. The window closes where the recording ended by closing it.
+    ///
+    /// The simulation itself isn't deterministic, give it a `SimulatorBuilder::seed` and start it
+    /// paused for reproducible interaction tests.
+    ///
+    /// Default: `None`
+    #[cfg(feature = "serde")]
+    pub fn replay_input<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.replay_input = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Fullscreen mode `F11` switches to, `WindowMode::Windowed` disables the key
+    ///
+    /// Default: `WindowMode::Borderless`
+    pub fn fullscreen_mode(mut self, fullscreen_mode: WindowMode) -> Self {
+        self.fullscreen_mode = fullscreen_mode;
+        self
+    }
+
+    /// Position the camera of every tab starts at, looking straight at the `z = 0` plane
+    ///
+    /// Default: `Vec3::new(0.0, 0.0, 150.0)`
+    pub fn camera_position(mut self, position: Vec3) -> Self {
+        self.camera_position = position;
+        self
+    }
+
+    /// Keys of the controls
+    ///
+    /// Default: `KeyBindings::default()`
+    pub fn key_bindings(mut self, key_bindings: KeyBindings) -> Self {
+        self.key_bindings = key_bindings;
+        self
+    }
+
+    /// Graph file formats that can be loaded with `build_from_file` or by dropping a file onto the
+    /// window
+    ///
+    /// Default: `FormatRegistry::default()`
+    pub fn formats(mut self, formats: FormatRegistry) -> Self {
+        self.formats = Arc::new(formats);
+        self
+    }
+
+    /// Pauses or resumes the simulations of all tabs when the window opens
+    ///
+    /// Default: the simulations keep their state, a new `Simulator` starts paused
+    pub fn start_paused(mut self, paused: bool) -> Self {
+        self.start_paused = Some(paused);
+        self
+    }
+
+    /// How far one line of scrolling zooms, as the logarithm of the zoom factor. Negative values
+    /// invert the scroll direction.
+    ///
+    /// Default: `0.1`
+    pub fn scroll_sensitivity(mut self, scroll_sensitivity: f32) -> Self {
+        self.scroll_sensitivity = scroll_sensitivity;
+        self
+    }
+
+    pub fn build(self, simulator: Simulator) -> Renderer {
+        Renderer {
+            scene_contexts: vec![Arc::new(Mutex::new(self.scene_context(simulator)))],
+            frame_requests: Arc::new(Mutex::new(vec![])),
+            window_mode_request: Arc::new(Mutex::new(None)),
+            recorder: Arc::new(Mutex::new(None)),
+            label_budget: 50,
+            settings: self,
+        }
+    }
+
+    /// Like `Renderer::from_file`, using the configured formats
+    pub fn build_from_file<P: AsRef<Path>>(self, path: P, builder: SimulatorBuilder) -> Renderer {
+        let formats = Arc::clone(&self.formats);
+        let renderer = self.build(SimulatorBuilder::new().build(StableGraph::<(), ()>::new()));
+        renderer.scene_contexts[0].lock().unwrap().loading =
+            Some(Loading::spawn(path.as_ref(), formats, builder, false));
+        renderer
+    }
+
+    fn scene_context(&self, simulator: Simulator) -> SceneContext {
+        let mut scene_context = SceneContext::new(simulator);
+        scene_context.camera = Camera::new(self.camera_position);
+        scene_context
+            .camera
+            .look_at(&self.camera_position.truncate().extend(0.0));
+        scene_context.layers.clear_color = self.background_color;
+        scene_context.formats = Arc::clone(&self.formats);
+        scene_context
+    }
+}
+
+impl Default for RendererBuilder {
+    /// Get a Instance of `RendererBuilder` with default values
+    fn default() -> Self {
+        Self {
+            title: "RustGrapher".to_string(),
+            window_size: (800, 480),
+            background_color: [0.0, 0.0, 0.0, 1.0],
+            target_fps: 30,
+            vsync: true,
+            window_mode: WindowMode::Windowed,
+            fullscreen_mode: WindowMode::Borderless,
+            inspector: false,
+            #[cfg(feature = "serde")]
+            record_input: None,
+            #[cfg(feature = "serde")]
+            replay_input: None,
+            camera_position: Vec3::new(0.0, 0.0, 150.0),
+            key_bindings: KeyBindings::default(),
+            formats: Arc::new(FormatRegistry::default()),
+            start_paused: None,
+            scroll_sensitivity: 0.1,
         }
+    }
+}
 
-        thread::spawn(move || loop {
-            let toggle_sim_read_guard = toggle_sim.read().unwrap();
-            let sim_toggle = *toggle_sim_read_guard;
-            drop(toggle_sim_read_guard);
+fn open_inspector(target: &EventLoopWindowTarget<()>, title: &str) -> Option<Inspector> {
+    Inspector::open(target, title)
+        .map_err(|e| eprintln!("Failed to open the inspector: {}", e))
+        .ok()
+}
+
+/// Steps the simulation until it is dropped from the `SceneContext`
+fn spawn_simulation_thread(simulator: &Arc<Simulator>) {
+    let sim = Arc::downgrade(simulator);
 
-            if sim_toggle {
+    thread::spawn(move || {
+        while let Some(sim) = sim.upgrade() {
+            // Times out so the thread ends soon after the simulator is dropped
+            if sim.wait_until_running(PAUSED_WAIT) {
                 sim.simulation_step();
             }
+        }
+    });
+}
+
+/// Selects the node under `point`, or else the edge under `point`, or else the closest node.
+///
+/// Clicking a node of the box selection or a hull drags the whole group instead.
+fn select(scene_context: &mut SceneContext, window: &Window, point: Vec2) {
+    let node = node_under(scene_context, point);
+    let edge = if node.is_some() {
+        None
+    } else {
+        let max_distance =
+            EDGE_PICK_DISTANCE_PX * world_units_per_pixel(window, &scene_context.camera);
+        pick_edge(scene_context, point, max_distance)
+    };
+
+    let group = if node
+        .is_some_and(|n| !scene_context.place_mode && scene_context.selection.contains(n))
+    {
+        Some(scene_context.selection.nodes().to_vec())
+    } else {
+        scene_context.selection.clear();
+        if node.is_none() && edge.is_none() && !scene_context.place_mode && scene_context.show_hulls
+        {
+            group_under(scene_context, point)
+        } else {
+            None
+        }
+    };
+
+    if let Some(members) = group {
+        let was_fixed = {
+            let rb_read = scene_context.simulator.rigid_bodies.read().unwrap();
+            members.iter().map(|i| rb_read[*i as usize].fixed).collect()
+        };
+        for index in &members {
+            scene_context
+                .simulator
+                .set_node_fixed_by_index(true, *index);
+        }
+        scene_context.group_drag = Some(GroupDrag {
+            members,
+            was_fixed,
+            last: point,
+        });
+        scene_context.selected_edge_index = None;
+        scene_context.selected_node_index = None;
+        return;
+    }
+
+    if scene_context.place_mode {
+        scene_context.edge_source = node;
+    }
+
+    scene_context.selected_edge_index = edge;
+    scene_context.selected_node_index = match (node, edge) {
+        (Some(node), _) => Some(node),
+        (None, Some(_)) => None,
+        (None, None) => scene_context
+            .simulator
+            .find_closest_node_index(point.extend(0.0)),
+    };
+}
+
+/// Members of the group with the smallest hull containing `point`
+fn group_under(scene_context: &SceneContext, point: Vec2) -> Option<Vec<u32>> {
+    draw::group_hulls(scene_context)
+        .into_iter()
+        .filter(|(_, hull)| hull::contains(hull, point))
+        .min_by(|a, b| hull::area(&a.1).total_cmp(&hull::area(&b.1)))
+        .map(|(members, _)| members)
+}
+
+/// Position of the cursor on the `z = 0` plane
+fn cursor_world_position(scene_context: &SceneContext, window: &Window) -> Vec2 {
+    screen_to_world(scene_context, window, scene_context.cursor_pos)
+}
+
+/// Position on the `z = 0` plane that is shown at the window pixel `point`
+fn screen_to_world(scene_context: &SceneContext, window: &Window, point: Vec2) -> Vec2 {
+    vector_plane_intersection(
+        cursor_pos_to_world_vec(window, &scene_context.camera, &point),
+        scene_context.camera.position,
+        Vec4::new(0.0, 0.0, 1.0, 0.0),
+        2,
+    )
+    .xy()
+}
+
+/// Minimap of the scene if it is shown and the graph has nodes
+fn minimap(scene_context: &SceneContext, window: &Window) -> Option<Minimap> {
+    if !scene_context.show_minimap {
+        return None;
+    }
+    let size = window.inner_size();
+    let bounds = scene_context.simulator.bounding_box()?;
+    Some(Minimap::new(
+        &bounds,
+        Vec2::new(size.width as f32, size.height as f32),
+    ))
+}
+
+/// Pins or unpins the node under the cursor, or all selected nodes if it is part of the box selection
+fn toggle_pin(scene_context: &SceneContext, window: &Window) {
+    let point = cursor_world_position(scene_context, window);
+    let Some(index) = node_under(scene_context, point) else {
+        return;
+    };
+
+    let nodes = if scene_context.selection.contains(index) {
+        scene_context.selection.nodes().to_vec()
+    } else {
+        vec![index]
+    };
+    let all_fixed = {
+        let rb_read = scene_context.simulator.rigid_bodies.read().unwrap();
+        nodes.iter().all(|i| rb_read[*i as usize].fixed)
+    };
+    for index in nodes {
+        scene_context
+            .simulator
+            .set_node_fixed_by_index(!all_fixed, index);
+    }
+}
+
+/// Removes the nodes of the box selection from the simulation
+fn remove_selected_nodes(scene_context: &mut SceneContext) {
+    scene_context
+        .simulator
+        .remove_nodes(scene_context.selection.nodes());
+    follow_node_changes(scene_context);
+
+    scene_context.selection.clear();
+    scene_context.selected_node_index = None;
+    scene_context.selected_edge_index = None;
+    scene_context.hovered_node_index = None;
+    scene_context.hovered_edge_index = None;
+    scene_context.edge_grid = None;
+    scene_context.group_drag = None;
+    scene_context.edge_source = None;
+}
+
+/// Region around the nodes of the box selection
+fn selection_region(scene_context: &SceneContext) -> Option<Annotation> {
+    let rb_read = scene_context.simulator.rigid_bodies.read().unwrap();
+    let nodes = scene_context.selection.nodes();
+    let padding = nodes
+        .iter()
+        .map(|i| draw::node_radius(scene_context, *i as usize, &rb_read[*i as usize]))
+        .fold(0.0, f32::max);
+    let (min, max) = nodes
+        .iter()
+        .map(|i| rb_read[*i as usize].position)
+        .fold((Vec2::INFINITY, Vec2::NEG_INFINITY), |(min, max), p| {
+            (min.min(p), max.max(p))
         });
+    (!nodes.is_empty()).then(|| Annotation::region(min - padding, max + padding))
+}
+
+/// Removes the annotation closest to the cursor
+fn remove_annotation_under_cursor(scene_context: &mut SceneContext, window: &Window) {
+    let point = cursor_world_position(scene_context, window);
+    let max_distance = EDGE_PICK_DISTANCE_PX * world_units_per_pixel(window, &scene_context.camera);
+    let closest = scene_context
+        .annotations
+        .iter()
+        .enumerate()
+        .map(|(i, annotation)| (i, annotation.distance(point)))
+        .filter(|(_, distance)| *distance <= max_distance)
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+    if let Some((index, _)) = closest {
+        scene_context.annotations.remove(index);
+        scene_context.editing_note = None;
+    }
+}
+
+/// Moves the per node state of the scene along when the simulator reorders or removes nodes
+fn follow_node_changes(scene_context: &mut SceneContext) {
+    let changes: Vec<GraphChange> = scene_context.graph_changes.try_iter().collect();
+    if !changes.is_empty() {
+        scene_context.semantic_zoom.invalidate();
+    }
+    let mappings: Vec<(Vec<u32>, bool)> = changes
+        .into_iter()
+        .filter_map(|change| match change {
+            GraphChange::NodesReordered(mapping) => Some((mapping, false)),
+            GraphChange::NodesRemoved(mapping) => Some((mapping, true)),
+            _ => None,
+        })
+        .collect();
+
+    for (mapping, removed) in mappings {
+        let map = |index: u32| match mapping.get(index as usize) {
+            Some(&u32::MAX) => None,
+            Some(m) => Some(*m),
+            None => Some(index),
+        };
+        if let Some(partition) = &mut scene_context.partition {
+            remap(partition, &mapping);
+        }
+        if let Some(labels) = &mut scene_context.labels {
+            remap(labels, &mapping);
+        }
+        scene_context.selection.remap(&mapping);
+        scene_context.selected_node_index = scene_context.selected_node_index.and_then(map);
+        scene_context.hovered_node_index = scene_context.hovered_node_index.and_then(map);
+        scene_context.edge_source = scene_context.edge_source.and_then(map);
+        if removed {
+            // The springs of removed nodes are gone, the following springs moved down
+            scene_context.selected_edge_index = None;
+            scene_context.hovered_edge_index = None;
+            scene_context.group_drag = None;
+        } else if let Some(drag) = &mut scene_context.group_drag {
+            drag.members
+                .iter_mut()
+                .for_each(|m| *m = map(*m).unwrap_or(*m));
+        }
+        scene_context.edge_grid = None;
+    }
+}
+
+/// Applies the updates of the `GraphStream` set with `Renderer::set_stream`
+fn poll_stream(scene_context: &mut SceneContext) {
+    let Some(stream) = &mut scene_context.stream else {
+        return;
+    };
+    let updates = stream.poll_updates();
+    if updates.is_empty() {
+        return;
+    }
+    for update in updates {
+        scene_context.simulator.apply(update);
+    }
+    scene_context.edge_grid = None;
+}
+
+/// Updates the node or else the edge under the cursor
+fn update_hover(scene_context: &mut SceneContext, window: &Window) {
+    let point = cursor_world_position(scene_context, window);
+
+    scene_context.hovered_node_index = node_under(scene_context, point);
+    scene_context.hovered_edge_index = if scene_context.hovered_node_index.is_some() {
+        None
+    } else {
+        let max_distance =
+            EDGE_PICK_DISTANCE_PX * world_units_per_pixel(window, &scene_context.camera);
+        pick_edge(scene_context, point, max_distance)
+    };
+}
+
+/// Closest node to `point` if `point` is within its drawn radius
+fn node_under(scene_context: &SceneContext, point: Vec2) -> Option<u32> {
+    let index = scene_context
+        .simulator
+        .find_closest_node_index(point.extend(0.0))?;
+    let rb = scene_context.simulator.rigid_bodies.read().unwrap()[index as usize].clone();
+    (rb.position.distance(point) <= draw::node_radius(scene_context, index as usize, &rb))
+        .then_some(index)
+}
+
+/// Index of the edge closest to `point`, rebuilds the edge grid when the nodes may have moved
+fn pick_edge(scene_context: &mut SceneContext, point: Vec2, max_distance: f32) -> Option<usize> {
+    let spring_count = scene_context.simulator.springs.read().unwrap().len();
+    let stale = scene_context.simulator.is_running()
+        || scene_context
+            .edge_grid
+            .as_ref()
+            .is_none_or(|grid| grid.len() != spring_count);
+    if stale {
+        scene_context.edge_grid = Some(scene_context.simulator.edge_grid());
+    }
+
+    scene_context
+        .edge_grid
+        .as_ref()
+        .and_then(|grid| grid.closest(point, max_distance))
+        .map(|(index, _)| index)
+}
+
+/// Size of a pixel at the distance of the camera focus
+fn world_units_per_pixel(window: &Window, camera: &Camera) -> f32 {
+    let height = window.inner_size().height.max(1) as f32;
+    2.0 * camera.distance() * (FOV / 2.0).tan() / height
+}
+
+/// Loads the graph of `path` in the background. It replaces the simulation of the scene, keeping
+/// the simulator settings, or is merged into it while shift is held.
+fn load_dropped_file(scene_context: &mut SceneContext, path: &Path) {
+    let formats = Arc::clone(&scene_context.formats);
+    let loading = if is_shift_held(&scene_context.event_manager) {
+        Loading::merge(path, formats)
+    } else {
+        Loading::spawn(path, formats, scene_context.simulator.settings(), true)
+    };
+    scene_context.loading = Some(loading);
+}
+
+/// Replaces the simulation of the scene or merges the graph into it once it finished loading
+fn finish_loading(scene_context: &mut SceneContext) {
+    let Some(result) = scene_context.loading.as_ref().and_then(Loading::try_finish) else {
+        return;
+    };
+    let loading = scene_context.loading.take().unwrap();
+
+    let (simulator, labels) = match result {
+        Ok(Loaded::Simulator(simulator, labels)) => (*simulator, labels),
+        Ok(Loaded::Graph(graph)) => {
+            merge_loaded_graph(scene_context, &graph);
+            return;
+        }
+        Err(e) => {
+            eprintln!("Failed to load {}: {}", loading.path.display(), e);
+            return;
+        }
+    };
+
+    let old_simulator = &scene_context.simulator;
+    if !loading.inherit_state {
+        simulator.resume();
+    } else {
+        simulator.set_parameters(old_simulator.parameters());
+        if old_simulator.is_running() {
+            simulator.resume();
+        }
+    }
+
+    scene_context.graph_changes = simulator.subscribe_changes();
+    scene_context.simulator = Arc::new(simulator);
+    scene_context.selected_node_index = None;
+    scene_context.selected_edge_index = None;
+    scene_context.hovered_node_index = None;
+    scene_context.hovered_edge_index = None;
+    scene_context.edge_grid = None;
+    scene_context.group_drag = None;
+    scene_context.edge_source = None;
+    scene_context.selection.clear();
+    scene_context.partition = Some(analysis::communities(&scene_context.simulator));
+    if let Some(metric_colors) = &mut scene_context.metric_colors {
+        metric_colors.reset(&scene_context.simulator);
+    }
+    scene_context.labels = Some(labels);
+    spawn_simulation_thread(&scene_context.simulator);
+}
+
+/// Adds `graph` to the simulation of the scene, nodes with the label of an existing node are
+/// merged into it
+fn merge_loaded_graph(
+    scene_context: &mut SceneContext,
+    graph: &StableGraph<String, (), Directed, u32>,
+) {
+    let labels = scene_context.labels.get_or_insert_with(Vec::new);
+    let existing: HashMap<&str, u32> = labels
+        .iter()
+        .enumerate()
+        .filter(|(_, label)| !label.is_empty())
+        .map(|(i, label)| (label.as_str(), i as u32))
+        .collect();
+    let mapping = scene_context
+        .simulator
+        .merge_graph(graph, |_, label| existing.get(label.as_str()).copied());
+
+    for (node, index) in graph
+        .node_indices()
+        .map(|n| (n, mapping[n.index()] as usize))
+    {
+        if labels.len() <= index {
+            labels.resize(index + 1, String::new());
+        }
+        if labels[index].is_empty() {
+            labels[index] = graph[node].clone();
+        }
+    }
+    scene_context.partition = Some(analysis::communities(&scene_context.simulator));
+    if let Some(metric_colors) = &mut scene_context.metric_colors {
+        metric_colors.reset(&scene_context.simulator);
     }
 }
 
@@ -192,56 +1632,186 @@ struct SceneContext {
     event_manager: EventManager,
     cursor_pos: Vec2,
     selected_node_index: Option<u32>,
+    selected_edge_index: Option<usize>,
+    hovered_node_index: Option<u32>,
+    hovered_edge_index: Option<usize>,
+    edge_grid: Option<EdgeGrid>,
     simulator: Arc<Simulator>,
+    /// Changes of `simulator`, used to follow reordered nodes
+    graph_changes: Receiver<GraphChange>,
     last_pause: Instant,
 
-    toggle_sim: Arc<RwLock<bool>>,
     place_mode: bool,
+    show_quadtree_error: bool,
+    show_quadtree: bool,
+    show_energy_graph: bool,
+    aggregate_edges: bool,
+    bundle_edges: bool,
+    semantic_zoom: SemanticZoom,
+    partition: Option<Vec<u32>>,
+    labels: Option<Vec<String>>,
+    color_scheme: ColorScheme,
+    /// Overrides `color_scheme`, see `Renderer::set_dynamic_metric`
+    metric_colors: Option<MetricColors>,
+    /// `ColorScheme::Velocity` or `ColorScheme::Force` while toggled on, overrides the other colors
+    heatmap: Option<ColorScheme>,
+    node_size_fn: Option<Arc<NodeSizeFn>>,
+    draw_order: DrawOrder,
+    layers: Layers,
+    draw_passes: Vec<Box<dyn DrawPass>>,
+    loading: Option<Loading>,
+    /// Formats of dropped graph files
+    formats: Arc<FormatRegistry>,
+    energy_history: VecDeque<f32>,
+    show_hulls: bool,
+    show_minimap: bool,
+    show_hud: bool,
+    group_drag: Option<GroupDrag>,
+    /// Node a new edge is dragged from in place mode
+    edge_source: Option<u32>,
+    selection: Selection,
+    stream: Option<Box<dyn GraphStream + Send>>,
+    annotations: Vec<Annotation>,
+    editing_note: Option<NoteEdit>,
+    /// Start of an arrow that ends at the cursor until `J` is pressed again
+    arrow_start: Option<Vec2>,
+}
+
+/// Note that typed text is added to
+struct NoteEdit {
+    index: usize,
+    /// The character of the key that started the edit is still to come
+    skip_char: bool,
+}
+
+/// Group of nodes that is dragged by its hull, the members are pinned during the drag
+struct GroupDrag {
+    members: Vec<u32>,
+    was_fixed: Vec<bool>,
+    last: Vec2,
 }
 
 impl SceneContext {
+    /// Color of every node in `rigid_bodies`
+    fn node_colors(&self, rigid_bodies: &[RigidBody2D], springs: &[Spring]) -> Vec<[f32; 4]> {
+        let color_scheme = match (&self.heatmap, &self.metric_colors) {
+            (Some(heatmap), _) => heatmap,
+            (None, Some(metric_colors)) => return metric_colors.colors(rigid_bodies.len()),
+            (None, None) => &self.color_scheme,
+        };
+        let forces = if matches!(color_scheme, ColorScheme::Force) {
+            self.simulator.net_forces()
+        } else {
+            vec![]
+        };
+        color_scheme.colors(rigid_bodies, springs, self.partition.as_deref(), &forces)
+    }
+
     pub fn new(simulator: Simulator) -> Self {
         let mut camera = Camera::new(Vec3::new(0.0, 0.0, 150.0));
         camera.look_at(&Vec3::ZERO);
+        let communities = analysis::communities(&simulator);
 
         Self {
             camera,
             event_manager: EventManager::new(),
             cursor_pos: Vec2::ZERO,
             selected_node_index: None,
+            selected_edge_index: None,
+            hovered_node_index: None,
+            hovered_edge_index: None,
+            edge_grid: None,
+            graph_changes: simulator.subscribe_changes(),
             simulator: Arc::new(simulator),
             last_pause: Instant::now(),
-            toggle_sim: Arc::new(RwLock::new(false)),
             place_mode: false,
+            show_quadtree_error: false,
+            show_quadtree: false,
+            show_energy_graph: false,
+            aggregate_edges: false,
+            bundle_edges: false,
+            semantic_zoom: SemanticZoom::default(),
+            partition: Some(communities),
+            labels: None,
+            color_scheme: ColorScheme::default(),
+            metric_colors: None,
+            heatmap: None,
+            node_size_fn: None,
+            draw_order: DrawOrder::default(),
+            layers: Layers::default(),
+            draw_passes: vec![],
+            loading: None,
+            formats: Arc::new(FormatRegistry::default()),
+            energy_history: VecDeque::with_capacity(ENERGY_HISTORY_LEN),
+            show_hulls: false,
+            show_minimap: false,
+            show_hud: false,
+            group_drag: None,
+            edge_source: None,
+            stream: None,
+            selection: Selection::default(),
+            annotations: vec![],
+            editing_note: None,
+            arrow_start: None,
         }
     }
 }
 
+/// Draws the scene, the caller finishes the frame after drawing overlays
 fn draw_graph(
     scene_context: Arc<Mutex<SceneContext>>,
+    render_state: &mut RenderState,
     display: &Display<WindowSurface>,
     window: &Window,
     highlight_index: &[u32],
-) {
+    label_budget: usize,
+) -> Frame {
     let mut target = display.draw();
-    target.clear_color_and_depth((0.0, 0.0, 0.0, 1.0), 1.0);
+    let [r, g, b, a] = scene_context.lock().unwrap().layers.clear_color;
+    target.clear_color_and_depth((r, g, b, a), 1.0);
 
     let uniforms = uniform! {
         matrix: scene_context.lock().unwrap().camera.matrix().to_cols_array_2d(),
         projection: build_perspective_matrix(window).to_cols_array_2d()
     };
 
+    // See `layer` for the depth strategy
     let params = glium::DrawParameters {
         depth: glium::Depth {
-            test: glium::draw_parameters::DepthTest::IfLess,
+            test: glium::draw_parameters::DepthTest::IfLessOrEqual,
             write: true,
             ..Default::default()
         },
         ..Default::default()
     };
 
+    draw::draw_quadtree(
+        &scene_context.lock().unwrap(),
+        &mut target,
+        display,
+        &uniforms,
+        &params,
+    );
+    draw::draw_hulls(
+        &scene_context.lock().unwrap(),
+        &mut target,
+        display,
+        &uniforms,
+        &params,
+    );
+    {
+        let mut scene_context = scene_context.lock().unwrap();
+        let pixels_per_unit = 1.0 / world_units_per_pixel(window, &scene_context.camera);
+        let simulator = Arc::clone(&scene_context.simulator);
+        scene_context.semantic_zoom.update(
+            &simulator.rigid_bodies.read().unwrap(),
+            &simulator.springs.read().unwrap(),
+            pixels_per_unit,
+        );
+    }
     draw::draw_edge(
         Arc::clone(&scene_context),
+        render_state,
         &mut target,
         display,
         &uniforms,
@@ -249,6 +1819,7 @@ fn draw_graph(
     );
     draw::draw_node(
         Arc::clone(&scene_context),
+        render_state,
         &mut target,
         display,
         &uniforms,
@@ -256,18 +1827,123 @@ fn draw_graph(
         highlight_index,
     );
 
-    target.finish().unwrap();
+    let mut scene_context = scene_context.lock().unwrap();
+    let scene_context = &mut *scene_context;
+
+    let view_projection = build_perspective_matrix(window) * scene_context.camera.matrix();
+    let cursor = vector_plane_intersection(
+        cursor_pos_to_world_vec(window, &scene_context.camera, &scene_context.cursor_pos),
+        scene_context.camera.position,
+        Vec4::new(0.0, 0.0, 1.0, 0.0),
+        2,
+    );
+    draw::draw_labels(
+        scene_context,
+        &mut target,
+        display,
+        &uniforms,
+        &params,
+        view_projection,
+        cursor.xy(),
+        label_budget,
+    );
+
+    draw::draw_annotations(
+        scene_context,
+        &mut target,
+        display,
+        &uniforms,
+        &params,
+        cursor.xy(),
+    );
+
+    draw::draw_edge_highlight(
+        scene_context,
+        &mut target,
+        display,
+        &uniforms,
+        &params,
+        cursor.xy(),
+    );
+
+    let size = window.inner_size();
+    let window_size = Vec2::new(size.width as f32, size.height as f32);
+
+    if !scene_context.draw_passes.is_empty() {
+        let snapshot = scene_context.simulator.snapshot();
+        let mut context = DrawContext {
+            frame: &mut target,
+            display,
+            view: scene_context.camera.matrix(),
+            projection: build_perspective_matrix(window),
+            parameters: &params,
+            snapshot: &snapshot,
+            window_size,
+            layers: &scene_context.layers,
+        };
+        for pass in scene_context.draw_passes.iter_mut() {
+            pass.draw(&mut context);
+        }
+    }
+
+    if let Some(loading) = &scene_context.loading {
+        draw::draw_progress_bar(loading.progress(), &mut target, display);
+    }
+
+    if scene_context.show_energy_graph {
+        draw::draw_sparkline(&scene_context.energy_history, &mut target, display);
+    }
+
+    if let Some(minimap) = minimap(scene_context, window) {
+        // Part of the z = 0 plane the camera sees
+        let frustum = [
+            Vec2::ZERO,
+            Vec2::new(window_size.x, 0.0),
+            window_size,
+            Vec2::new(0.0, window_size.y),
+        ]
+        .map(|corner| screen_to_world(scene_context, window, corner));
+        draw::draw_minimap(
+            scene_context,
+            &minimap,
+            &frustum,
+            &mut target,
+            display,
+            window_size,
+        );
+    }
+
+    draw::draw_tooltip(scene_context, &mut target, display, window_size);
+
+    target
+}
+
+fn is_shift_held(event_manager: &EventManager) -> bool {
+    event_manager.contains_key(&winit::event::VirtualKeyCode::LShift)
+        || event_manager.contains_key(&winit::event::VirtualKeyCode::RShift)
+}
+
+fn window_title(title: &str, active_tab: usize, tab_count: usize) -> String {
+    if tab_count > 1 {
+        format!("{} - Tab {}/{}", title, active_tab + 1, tab_count)
+    } else {
+        title.to_string()
+    }
 }
 
 fn build_perspective_matrix(window: &Window) -> Mat4 {
     let width = window.inner_size().width;
     let height = window.inner_size().height;
-    Mat4::perspective_infinite_rh(0.8, width as f32 / height as f32, 0.1)
+    Mat4::perspective_infinite_rh(FOV, width as f32 / height as f32, 0.1)
 }
 
 fn vector_plane_intersection(vec: Vec3, off: Vec3, plane: Vec4, accuracy: u32) -> Vec3 {
     let f = |r: f32| (plane.xyz() * (vec * r + off)).element_sum() - plane.w;
     let f_d = || (plane.xyz() * vec).element_sum();
+    if f_d() == 0.0 {
+        // The ray is parallel to the plane, e.g. when the camera is orbited edge-on
+        return off;
+    }
 
     let mut r_approx = rand::thread_rng().gen_range(-100.0..100.0);
 
@@ -316,41 +1992,113 @@ fn normalize_view_space(window: &Window, view_space_coordinate: &Vec2) -> Vec2 {
     normalized_view_space - 1.0
 }
 
-fn camera_movement(scene_context: &mut SceneContext, delta_time: f32) {
+fn camera_movement(scene_context: &mut SceneContext, delta_time: f32, key_bindings: &KeyBindings) {
     let event_manager = &scene_context.event_manager;
     let camera = &mut scene_context.camera;
+    camera.update(delta_time);
 
     // Camera movement
-    if event_manager.contains_key(&winit::event::VirtualKeyCode::W) {
-        camera.position[1] += CAMERA_MOVEMENT_SENSITIVITY * delta_time;
+    let step = CAMERA_MOVEMENT_SENSITIVITY * delta_time;
+    if event_manager.contains_key(&key_bindings.move_up) {
+        camera.translate(camera.up * step);
     }
-    if event_manager.contains_key(&winit::event::VirtualKeyCode::S) {
-        camera.position[1] -= CAMERA_MOVEMENT_SENSITIVITY * delta_time;
+    if event_manager.contains_key(&key_bindings.move_down) {
+        camera.translate(-camera.up * step);
     }
-    if event_manager.contains_key(&winit::event::VirtualKeyCode::A) {
-        camera.position[0] -= CAMERA_MOVEMENT_SENSITIVITY * delta_time;
+    if event_manager.contains_key(&key_bindings.move_left) {
+        camera.translate(-camera.right * step);
     }
-    if event_manager.contains_key(&winit::event::VirtualKeyCode::D) {
-        camera.position[0] += CAMERA_MOVEMENT_SENSITIVITY * delta_time;
+    if event_manager.contains_key(&key_bindings.move_right) {
+        camera.translate(camera.right * step);
+    }
+}
+
+/// Types into the edited note, `Return` finishes the note and `Escape` removes it.
+///
+/// Returns if the event was used.
+fn edit_note(scene_context: &mut SceneContext, event: &WindowEvent) -> bool {
+    let Some(edit) = &mut scene_context.editing_note else {
+        return false;
+    };
+    let index = edit.index;
+    let Some(Annotation::Note { text, .. }) = scene_context.annotations.get_mut(index) else {
+        scene_context.editing_note = None;
+        return false;
+    };
+
+    match event {
+        WindowEvent::ReceivedCharacter(_) if edit.skip_char => edit.skip_char = false,
+        WindowEvent::ReceivedCharacter(c) if !c.is_control() => text.push(*c),
+        WindowEvent::ReceivedCharacter(_) => (),
+        WindowEvent::KeyboardInput { input, .. } => {
+            match (input.state, input.virtual_keycode) {
+                (ElementState::Pressed, Some(winit::event::VirtualKeyCode::Back)) => {
+                    text.pop();
+                }
+                (ElementState::Pressed, Some(winit::event::VirtualKeyCode::Return)) => {
+                    scene_context.editing_note = None;
+                }
+                (ElementState::Pressed, Some(winit::event::VirtualKeyCode::Escape)) => {
+                    scene_context.annotations.remove(index);
+                    scene_context.editing_note = None;
+                }
+                // Keys that were held before typing started
+                (ElementState::Released, Some(keycode)) => {
+                    scene_context.event_manager.remove_key(&keycode);
+                }
+                _ => (),
+            }
+        }
+        _ => return false,
     }
+    true
+}
+
+/// If the camera orbits with the cursor, while alt and the left mouse button are held
+fn is_orbiting(event_manager: &EventManager) -> bool {
+    event_manager.contains_mouse_button(&winit::event::MouseButton::Left)
+        && (event_manager.contains_key(&winit::event::VirtualKeyCode::LAlt)
+            || event_manager.contains_key(&winit::event::VirtualKeyCode::RAlt))
 }
 
-fn events(event: &Event<'_, ()>, scene_context: Arc<Mutex<SceneContext>>) {
+fn events(
+    event: &Event<'_, ()>,
+    scene_context: Arc<Mutex<SceneContext>>,
+    window: &Window,
+    scroll_sensitivity: f32,
+    key_bindings: &KeyBindings,
+) {
     let mut scene_context = scene_context.lock().unwrap();
 
+    if let Event::WindowEvent { event, .. } = event {
+        if scene_context.editing_note.is_some() && edit_note(&mut scene_context, event) {
+            return;
+        }
+    }
+
     #[allow(clippy::collapsible_match)]
     if let Event::WindowEvent { event, .. } = event {
         match event {
             WindowEvent::MouseWheel { delta, .. } => {
                 if let winit::event::MouseScrollDelta::LineDelta(_, y) = delta {
-                    if *y < 0.0 {
-                        scene_context.camera.position[2] -= SCROLL_SENSITIVITY;
-                    } else if *y > 0.0 {
-                        scene_context.camera.position[2] += SCROLL_SENSITIVITY;
-                    }
+                    let cursor = cursor_world_position(&scene_context, window);
+                    scene_context
+                        .camera
+                        .zoom_towards_smooth(cursor.extend(0.0), (scroll_sensitivity * y).exp());
                 }
             }
             WindowEvent::MouseInput { state, button, .. } => {
+                // Clicks on the minimap move the camera instead of selecting
+                if *state == ElementState::Pressed && *button == winit::event::MouseButton::Left {
+                    if let Some(minimap) = minimap(&scene_context, window) {
+                        if minimap.contains(scene_context.cursor_pos) {
+                            let point = minimap.to_world(scene_context.cursor_pos);
+                            scene_context.camera.center_on(point.extend(0.0));
+                            return;
+                        }
+                    }
+                }
+
                 let event_manager = &mut scene_context.event_manager;
                 match state {
                     ElementState::Pressed => {
@@ -361,25 +2109,48 @@ fn events(event: &Event<'_, ()>, scene_context: Arc<Mutex<SceneContext>>) {
                     }
                 }
             }
+            WindowEvent::DroppedFile(path) => {
+                load_dropped_file(&mut scene_context, path);
+            }
             WindowEvent::CursorMoved { position, .. } => {
-                scene_context.cursor_pos[0] = position.x as f32;
-                scene_context.cursor_pos[1] = position.y as f32;
+                let position = Vec2::new(position.x as f32, position.y as f32);
+                let delta = position - scene_context.cursor_pos;
+                scene_context.cursor_pos = position;
+
+                let event_manager = &scene_context.event_manager;
+                if event_manager.contains_mouse_button(&winit::event::MouseButton::Middle) {
+                    let scale = world_units_per_pixel(window, &scene_context.camera);
+                    scene_context.camera.pan(delta * scale);
+                } else if is_orbiting(event_manager) {
+                    scene_context.camera.orbit(delta * ORBIT_SENSITIVITY);
+                }
             }
             WindowEvent::KeyboardInput { input, .. } => match input.virtual_keycode {
-                Some(winit::event::VirtualKeyCode::Space) => {
+                Some(keycode) if keycode == key_bindings.pause => {
                     if scene_context.last_pause.elapsed().as_millis() >= 400 {
-                        {
-                            let mut toggle_sim_write_guard =
-                                scene_context.toggle_sim.write().unwrap();
-                            *toggle_sim_write_guard = !(*toggle_sim_write_guard);
+                        let sim = &scene_context.simulator;
+                        if sim.is_running() {
+                            sim.pause();
+                        } else {
+                            sim.resume();
                         }
                         scene_context.last_pause = Instant::now();
                     }
                 }
-                Some(winit::event::VirtualKeyCode::Return) => {
+                Some(keycode)
+                    if keycode == key_bindings.center && input.state == ElementState::Pressed =>
+                {
                     let avg = scene_context.simulator.average_node_position();
-                    scene_context.camera.position[0] = avg[0];
-                    scene_context.camera.position[1] = avg[1];
+                    scene_context.camera.center_on(avg.extend(0.0));
+                }
+                Some(keycode)
+                    if keycode == key_bindings.fit_view && input.state == ElementState::Pressed =>
+                {
+                    if let Some(bounds) = scene_context.simulator.bounding_box() {
+                        let size = window.inner_size();
+                        let aspect = size.width.max(1) as f32 / size.height.max(1) as f32;
+                        scene_context.camera.fit_bounds(&bounds, FOV, aspect);
+                    }
                 }
                 Some(keycode) => {
                     let event_manager = &mut scene_context.event_manager;
@@ -0,0 +1,82 @@
+use glam::Vec2;
+
+use crate::edge_grid::distance_to_segment;
+
+/// Note or marker pinned to world coordinates, see `Renderer::add_annotation`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Annotation {
+    /// Text with its lower left corner at `position`
+    Note {
+        position: Vec2,
+        text: String,
+        color: [f32; 4],
+    },
+    /// Arrow pointing from `from` to `to`
+    Arrow {
+        from: Vec2,
+        to: Vec2,
+        color: [f32; 4],
+    },
+    /// Highlighted rectangle between the corners `min` and `max`
+    Region {
+        min: Vec2,
+        max: Vec2,
+        color: [f32; 4],
+    },
+}
+
+impl Annotation {
+    pub fn note(position: Vec2, text: &str) -> Self {
+        Annotation::Note {
+            position,
+            text: text.to_string(),
+            color: [1.0, 0.9, 0.4, 1.0],
+        }
+    }
+
+    pub fn arrow(from: Vec2, to: Vec2) -> Self {
+        Annotation::Arrow {
+            from,
+            to,
+            color: [1.0, 0.9, 0.4, 1.0],
+        }
+    }
+
+    /// Region between two opposite corners
+    pub fn region(a: Vec2, b: Vec2) -> Self {
+        Annotation::Region {
+            min: a.min(b),
+            max: a.max(b),
+            color: [0.4, 0.6, 1.0, 1.0],
+        }
+    }
+
+    /// Distance from `point` to the anchor of a note, the line of an arrow or a region, `0.0`
+    /// inside a region
+    pub fn distance(&self, point: Vec2) -> f32 {
+        match self {
+            Annotation::Note { position, .. } => position.distance(point),
+            Annotation::Arrow { from, to, .. } => distance_to_segment(point, *from, *to),
+            Annotation::Region { min, max, .. } => {
+                (*min - point).max(point - *max).max(Vec2::ZERO).length()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_distance() {
+        let region = Annotation::region(Vec2::new(2.0, 2.0), Vec2::ZERO);
+        assert_eq!(region.distance(Vec2::ONE), 0.0);
+        assert_eq!(region.distance(Vec2::new(5.0, 6.0)), 5.0);
+
+        let arrow = Annotation::arrow(Vec2::ZERO, Vec2::new(4.0, 0.0));
+        assert_eq!(arrow.distance(Vec2::new(2.0, 1.0)), 1.0);
+        assert_eq!(Annotation::note(Vec2::ZERO, "a").distance(Vec2::X), 1.0);
+    }
+}
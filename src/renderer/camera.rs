@@ -1,28 +1,121 @@
-use glam::{Mat4, Vec3};
+use glam::{Mat4, Quat, Vec2, Vec3};
+
+use crate::quadtree::BoundingBox2D;
+
+/// Closest distance the camera can zoom to its focus point
+const MIN_DISTANCE: f32 = 1.0;
+/// Space around the bounds fitted into the view, relative to their size
+const FIT_MARGIN: f32 = 1.1;
+/// Rate a smooth zoom approaches its target with, per second
+const ZOOM_SMOOTHING: f32 = 15.0;
 
 pub struct Camera {
     pub position: Vec3,
+    /// Point the camera looks at and orbits around
+    pub focus: Vec3,
     pub direction: Vec3,
     pub right: Vec3,
     pub up: Vec3,
+    /// Point of a smooth zoom and the logarithm of the zoom factor that is still left to apply
+    zoom: Option<(Vec3, f32)>,
 }
 
 impl Camera {
     pub fn new(position: Vec3) -> Self {
         Self {
             position,
+            focus: Vec3::ZERO,
             direction: Vec3::ZERO,
             right: Vec3::ZERO,
             up: Vec3::ZERO,
+            zoom: None,
         }
     }
 
     pub fn look_at(&mut self, look_at: &Vec3) {
+        self.focus = *look_at;
         self.direction = (self.position - look_at).normalize();
         self.right = Vec3::new(0.0, 1.0, 0.0).cross(self.direction);
         self.up = self.direction.cross(self.right);
     }
 
+    /// Distance between the camera and its focus point
+    pub fn distance(&self) -> f32 {
+        self.position.distance(self.focus)
+    }
+
+    /// Moves the camera and its focus point by `offset`
+    pub fn translate(&mut self, offset: Vec3) {
+        self.position += offset;
+        self.focus += offset;
+    }
+
+    /// Moves the camera so it looks at `point` from the same direction and distance
+    pub fn center_on(&mut self, point: Vec3) {
+        self.translate(point - self.focus);
+    }
+
+    /// Moves the camera and its focus point parallel to the screen, `delta` is in screen space
+    /// with y pointing down
+    pub fn pan(&mut self, delta: Vec2) {
+        self.translate(self.up * delta.y - self.right * delta.x);
+    }
+
+    /// Rotates the camera around its focus point like an arcball, `angles` are the rotation
+    /// around the up and the right axis in radians
+    pub fn orbit(&mut self, angles: Vec2) {
+        let rotation = Quat::from_axis_angle(self.up, -angles.x)
+            * Quat::from_axis_angle(self.right, -angles.y);
+        let offset = rotation * (self.position - self.focus);
+
+        self.position = self.focus + offset;
+        self.direction = offset.normalize();
+        self.right = (rotation * self.up).cross(self.direction).normalize();
+        self.up = self.direction.cross(self.right);
+    }
+
+    /// Scales the distance of the camera and its focus point to `point` by `factor`, so `point`
+    /// stays at the same place on the screen
+    pub fn zoom_towards(&mut self, point: Vec3, factor: f32) {
+        let factor = factor.max(MIN_DISTANCE / self.distance().max(MIN_DISTANCE));
+        self.position = point + (self.position - point) * factor;
+        self.focus = point + (self.focus - point) * factor;
+    }
+
+    /// Like `zoom_towards`, but eases into the zoom over the next frames, see `update`.
+    ///
+    /// Zooming again before the zoom finished adds to the remaining zoom.
+    pub fn zoom_towards_smooth(&mut self, point: Vec3, factor: f32) {
+        let remaining = self.zoom.map_or(0.0, |(_, remaining)| remaining);
+        self.zoom = Some((point, remaining + factor.ln()));
+    }
+
+    /// Advances a smooth zoom by `delta_time` seconds
+    pub fn update(&mut self, delta_time: f32) {
+        let Some((point, remaining)) = self.zoom else {
+            return;
+        };
+        let step = if remaining.abs() < 1e-4 {
+            remaining
+        } else {
+            remaining * (1.0 - (-delta_time * ZOOM_SMOOTHING).exp())
+        };
+        self.zoom_towards(point, step.exp());
+        self.zoom = (step != remaining).then_some((point, remaining - step));
+    }
+
+    /// Moves the camera along its viewing direction so `bounds` on the `z = 0` plane fill the view
+    /// of a perspective projection with the vertical field of view `fov` and the aspect ratio
+    /// `aspect`
+    pub fn fit_bounds(&mut self, bounds: &BoundingBox2D, fov: f32, aspect: f32) {
+        let tan = (fov / 2.0).tan();
+        let distance = (bounds.height / 2.0 / tan).max(bounds.width / 2.0 / (tan * aspect));
+
+        self.zoom = None;
+        self.focus = bounds.center.extend(0.0);
+        self.position = self.focus + self.direction * (distance * FIT_MARGIN).max(MIN_DISTANCE);
+    }
+
     pub fn matrix(&self) -> Mat4 {
         let d = self.direction;
         let r = self.right;
@@ -39,3 +132,68 @@ impl Camera {
         ])
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn camera() -> Camera {
+        let mut camera = Camera::new(Vec3::new(0.0, 0.0, 100.0));
+        camera.look_at(&Vec3::ZERO);
+        camera
+    }
+
+    #[test]
+    fn test_orbit_keeps_distance_to_focus() {
+        let mut camera = camera();
+        camera.orbit(Vec2::new(0.3, -0.7));
+        assert!((camera.distance() - 100.0).abs() < 1e-3);
+
+        // The focus stays in the center of the view
+        let focus = camera.matrix().transform_point3(camera.focus);
+        assert!(focus.truncate().length() < 1e-3);
+        assert!((camera.up.length() - 1.0).abs() < 1e-5);
+        assert!(camera.up.dot(camera.direction).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_zoom_towards_point() {
+        let mut camera = camera();
+        let point = Vec3::new(10.0, 0.0, 0.0);
+        camera.zoom_towards(point, 0.5);
+        assert_eq!(camera.position, Vec3::new(5.0, 0.0, 50.0));
+        assert_eq!(camera.focus, Vec3::new(5.0, 0.0, 0.0));
+
+        camera.zoom_towards(point, 0.0);
+        assert!((camera.distance() - MIN_DISTANCE).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_fit_bounds() {
+        let mut camera = camera();
+        let bounds = BoundingBox2D::new(Vec2::new(50.0, 20.0), 400.0, 100.0);
+        camera.fit_bounds(&bounds, 0.8, 2.0);
+        assert_eq!(camera.focus, Vec3::new(50.0, 20.0, 0.0));
+
+        // The corners of the bounds are inside the view, the wider side touches its margin
+        let projection = Mat4::perspective_infinite_rh(0.8, 2.0, 0.1) * camera.matrix();
+        let corner = projection.project_point3(Vec3::new(250.0, 70.0, 0.0));
+        assert!((corner.x - 1.0 / FIT_MARGIN).abs() < 1e-3);
+        assert!(corner.y.abs() < 1.0);
+    }
+
+    #[test]
+    fn test_smooth_zoom_reaches_target() {
+        let mut camera = camera();
+        camera.zoom_towards_smooth(Vec3::ZERO, 0.5);
+        camera.zoom_towards_smooth(Vec3::ZERO, 0.5);
+
+        camera.update(0.01);
+        assert!(camera.distance() < 100.0 && camera.distance() > 25.0);
+        for _ in 0..200 {
+            camera.update(0.01);
+        }
+        assert!((camera.distance() - 25.0).abs() < 1e-2);
+        assert!(camera.zoom.is_none());
+    }
+}
@@ -0,0 +1,182 @@
+use std::sync::Arc;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use glam::Vec2;
+
+use crate::properties::{RigidBody2D, Spring};
+
+const LOW: [f32; 3] = [0.2, 0.4, 1.0];
+const HIGH: [f32; 3] = [1.0, 0.3, 0.2];
+
+/// Maps a node index and its rigid body to a color
+pub type NodeColorFn = dyn Fn(u32, &RigidBody2D) -> [f32; 4] + Send + Sync;
+
+/// How nodes are colored
+#[derive(Clone)]
+pub enum ColorScheme {
+    /// Random color seeded by the node index
+    Random,
+    /// Gradient from blue to red by the number of edges of a node
+    Degree,
+    /// Gradient from blue to red by the mass of a node
+    Mass,
+    /// Gradient from blue for frozen and pinned nodes to red for the fastest node
+    Velocity,
+    /// Gradient from blue for frozen and pinned nodes to red for the node with the largest net
+    /// force in the last simulation step, see `Simulator::net_forces`
+    Force,
+    /// Random color seeded by the group set with `Renderer::set_partition`, which defaults to the
+    /// communities found by `analysis::communities`
+    Community,
+    /// Colors the groups set with `Renderer::set_partition`, or the nodes if there is no partition,
+    /// by cycling through the palette
+    Palette(Vec<[f32; 4]>),
+    /// See `Renderer::set_node_color_fn`
+    Custom(Arc<NodeColorFn>),
+}
+
+impl Default for ColorScheme {
+    fn default() -> Self {
+        ColorScheme::Community
+    }
+}
+
+impl ColorScheme {
+    /// Color of every node in `rigid_bodies`
+    pub(crate) fn colors(
+        &self,
+        rigid_bodies: &[RigidBody2D],
+        springs: &[Spring],
+        partition: Option<&[u32]>,
+        forces: &[Vec2],
+    ) -> Vec<[f32; 4]> {
+        let group = |i: usize| partition.and_then(|p| p.get(i).copied());
+
+        match self {
+            ColorScheme::Random => (0..rigid_bodies.len())
+                .map(|i| random_color(i as u64))
+                .collect(),
+            ColorScheme::Degree => {
+                let mut degree = vec![0usize; rigid_bodies.len()];
+                for spring in springs {
+                    degree[spring.rb1] += 1;
+                    degree[spring.rb2] += 1;
+                }
+                let max = degree.iter().copied().max().unwrap_or(0).max(1) as f32;
+                degree.iter().map(|d| gradient(*d as f32 / max)).collect()
+            }
+            ColorScheme::Mass => {
+                let max = rigid_bodies
+                    .iter()
+                    .map(|rb| rb.mass)
+                    .fold(f32::EPSILON, f32::max);
+                rigid_bodies
+                    .iter()
+                    .map(|rb| gradient(rb.mass / max))
+                    .collect()
+            }
+            ColorScheme::Velocity => heatmap(rigid_bodies, |_, rb| rb.velocity.length()),
+            ColorScheme::Force => heatmap(rigid_bodies, |i, _| {
+                forces.get(i).map_or(0.0, |force| force.length())
+            }),
+            ColorScheme::Community => (0..rigid_bodies.len())
+                .map(|i| group(i).map_or([0.5, 0.5, 0.5, 1.0], |g| random_color(g as u64)))
+                .collect(),
+            ColorScheme::Palette(palette) if !palette.is_empty() => (0..rigid_bodies.len())
+                .map(|i| palette[group(i).map_or(i, |g| g as usize) % palette.len()])
+                .collect(),
+            ColorScheme::Palette(_) => vec![[1.0; 4]; rigid_bodies.len()],
+            ColorScheme::Custom(f) => rigid_bodies
+                .iter()
+                .enumerate()
+                .map(|(i, rb)| f(i as u32, rb))
+                .collect(),
+        }
+    }
+}
+
+/// Gradient by `magnitude` relative to the largest one, nodes that don't move are blue
+fn heatmap<F>(rigid_bodies: &[RigidBody2D], magnitude: F) -> Vec<[f32; 4]>
+where
+    F: Fn(usize, &RigidBody2D) -> f32,
+{
+    let magnitudes: Vec<f32> = rigid_bodies
+        .iter()
+        .enumerate()
+        .map(|(i, rb)| {
+            if rb.fixed || rb.frozen {
+                0.0
+            } else {
+                magnitude(i, rb)
+            }
+        })
+        .collect();
+    let max = magnitudes.iter().copied().fold(f32::EPSILON, f32::max);
+    magnitudes.iter().map(|m| gradient(m / max)).collect()
+}
+
+fn random_color(seed: u64) -> [f32; 4] {
+    let mut rand = StdRng::seed_from_u64(seed);
+    [
+        (rand.gen_range(10..=100) as f32) / 100.0,
+        (rand.gen_range(10..=100) as f32) / 100.0,
+        (rand.gen_range(10..=100) as f32) / 100.0,
+        1.0,
+    ]
+}
+
+/// Blue to red for `t` in `0..=1`
+pub(super) fn gradient(t: f32) -> [f32; 4] {
+    let t = t.clamp(0.0, 1.0);
+    [
+        LOW[0] + (HIGH[0] - LOW[0]) * t,
+        LOW[1] + (HIGH[1] - LOW[1]) * t,
+        LOW[2] + (HIGH[2] - LOW[2]) * t,
+        1.0,
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_color_schemes() {
+        let rbs = vec![
+            RigidBody2D::new(Vec2::ZERO, 1.0),
+            RigidBody2D::new(Vec2::ONE, 4.0),
+        ];
+        let springs = vec![];
+
+        let mass = ColorScheme::Mass.colors(&rbs, &springs, None, &[]);
+        assert_eq!(mass[1], gradient(1.0));
+        assert_eq!(mass[0], gradient(0.25));
+
+        let palette = ColorScheme::Palette(vec![[1.0, 0.0, 0.0, 1.0], [0.0, 1.0, 0.0, 1.0]]);
+        let colors = palette.colors(&rbs, &springs, Some(&[1, 1]), &[]);
+        assert_eq!(colors, vec![[0.0, 1.0, 0.0, 1.0]; 2]);
+
+        let custom = ColorScheme::Custom(Arc::new(|i, _| [i as f32, 0.0, 0.0, 1.0]));
+        assert_eq!(
+            custom.colors(&rbs, &springs, None, &[])[1],
+            [1.0, 0.0, 0.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn test_heatmaps() {
+        let mut rbs = vec![RigidBody2D::new(Vec2::ZERO, 1.0); 3];
+        rbs[0].velocity = Vec2::new(2.0, 0.0);
+        rbs[1].velocity = Vec2::new(1.0, 0.0);
+        rbs[2].frozen = true;
+        let forces = [Vec2::ONE, Vec2::ZERO, Vec2::splat(4.0)];
+
+        let velocity = ColorScheme::Velocity.colors(&rbs, &[], None, &forces);
+        assert_eq!(velocity, vec![gradient(1.0), gradient(0.5), gradient(0.0)]);
+
+        // The frozen node is blue even though it has the largest force
+        let force = ColorScheme::Force.colors(&rbs, &[], None, &forces);
+        assert_eq!(force, vec![gradient(1.0), gradient(0.0), gradient(0.0)]);
+    }
+}
@@ -0,0 +1,86 @@
+use winit::event::VirtualKeyCode;
+
+/// Keys of the renderer controls, see `RendererBuilder::key_bindings`
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyBindings {
+    /// Start or pause the simulation
+    pub pause: VirtualKeyCode,
+    /// Center the camera on the average node position
+    pub center: VirtualKeyCode,
+    /// Move the camera so the whole graph is visible
+    pub fit_view: VirtualKeyCode,
+    pub move_up: VirtualKeyCode,
+    pub move_down: VirtualKeyCode,
+    pub move_left: VirtualKeyCode,
+    pub move_right: VirtualKeyCode,
+    /// Switch between dragging and placing nodes
+    pub place_mode: VirtualKeyCode,
+    pub next_tab: VirtualKeyCode,
+    /// Show the estimated Barnes-Hut error in the window title
+    pub quadtree_error: VirtualKeyCode,
+    /// Show the cells of the Barnes-Hut quadtree
+    pub quadtree: VirtualKeyCode,
+    /// Color the nodes by their velocity, then by their net force, then as before
+    pub heatmap: VirtualKeyCode,
+    /// Show the frame rate and simulation telemetry, see `Simulator::metrics`
+    pub hud: VirtualKeyCode,
+    pub screenshot: VirtualKeyCode,
+    /// Open or close the inspector window, see `RendererBuilder::inspector`
+    pub inspector: VirtualKeyCode,
+    /// Switch between the window and `RendererBuilder::fullscreen_mode`
+    pub fullscreen: VirtualKeyCode,
+    /// Start or stop recording
+    pub record: VirtualKeyCode,
+    pub bundle_edges: VirtualKeyCode,
+    /// Replace the edges between groups with one edge
+    pub aggregate_edges: VirtualKeyCode,
+    /// Show clusters of the coarsened graph instead of the nodes when zoomed out
+    pub semantic_zoom: VirtualKeyCode,
+    pub hulls: VirtualKeyCode,
+    pub energy_graph: VirtualKeyCode,
+    /// Show an overview of the whole graph, click it to move the camera
+    pub minimap: VirtualKeyCode,
+    /// Push the nodes around the cursor apart
+    pub explode: VirtualKeyCode,
+    /// Remove the selected nodes, the selected edge or the annotation under the cursor
+    pub delete: VirtualKeyCode,
+    pub note: VirtualKeyCode,
+    pub arrow: VirtualKeyCode,
+    /// Mark the area around the selected nodes
+    pub region: VirtualKeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            pause: VirtualKeyCode::Space,
+            center: VirtualKeyCode::Return,
+            fit_view: VirtualKeyCode::Home,
+            move_up: VirtualKeyCode::W,
+            move_down: VirtualKeyCode::S,
+            move_left: VirtualKeyCode::A,
+            move_right: VirtualKeyCode::D,
+            place_mode: VirtualKeyCode::P,
+            next_tab: VirtualKeyCode::Tab,
+            quadtree_error: VirtualKeyCode::E,
+            quadtree: VirtualKeyCode::Q,
+            heatmap: VirtualKeyCode::F,
+            hud: VirtualKeyCode::I,
+            screenshot: VirtualKeyCode::F12,
+            inspector: VirtualKeyCode::O,
+            fullscreen: VirtualKeyCode::F11,
+            record: VirtualKeyCode::R,
+            bundle_edges: VirtualKeyCode::B,
+            aggregate_edges: VirtualKeyCode::G,
+            semantic_zoom: VirtualKeyCode::Z,
+            hulls: VirtualKeyCode::H,
+            energy_graph: VirtualKeyCode::K,
+            minimap: VirtualKeyCode::V,
+            explode: VirtualKeyCode::X,
+            delete: VirtualKeyCode::Delete,
+            note: VirtualKeyCode::N,
+            arrow: VirtualKeyCode::J,
+            region: VirtualKeyCode::M,
+        }
+    }
+}
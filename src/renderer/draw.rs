@@ -1,85 +1,187 @@
 use core::f32;
 use std::{
+    collections::{HashMap, VecDeque},
     f32::consts::PI,
     sync::{Arc, Mutex},
 };
 
 use glium::{
     glutin::surface::WindowSurface,
-    implement_vertex,
+    implement_vertex, uniform,
     uniforms::{AsUniformValue, Uniforms, UniformsStorage},
     Display, DrawParameters, Frame, Surface,
 };
 
-use rand::{rngs::StdRng, Rng, SeedableRng};
+use glam::{Mat4, Vec2, Vec4};
 
-use super::{shapes, SceneContext};
-
-static VERTEX_SHADER_SRC: &str = r#"
-#version 150
-
-in vec3 position;
-in vec4 color;
-out vec4 vertex_color;
+use super::{
+    font, hud, hull,
+    layer::Layer,
+    minimap::Minimap,
+    order::NodeCategory,
+    render_state::{self, RenderState},
+    shader::{self, Shader},
+    shapes, Annotation, SceneContext,
+};
+use crate::{
+    properties::{RigidBody2D, Spring},
+    quadtree::BoundingBox2D,
+};
 
-uniform mat4 projection;
-uniform mat4 matrix;
+/// Distance between parallel edges relative to their length
+const PARALLEL_EDGE_SPACING: f32 = 0.15;
+const BEZIER_SEGMENTS: usize = 8;
+/// How far bundled edges are pulled towards the center of their bundle
+const BUNDLE_STRENGTH: f32 = 0.8;
+const BUNDLE_DIRECTIONS: i32 = 8;
+/// Width of a edge between two clusters of the semantic zoom that merges a single edge
+const CLUSTER_EDGE_WIDTH: f32 = 0.3;
+/// Radius of the outline of pinned nodes relative to the node radius
+const PINNED_OUTLINE_SCALE: f32 = 1.3;
+/// Text size, line spacing and padding of the tooltip and the HUD in pixels
+const BOX_TEXT_HEIGHT: f32 = 12.0;
+const BOX_LINE_HEIGHT: f32 = 20.0;
+pub const BOX_PADDING: f32 = 8.0;
 
-void main() {
-    vertex_color = color;
-    gl_Position = projection * matrix * vec4(position, 1.0);
+#[derive(Copy, Clone, Debug)]
+pub struct Vertex {
+    pub position: [f32; 3],
+    pub color: [f32; 4],
 }
-"#;
-
-static INSTANCE_SHADER_SRC: &str = r#"
-#version 150
 
-in vec3 position;
-in vec3 color;
-in vec3 color_attr;
-in vec3 world_position;
-in float scale;
+implement_vertex!(Vertex, position, color);
 
-out vec4 vertex_color;
+/// Per instance data of a node
+#[derive(Copy, Clone)]
+pub struct Attr {
+    color_attr: [f32; 4],
+    world_position: [f32; 3],
+    scale: f32,
+}
+implement_vertex!(Attr, color_attr, world_position, scale);
 
-uniform mat4 projection;
-uniform mat4 matrix;
+/// Members and convex hull of every group set with `Renderer::set_partition`
+pub fn group_hulls(scene_context: &SceneContext) -> Vec<(Vec<u32>, Vec<Vec2>)> {
+    let Some(partition) = &scene_context.partition else {
+        return vec![];
+    };
+    let rb_read_guard = scene_context.simulator.rigid_bodies.read().unwrap();
 
-void main() {
-    vertex_color = vec4(color_attr, 1.0);
-    gl_Position = projection * matrix * vec4((position*scale)+world_position, 1.0);
+    let mut groups: HashMap<u32, (Vec<u32>, Vec<Vec2>)> = HashMap::new();
+    for (i, (rb, group)) in rb_read_guard.iter().zip(partition).enumerate() {
+        if rb.rope {
+            continue;
+        }
+        let (members, points) = groups.entry(*group).or_default();
+        members.push(i as u32);
+        points.push(rb.position);
+    }
+    groups
+        .into_values()
+        .map(|(members, points)| (members, hull::convex_hull(&points)))
+        .collect()
 }
-"#;
 
-static FRAGMENT_SHADER_SRC: &str = r#"
-#version 140
+/// Outlines the cells of the Barnes-Hut quadtree behind the edges. The cells that are approximated
+/// as one body for the hovered or selected node are highlighted.
+pub fn draw_quadtree<H, R>(
+    scene_context: &SceneContext,
+    target: &mut Frame,
+    display: &Display<WindowSurface>,
+    uniform: &UniformsStorage<H, R>,
+    params: &DrawParameters,
+) where
+    H: AsUniformValue,
+    R: Uniforms,
+{
+    if !scene_context.show_quadtree {
+        return;
+    }
+    let quadtree = scene_context.simulator.quadtree();
+    let viewpoint = scene_context
+        .hovered_node_index
+        .or(scene_context.selected_node_index)
+        .and_then(|i| {
+            let rb_read = scene_context.simulator.rigid_bodies.read().unwrap();
+            rb_read.get(i as usize).map(|rb| rb.position)
+        });
 
-in vec4 vertex_color;
-out vec4 color;
+    let z = scene_context.layers.z(Layer::Hulls);
+    let cell = |bb: &BoundingBox2D, color: [f32; 4]| {
+        shapes::rectangle_lines(
+            [bb.center.x, bb.center.y, z],
+            color,
+            bb.width / 2.0,
+            bb.height / 2.0,
+        )
+    };
+    let mut shape: Vec<Vertex> = quadtree
+        .cells()
+        .iter()
+        .flat_map(|bb| cell(bb, [0.3, 0.3, 0.3, 1.0]))
+        .collect();
+    if let Some(position) = viewpoint {
+        let theta = scene_context.simulator.parameters().quadtree_theta;
+        for bb in quadtree.approximated_cells(&position, theta) {
+            shape.append(&mut cell(&bb, [0.9, 0.6, 0.2, 1.0]));
+        }
+    }
+    if shape.is_empty() {
+        return;
+    }
 
-void main() {
-    color = vec4(vertex_color);
+    let program = shader::program(display, Shader::Vertex, Shader::Fragment);
+    let vertex_buffer = glium::VertexBuffer::new(display, &shape).unwrap();
+    let indices = glium::index::NoIndices(glium::index::PrimitiveType::LinesList);
+    target
+        .draw(&vertex_buffer, indices, &program, uniform, params)
+        .unwrap();
 }
-"#;
 
-#[derive(Copy, Clone, Debug)]
-pub struct Vertex {
-    pub position: [f32; 3],
-    pub color: [f32; 4],
-}
+/// Outlines the convex hull of every group behind the edges
+pub fn draw_hulls<H, R>(
+    scene_context: &SceneContext,
+    target: &mut Frame,
+    display: &Display<WindowSurface>,
+    uniform: &UniformsStorage<H, R>,
+    params: &DrawParameters,
+) where
+    H: AsUniformValue,
+    R: Uniforms,
+{
+    if !scene_context.show_hulls {
+        return;
+    }
+    let hulls = group_hulls(scene_context);
+    let colors = scene_context.node_colors(
+        &scene_context.simulator.rigid_bodies.read().unwrap(),
+        &scene_context.simulator.springs.read().unwrap(),
+    );
 
-implement_vertex!(Vertex, position, color);
+    let z = scene_context.layers.z(Layer::Hulls);
+    let mut shape: Vec<Vertex> = vec![];
+    for (members, hull) in hulls.iter().filter(|(_, hull)| hull.len() >= 3) {
+        let color = colors[members[0] as usize];
+        for i in 0..hull.len() {
+            let (a, b) = (hull[i], hull[(i + 1) % hull.len()]);
+            shape.append(&mut shapes::line([a.x, a.y, z], [b.x, b.y, z], color));
+        }
+    }
+    if shape.is_empty() {
+        return;
+    }
 
-#[derive(Copy, Clone)]
-struct Attr {
-    color_attr: [f32; 3],
-    world_position: [f32; 3],
-    scale: f32,
+    let program = shader::program(display, Shader::Vertex, Shader::Fragment);
+    let vertex_buffer = glium::VertexBuffer::new(display, &shape).unwrap();
+    let indices = glium::index::NoIndices(glium::index::PrimitiveType::LinesList);
+    target
+        .draw(&vertex_buffer, indices, &program, uniform, params)
+        .unwrap();
 }
-implement_vertex!(Attr, color_attr, world_position, scale);
 
 pub fn draw_edge<H, R>(
     scene_context: Arc<Mutex<SceneContext>>,
+    render_state: &mut RenderState,
     target: &mut Frame,
     display: &Display<WindowSurface>,
     uniform: &UniformsStorage<H, R>,
@@ -90,48 +192,215 @@ pub fn draw_edge<H, R>(
 {
     let scene_context = scene_context.lock().unwrap();
 
-    let program =
-        glium::Program::from_source(display, VERTEX_SHADER_SRC, FRAGMENT_SHADER_SRC, None).unwrap();
-
     let mut shape: Vec<Vertex> = vec![];
 
     let spring_read_guard = scene_context.simulator.springs.read().unwrap();
     let rb_read_guard = scene_context.simulator.rigid_bodies.read().unwrap();
 
+    let z = scene_context.layers.z(Layer::Edges);
+    let program = render_state
+        .programs
+        .get(display, Shader::Vertex, Shader::Fragment);
+
+    // Zoomed out far enough for semantic zoom, draw the edges between the clusters instead
+    if let Some(clusters) = scene_context.semantic_zoom.clusters(&rb_read_guard) {
+        let mut thick_lines: Vec<Vertex> = vec![];
+        for (c1, c2, count) in scene_context.semantic_zoom.edges() {
+            let (p1, p2) = (clusters[c1].position, clusters[c2].position);
+            thick_lines.append(&mut shapes::thick_line(
+                [p1.x, p1.y, z],
+                [p2.x, p2.y, z],
+                CLUSTER_EDGE_WIDTH * (1.0 + (count as f32).ln()),
+                [0.6, 0.6, 0.6, 1.0],
+            ));
+        }
+        if !thick_lines.is_empty() {
+            let vertex_buffer = glium::VertexBuffer::new(display, &thick_lines).unwrap();
+            let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+            target
+                .draw(&vertex_buffer, indices, program, uniform, params)
+                .unwrap();
+        }
+        return;
+    }
+
     let mut longest_len = 0.0_f32;
+    // Number of edges between each node pair, to spread parallel edges apart
+    let mut parallel_edges: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
 
     for edge in spring_read_guard.iter() {
         let rb1 = &rb_read_guard[edge.rb1];
         let rb2 = &rb_read_guard[edge.rb2];
 
         longest_len = longest_len.max(rb1.position.distance(rb2.position));
+        parallel_edges
+            .entry((edge.rb1.min(edge.rb2), edge.rb1.max(edge.rb2)))
+            .or_default()
+            .0 += 1;
     }
 
+    let bundles = if scene_context.bundle_edges {
+        edge_bundles(&spring_read_guard, &rb_read_guard, longest_len)
+    } else {
+        HashMap::new()
+    };
+
+    // Only aggregate edges when a partition is active
+    let partition = scene_context
+        .partition
+        .as_ref()
+        .filter(|_| scene_context.aggregate_edges);
+    let group = |node: usize| partition.and_then(|p| p.get(node).copied());
+    let mut group_edges: HashMap<(u32, u32), usize> = HashMap::new();
+
     for edge in spring_read_guard.iter() {
         let rb1 = &rb_read_guard[edge.rb1];
         let rb2 = &rb_read_guard[edge.rb2];
 
+        if let (Some(g1), Some(g2)) = (group(edge.rb1), group(edge.rb2)) {
+            if g1 != g2 {
+                *group_edges.entry((g1.min(g2), g1.max(g2))).or_default() += 1;
+                continue;
+            }
+        }
+
         let dist = rb1.position.distance(rb2.position);
 
-        let color = [dist / longest_len, 0.0, 0.0, 0.0];
+        let color = edge
+            .kind
+            .and_then(|k| scene_context.simulator.edge_kinds()[k].color)
+            .unwrap_or([dist / longest_len, 0.0, 0.0, 0.0]);
 
-        shape.append(&mut shapes::line(
-            [rb1.position[0], rb1.position[1], -1.0],
-            [rb2.position[0], rb2.position[1], -1.0],
-            color,
-        ));
+        // The curve passes through `through` halfway between the nodes
+        let midpoint = (rb1.position + rb2.position) / 2.0;
+        let mut through = bundles
+            .get(&bundle_key(rb1.position, rb2.position, longest_len))
+            .map_or(midpoint, |(sum, count)| {
+                midpoint.lerp(*sum / *count, BUNDLE_STRENGTH)
+            });
+
+        let key = (edge.rb1.min(edge.rb2), edge.rb1.max(edge.rb2));
+        let (count, seen) = parallel_edges.get_mut(&key).unwrap();
+        if *count > 1 {
+            // Same normal for both directions so the offsets don't cancel out
+            let normal = (rb_read_guard[key.1].position - rb_read_guard[key.0].position)
+                .normalize_or_zero()
+                .perp();
+            let offset = *seen as f32 - (*count - 1) as f32 / 2.0;
+            through += normal * offset * PARALLEL_EDGE_SPACING * dist;
+            *seen += 1;
+        }
+
+        if through == midpoint {
+            shape.append(&mut shapes::line(
+                [rb1.position[0], rb1.position[1], z],
+                [rb2.position[0], rb2.position[1], z],
+                color,
+            ));
+        } else {
+            let control = 2.0 * through - midpoint;
+            shape.append(&mut shapes::bezier(
+                [rb1.position[0], rb1.position[1], z],
+                [control.x, control.y, z],
+                [rb2.position[0], rb2.position[1], z],
+                BEZIER_SEGMENTS,
+                color,
+            ));
+        }
     }
 
-    let vertex_buffer = glium::VertexBuffer::new(display, &shape).unwrap();
-    let indices = glium::index::NoIndices(glium::index::PrimitiveType::LinesList);
+    // Replace the edges between two groups with one edge between the group centroids
+    let mut thick_lines: Vec<Vertex> = vec![];
+    if let Some(partition) = partition {
+        let mut centroids: HashMap<u32, (Vec2, f32)> = HashMap::new();
+        for (rb, group) in rb_read_guard.iter().zip(partition) {
+            let (sum, count) = centroids.entry(*group).or_default();
+            *sum += rb.position;
+            *count += 1.0;
+        }
+        let centroid = |group: &u32| {
+            let (sum, count) = centroids[group];
+            sum / count
+        };
 
-    target
-        .draw(&vertex_buffer, indices, &program, uniform, params)
-        .unwrap();
+        for ((g1, g2), count) in group_edges {
+            let (c1, c2) = (centroid(&g1), centroid(&g2));
+            let color = [0.8, 0.5, 0.1, 1.0];
+            thick_lines.append(&mut shapes::thick_line(
+                [c1.x, c1.y, z],
+                [c2.x, c2.y, z],
+                0.3 * (1.0 + (count as f32).ln()),
+                color,
+            ));
+
+            let center = (c1 + c2) / 2.0;
+            shape.append(&mut font::text_centered(
+                [
+                    center.x,
+                    center.y,
+                    scene_context.layers.z(Layer::EdgeHighlights),
+                ],
+                &count.to_string(),
+                1.5,
+                [1.0, 1.0, 1.0, 1.0],
+            ));
+        }
+    }
+
+    if let Some(vertex_buffer) = render_state::upload(display, &mut render_state.edges, &shape) {
+        let indices = glium::index::NoIndices(glium::index::PrimitiveType::LinesList);
+        target
+            .draw(vertex_buffer, indices, program, uniform, params)
+            .unwrap();
+    }
+
+    if !thick_lines.is_empty() {
+        let vertex_buffer = glium::VertexBuffer::new(display, &thick_lines).unwrap();
+        let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+
+        target
+            .draw(&vertex_buffer, indices, program, uniform, params)
+            .unwrap();
+    }
+}
+
+/// Groups edges with close midpoints and a similar direction.
+///
+/// Returns the sum and count of the midpoints of every group.
+fn edge_bundles(
+    springs: &[Spring],
+    rigid_bodies: &[RigidBody2D],
+    longest_len: f32,
+) -> HashMap<(i32, i32, i32), (Vec2, f32)> {
+    let mut bundles: HashMap<(i32, i32, i32), (Vec2, f32)> = HashMap::new();
+    for spring in springs {
+        let p1 = rigid_bodies[spring.rb1].position;
+        let p2 = rigid_bodies[spring.rb2].position;
+        let (sum, count) = bundles.entry(bundle_key(p1, p2, longest_len)).or_default();
+        *sum += (p1 + p2) / 2.0;
+        *count += 1.0;
+    }
+    bundles
+}
+
+/// Grid cell of the midpoint and direction bucket of an edge
+fn bundle_key(p1: Vec2, p2: Vec2, longest_len: f32) -> (i32, i32, i32) {
+    let cell_size = (longest_len / 8.0).max(1.0);
+    let midpoint = (p1 + p2) / 2.0 / cell_size;
+    let dir = p2 - p1;
+    // Undirected angle in [0, PI)
+    let angle = dir.y.atan2(dir.x).rem_euclid(PI);
+    let direction = ((angle / PI * BUNDLE_DIRECTIONS as f32) as i32).min(BUNDLE_DIRECTIONS - 1);
+    (
+        midpoint.x.floor() as i32,
+        midpoint.y.floor() as i32,
+        direction,
+    )
 }
 
 pub fn draw_node<H, R>(
     scene_context: Arc<Mutex<SceneContext>>,
+    render_state: &mut RenderState,
     target: &mut Frame,
     display: &Display<WindowSurface>,
     uniform: &UniformsStorage<H, R>,
@@ -143,24 +412,62 @@ pub fn draw_node<H, R>(
 {
     let scene_context = scene_context.lock().unwrap();
 
-    let program =
-        glium::Program::from_source(display, INSTANCE_SHADER_SRC, FRAGMENT_SHADER_SRC, None)
-            .unwrap();
-
-    let mut shape: Vec<Vertex> = vec![];
     let graph_read_guard = scene_context.simulator.rigid_bodies.read().unwrap();
 
-    shape.append(&mut shapes::circle(
-        [0.0, 0.0, 0.0],
-        [0.0, 0.0, 0.0, 0.0],
-        1.0,
-        10,
-    ));
-
     let mut attr_list: Vec<Attr> = vec![];
+    let colors = scene_context.node_colors(
+        &graph_read_guard,
+        &scene_context.simulator.springs.read().unwrap(),
+    );
 
-    for (e, rb) in graph_read_guard.iter().enumerate() {
-        let mut rand = StdRng::seed_from_u64(e as u64);
+    let radius: Vec<f32> = graph_read_guard
+        .iter()
+        .enumerate()
+        .map(|(e, rb)| node_radius(&scene_context, e, rb))
+        .collect();
+
+    // Zoomed out far enough for semantic zoom, a cluster covers the area of its members
+    let clusters = scene_context.semantic_zoom.clusters(&graph_read_guard);
+    if let Some(clusters) = &clusters {
+        for cluster in clusters.iter().filter(|c| !c.members.is_empty()) {
+            let count = cluster.members.len() as f32;
+            let color = cluster.members.iter().fold([0.0; 4], |sum, m| {
+                std::array::from_fn(|i| sum[i] + colors[*m][i] / count)
+            });
+            let area: f32 = cluster.members.iter().map(|m| radius[*m].powi(2)).sum();
+            attr_list.push(Attr {
+                color_attr: color,
+                world_position: [
+                    cluster.position.x,
+                    cluster.position.y,
+                    scene_context.layers.z(Layer::Nodes),
+                ],
+                scale: area.sqrt(),
+            });
+        }
+    }
+    let category = |e: usize| {
+        if scene_context.selected_node_index == Some(e as u32) {
+            NodeCategory::Selected
+        } else if highlight_index.contains(&(e as u32)) {
+            NodeCategory::Highlighted
+        } else {
+            NodeCategory::Normal
+        }
+    };
+
+    let order = match clusters {
+        Some(_) => vec![],
+        None => scene_context
+            .draw_order
+            .sort(&graph_read_guard, &radius, category),
+    };
+    for e in order {
+        let rb = &graph_read_guard[e];
+        if rb.rope {
+            continue;
+        }
+        let color = colors[e];
         let mut highlight_mul = 1.0;
 
         if !highlight_index.is_empty() && !highlight_index.contains(&(e as u32)) {
@@ -168,29 +475,683 @@ pub fn draw_node<H, R>(
         }
 
         let color_attr = [
-            (rand.gen_range(10..=100) as f32) / 100.0 * highlight_mul,
-            (rand.gen_range(10..=100) as f32) / 100.0 * highlight_mul,
-            (rand.gen_range(10..=100) as f32) / 100.0 * highlight_mul,
+            color[0] * highlight_mul,
+            color[1] * highlight_mul,
+            color[2] * highlight_mul,
+            color[3],
         ];
 
+        let world_position = [
+            rb.position[0],
+            rb.position[1],
+            scene_context.layers.z(Layer::Nodes) + scene_context.draw_order.z_offset(category(e)),
+        ];
+
+        // Outline pinned nodes with a bigger circle drawn right before them
+        if rb.fixed {
+            attr_list.push(Attr {
+                color_attr: [highlight_mul, highlight_mul, highlight_mul, 1.0],
+                world_position,
+                scale: radius[e] * PINNED_OUTLINE_SCALE,
+            });
+        }
+
         attr_list.push(Attr {
             color_attr,
-            world_position: [rb.position[0], rb.position[1], 0.0],
-            scale: (rb.mass / PI).sqrt() / 2.0,
+            world_position,
+            scale: radius[e],
+        })
+    }
+
+    let program = render_state
+        .programs
+        .get(display, Shader::Instance, Shader::Fragment);
+    if let Some(instances) = render_state::upload(display, &mut render_state.instances, &attr_list)
+    {
+        let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+        target
+            .draw(
+                (&render_state.circle, instances.per_instance().unwrap()),
+                indices,
+                program,
+                uniform,
+                params,
+            )
+            .unwrap();
+    }
+}
+
+pub fn node_radius(scene_context: &SceneContext, index: usize, rb: &RigidBody2D) -> f32 {
+    match &scene_context.node_size_fn {
+        Some(size_fn) => size_fn(index as u32, rb),
+        None => (rb.mass / PI).sqrt() / 2.0,
+    }
+}
+
+/// Draws the hovered and the selected edge thicker and labels them with their stiffness and
+/// neutral length. Also draws the edge that is dragged to the `cursor` in place mode and the
+/// selection box.
+pub fn draw_edge_highlight<H, R>(
+    scene_context: &SceneContext,
+    target: &mut Frame,
+    display: &Display<WindowSurface>,
+    uniform: &UniformsStorage<H, R>,
+    params: &DrawParameters,
+    cursor: Vec2,
+) where
+    H: AsUniformValue,
+    R: Uniforms,
+{
+    let springs = scene_context.simulator.springs.read().unwrap();
+    let rb_read_guard = scene_context.simulator.rigid_bodies.read().unwrap();
+    let width = scene_context.camera.distance() * 0.004;
+    let height = scene_context.camera.distance() * 0.015;
+    let z = scene_context.layers.z(Layer::EdgeHighlights);
+    let label_z = scene_context.layers.z(Layer::Labels);
+
+    let mut triangles: Vec<Vertex> = vec![];
+    let mut lines: Vec<Vertex> = vec![];
+    let highlighted = [
+        (scene_context.hovered_edge_index, [1.0, 1.0, 1.0, 1.0]),
+        (scene_context.selected_edge_index, [1.0, 0.8, 0.1, 1.0]),
+    ];
+    for (index, color) in highlighted {
+        let Some(spring) = index.and_then(|i| springs.get(i)) else {
+            continue;
+        };
+        let p1 = rb_read_guard[spring.rb1].position;
+        let p2 = rb_read_guard[spring.rb2].position;
+        triangles.append(&mut shapes::thick_line(
+            [p1.x, p1.y, z],
+            [p2.x, p2.y, z],
+            width,
+            color,
+        ));
+
+        let center = (p1 + p2) / 2.0;
+        lines.append(&mut font::text(
+            [center.x + height * 0.5, center.y + height * 0.5, label_z],
+            &format!(
+                "K:{:.2} L:{:.1}",
+                spring.spring_stiffness, spring.spring_neutral_len
+            ),
+            height,
+            color,
+        ));
+    }
+    if let Some(source) = scene_context.edge_source {
+        let p = rb_read_guard[source as usize].position;
+        triangles.append(&mut shapes::thick_line(
+            [p.x, p.y, z],
+            [cursor.x, cursor.y, z],
+            width,
+            [0.3, 0.9, 0.3, 1.0],
+        ));
+    }
+    if let Some((min, max)) = scene_context.selection.box_bounds(cursor) {
+        let corners = [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)];
+        for i in 0..corners.len() {
+            let (a, b) = (corners[i], corners[(i + 1) % corners.len()]);
+            triangles.append(&mut shapes::thick_line(
+                [a.x, a.y, z],
+                [b.x, b.y, z],
+                width,
+                [0.4, 0.6, 1.0, 1.0],
+            ));
+        }
+    }
+    if triangles.is_empty() {
+        return;
+    }
+
+    let program = shader::program(display, Shader::Vertex, Shader::Fragment);
+    for (shape, primitive) in [
+        (triangles, glium::index::PrimitiveType::TrianglesList),
+        (lines, glium::index::PrimitiveType::LinesList),
+    ] {
+        let vertex_buffer = glium::VertexBuffer::new(display, &shape).unwrap();
+        target
+            .draw(
+                &vertex_buffer,
+                glium::index::NoIndices(primitive),
+                &program,
+                uniform,
+                params,
+            )
+            .unwrap();
+    }
+}
+
+/// Draws the annotations, the note that is typed into with a cursor and the arrow that is
+/// dragged to the `cursor`
+pub fn draw_annotations<H, R>(
+    scene_context: &SceneContext,
+    target: &mut Frame,
+    display: &Display<WindowSurface>,
+    uniform: &UniformsStorage<H, R>,
+    params: &DrawParameters,
+    cursor: Vec2,
+) where
+    H: AsUniformValue,
+    R: Uniforms,
+{
+    let width = scene_context.camera.distance() * 0.004;
+    let height = scene_context.camera.distance() * 0.015;
+    let z = scene_context.layers.z(Layer::Annotations);
+
+    let mut triangles: Vec<Vertex> = vec![];
+    let mut lines: Vec<Vertex> = vec![];
+    let mut fills: Vec<Vertex> = vec![];
+    let arrow = |from: Vec2, to: Vec2, color: [f32; 4], triangles: &mut Vec<Vertex>| {
+        triangles.append(&mut shapes::thick_line(
+            [from.x, from.y, z],
+            [to.x, to.y, z],
+            width,
+            color,
+        ));
+        let back = (from - to).normalize_or_zero() * (height * 1.5).min(from.distance(to) / 2.0);
+        for angle in [-0.5_f32, 0.5] {
+            let head = to + Vec2::from_angle(angle).rotate(back);
+            triangles.append(&mut shapes::thick_line(
+                [to.x, to.y, z],
+                [head.x, head.y, z],
+                width,
+                color,
+            ));
+        }
+    };
+
+    let editing = scene_context.editing_note.as_ref().map(|edit| edit.index);
+    for (i, annotation) in scene_context.annotations.iter().enumerate() {
+        match annotation {
+            Annotation::Note {
+                position,
+                text,
+                color,
+            } => {
+                let text = if editing == Some(i) {
+                    format!("{}_", text)
+                } else {
+                    text.clone()
+                };
+                lines.append(&mut font::text(
+                    [position.x, position.y, z],
+                    &text,
+                    height,
+                    *color,
+                ));
+            }
+            Annotation::Arrow { from, to, color } => arrow(*from, *to, *color, &mut triangles),
+            Annotation::Region { min, max, color } => {
+                let corners = [*min, Vec2::new(max.x, min.y), *max, Vec2::new(min.x, max.y)];
+                for j in 0..corners.len() {
+                    let (a, b) = (corners[j], corners[(j + 1) % corners.len()]);
+                    triangles.append(&mut shapes::thick_line(
+                        [a.x, a.y, z],
+                        [b.x, b.y, z],
+                        width,
+                        *color,
+                    ));
+                }
+                let center = (*min + *max) / 2.0;
+                fills.append(&mut shapes::rectangle_filled(
+                    [center.x, center.y, z],
+                    [color[0], color[1], color[2], 0.15],
+                    (max.x - min.x) / 2.0,
+                    (max.y - min.y) / 2.0,
+                ));
+            }
+        }
+    }
+    if let Some(start) = scene_context.arrow_start {
+        arrow(start, cursor, [1.0, 0.9, 0.4, 0.6], &mut triangles);
+    }
+
+    // The fill is see-through and doesn't hide labels drawn after it
+    let fill_params = DrawParameters {
+        blend: glium::Blend::alpha_blending(),
+        depth: glium::Depth {
+            write: false,
+            ..params.depth
+        },
+        ..params.clone()
+    };
+
+    let program = shader::program(display, Shader::Vertex, Shader::Fragment);
+    for (shape, primitive, params) in [
+        (
+            fills,
+            glium::index::PrimitiveType::TrianglesList,
+            &fill_params,
+        ),
+        (
+            triangles,
+            glium::index::PrimitiveType::TrianglesList,
+            params,
+        ),
+        (lines, glium::index::PrimitiveType::LinesList, params),
+    ] {
+        if shape.is_empty() {
+            continue;
+        }
+        let vertex_buffer = glium::VertexBuffer::new(display, &shape).unwrap();
+        target
+            .draw(
+                &vertex_buffer,
+                glium::index::NoIndices(primitive),
+                &program,
+                uniform,
+                params,
+            )
+            .unwrap();
+    }
+}
+
+/// Draws the labels of at most `budget` visible nodes.
+///
+/// The selected node is always labeled, the other nodes are chosen by their degree and how close
+/// they are to the `cursor`.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_labels<H, R>(
+    scene_context: &SceneContext,
+    target: &mut Frame,
+    display: &Display<WindowSurface>,
+    uniform: &UniformsStorage<H, R>,
+    params: &DrawParameters,
+    view_projection: Mat4,
+    cursor: Vec2,
+    budget: usize,
+) where
+    H: AsUniformValue,
+    R: Uniforms,
+{
+    let Some(labels) = &scene_context.labels else {
+        return;
+    };
+    if budget == 0 {
+        return;
+    }
+
+    let rb_read_guard = scene_context.simulator.rigid_bodies.read().unwrap();
+    let mut degree = vec![0usize; rb_read_guard.len()];
+    for spring in scene_context.simulator.springs.read().unwrap().iter() {
+        degree[spring.rb1] += 1;
+        degree[spring.rb2] += 1;
+    }
+
+    // Nodes further away from the cursor than this lose half of their priority
+    let falloff = scene_context.camera.distance() * 0.1;
+
+    let mut candidates: Vec<(usize, f32)> = rb_read_guard
+        .iter()
+        .enumerate()
+        .take(labels.len())
+        .filter(|(_, rb)| !rb.rope)
+        .filter(|(_, rb)| {
+            let clip = view_projection * Vec4::new(rb.position.x, rb.position.y, 0.0, 1.0);
+            clip.w > 0.0 && clip.x.abs() <= clip.w && clip.y.abs() <= clip.w
         })
+        .map(|(i, rb)| {
+            let priority = if scene_context.selected_node_index == Some(i as u32) {
+                f32::INFINITY
+            } else {
+                (1 + degree[i]) as f32 / (1.0 + rb.position.distance(cursor) / falloff)
+            };
+            (i, priority)
+        })
+        .collect();
+
+    if candidates.len() > budget {
+        candidates.select_nth_unstable_by(budget - 1, |a, b| b.1.total_cmp(&a.1));
+        candidates.truncate(budget);
     }
 
+    let height = scene_context.camera.distance() * 0.015;
+    let mut shape: Vec<Vertex> = vec![];
+    for (i, _) in candidates {
+        let rb = &rb_read_guard[i];
+        let radius = node_radius(scene_context, i, rb);
+        shape.append(&mut font::text(
+            [
+                rb.position.x + radius + height * 0.5,
+                rb.position.y - height * 0.5,
+                scene_context.layers.z(Layer::Labels),
+            ],
+            &labels[i],
+            height,
+            [1.0, 1.0, 1.0, 1.0],
+        ));
+    }
+
+    let program = shader::program(display, Shader::Vertex, Shader::Fragment);
     let vertex_buffer = glium::VertexBuffer::new(display, &shape).unwrap();
-    let instance_buffer = glium::vertex::VertexBuffer::dynamic(display, &attr_list).unwrap();
-    let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+    let indices = glium::index::NoIndices(glium::index::PrimitiveType::LinesList);
 
     target
-        .draw(
-            (&vertex_buffer, instance_buffer.per_instance().unwrap()),
-            indices,
-            &program,
-            uniform,
-            params,
-        )
+        .draw(&vertex_buffer, indices, &program, uniform, params)
+        .unwrap();
+}
+
+/// Draws `history` as a sparkline in the bottom left corner of the window.
+///
+/// The values are scaled to the largest value in `history`.
+pub fn draw_sparkline(
+    history: &VecDeque<f32>,
+    target: &mut Frame,
+    display: &Display<WindowSurface>,
+) {
+    const CENTER: [f32; 3] = [-0.7, -0.8, 0.0];
+    const HALF_WIDTH: f32 = 0.25;
+    const HALF_HEIGHT: f32 = 0.15;
+
+    let program = shader::program(display, Shader::Vertex, Shader::Fragment);
+
+    // Draw in normalized device coordinates
+    let identity = glam::Mat4::IDENTITY.to_cols_array_2d();
+    let uniforms = uniform! {
+        matrix: identity,
+        projection: identity,
+    };
+    let params = DrawParameters::default();
+
+    let frame = shapes::rectangle_lines(CENTER, [0.4, 0.4, 0.4, 1.0], HALF_WIDTH, HALF_HEIGHT);
+    let vertex_buffer = glium::VertexBuffer::new(display, &frame).unwrap();
+    let indices = glium::index::NoIndices(glium::index::PrimitiveType::LinesList);
+    target
+        .draw(&vertex_buffer, indices, &program, &uniforms, &params)
+        .unwrap();
+
+    if history.len() < 2 {
+        return;
+    }
+
+    let max = history.iter().copied().fold(f32::EPSILON, f32::max);
+    let step = 2.0 * HALF_WIDTH / (history.len() - 1) as f32;
+    let line: Vec<Vertex> = history
+        .iter()
+        .enumerate()
+        .map(|(i, value)| Vertex {
+            position: [
+                CENTER[0] - HALF_WIDTH + i as f32 * step,
+                CENTER[1] - HALF_HEIGHT + value / max * 2.0 * HALF_HEIGHT,
+                0.0,
+            ],
+            color: [0.2, 0.9, 0.2, 1.0],
+        })
+        .collect();
+
+    let vertex_buffer = glium::VertexBuffer::new(display, &line).unwrap();
+    let indices = glium::index::NoIndices(glium::index::PrimitiveType::LineStrip);
+    target
+        .draw(&vertex_buffer, indices, &program, &uniforms, &params)
+        .unwrap();
+}
+
+/// Draws the nodes and edges on the minimap and outlines the part of the graph seen by the camera,
+/// `frustum` are the corners of the view on the `z = 0` plane
+pub fn draw_minimap(
+    scene_context: &SceneContext,
+    minimap: &Minimap,
+    frustum: &[Vec2; 4],
+    target: &mut Frame,
+    display: &Display<WindowSurface>,
+    window_size: Vec2,
+) {
+    let program = shader::program(display, Shader::Vertex, Shader::Fragment);
+    let uniforms = uniform! {
+        matrix: glam::Mat4::IDENTITY.to_cols_array_2d(),
+        projection: glam::Mat4::orthographic_rh(0.0, window_size.x, window_size.y, 0.0, -1.0, 1.0)
+            .to_cols_array_2d(),
+    };
+    // Nothing is drawn outside of the minimap
+    let params = DrawParameters {
+        scissor: Some(glium::Rect {
+            left: minimap.min.x.max(0.0) as u32,
+            bottom: (window_size.y - minimap.min.y - minimap.size).max(0.0) as u32,
+            width: minimap.size as u32,
+            height: minimap.size as u32,
+        }),
+        point_size: Some(2.0),
+        ..Default::default()
+    };
+    let vertex = |point: Vec2, color: [f32; 4]| Vertex {
+        position: [point.x, point.y, 0.0],
+        color,
+    };
+
+    let center = (minimap.min + minimap.size / 2.0).extend(0.0).to_array();
+    let half_size = minimap.size / 2.0;
+    let background = shapes::rectangle_filled(center, [0.1, 0.1, 0.1, 1.0], half_size, half_size);
+
+    let rigid_bodies = scene_context.simulator.rigid_bodies.read().unwrap();
+    let springs = scene_context.simulator.springs.read().unwrap();
+    let mut lines = vec![];
+    for spring in springs.iter() {
+        let color = [0.35, 0.35, 0.35, 1.0];
+        lines.push(vertex(
+            minimap.to_minimap(rigid_bodies[spring.rb1].position),
+            color,
+        ));
+        lines.push(vertex(
+            minimap.to_minimap(rigid_bodies[spring.rb2].position),
+            color,
+        ));
+    }
+    for i in 0..frustum.len() {
+        let color = [1.0, 1.0, 1.0, 1.0];
+        lines.push(vertex(minimap.to_minimap(frustum[i]), color));
+        lines.push(vertex(
+            minimap.to_minimap(frustum[(i + 1) % frustum.len()]),
+            color,
+        ));
+    }
+    lines.append(&mut shapes::rectangle_lines(
+        center,
+        [0.6, 0.6, 0.6, 1.0],
+        half_size,
+        half_size,
+    ));
+
+    let colors = scene_context.node_colors(&rigid_bodies, &springs);
+    let points: Vec<Vertex> = rigid_bodies
+        .iter()
+        .zip(colors)
+        .filter(|(rb, _)| !rb.rope)
+        .map(|(rb, color)| vertex(minimap.to_minimap(rb.position), color))
+        .collect();
+
+    for (shape, primitive) in [
+        (background, glium::index::PrimitiveType::TrianglesList),
+        (lines, glium::index::PrimitiveType::LinesList),
+        (points, glium::index::PrimitiveType::Points),
+    ] {
+        let vertex_buffer = glium::VertexBuffer::new(display, &shape).unwrap();
+        target
+            .draw(
+                &vertex_buffer,
+                glium::index::NoIndices(primitive),
+                &program,
+                &uniforms,
+                &params,
+            )
+            .unwrap();
+    }
+}
+
+/// Draws the label, index and degree of the hovered node next to the cursor
+pub fn draw_tooltip(
+    scene_context: &SceneContext,
+    target: &mut Frame,
+    display: &Display<WindowSurface>,
+    window_size: Vec2,
+) {
+    let Some(index) = scene_context.hovered_node_index else {
+        return;
+    };
+    let degree = scene_context
+        .simulator
+        .springs
+        .read()
+        .unwrap()
+        .iter()
+        .filter(|s| s.rb1 == index as usize || s.rb2 == index as usize)
+        .count();
+
+    let mut text = vec![];
+    if let Some(label) = scene_context
+        .labels
+        .as_ref()
+        .and_then(|l| l.get(index as usize))
+    {
+        text.push(label.clone());
+    }
+    text.push(format!("Node {}", index));
+    text.push(format!("Degree {}", degree));
+
+    let size = text_box_size(&text);
+
+    // Pixel coordinates with the origin at the bottom left, keep the box inside the window
+    let cursor = Vec2::new(
+        scene_context.cursor_pos.x,
+        window_size.y - scene_context.cursor_pos.y,
+    );
+    let corner = Vec2::new(
+        (cursor.x + BOX_PADDING * 2.0)
+            .min(window_size.x - size.x)
+            .max(0.0),
+        (cursor.y - BOX_PADDING * 2.0)
+            .max(size.y)
+            .min(window_size.y),
+    );
+    draw_text_box(&text, corner, target, display, window_size);
+}
+
+/// Draws the frame rate and the `SimulatorMetrics` in the top left corner of the window
+pub fn draw_hud(
+    scene_context: &SceneContext,
+    fps: f32,
+    target: &mut Frame,
+    display: &Display<WindowSurface>,
+    window_size: Vec2,
+) {
+    let text = hud::lines(
+        fps,
+        &scene_context.simulator.metrics(),
+        scene_context.simulator.is_running(),
+    );
+    let corner = Vec2::new(BOX_PADDING, window_size.y - BOX_PADDING);
+    draw_text_box(&text, corner, target, display, window_size);
+}
+
+/// Width and height of a text box with one line per entry of `text` in pixels
+fn text_box_size(text: &[String]) -> Vec2 {
+    let width = text
+        .iter()
+        .map(|line| font::text_width(line, BOX_TEXT_HEIGHT))
+        .fold(0.0, f32::max)
+        + 2.0 * BOX_PADDING;
+    let height = text.len() as f32 * BOX_LINE_HEIGHT + 2.0 * BOX_PADDING
+        - (BOX_LINE_HEIGHT - BOX_TEXT_HEIGHT);
+    Vec2::new(width, height)
+}
+
+/// Draws `text` in a box whose top left `corner` is in pixels with the origin at the bottom left
+pub fn draw_text_box(
+    text: &[String],
+    corner: Vec2,
+    target: &mut Frame,
+    display: &Display<WindowSurface>,
+    window_size: Vec2,
+) {
+    let size = text_box_size(text);
+    let (width, height) = (size.x, size.y);
+
+    let program = shader::program(display, Shader::Vertex, Shader::Fragment);
+    let uniforms = uniform! {
+        matrix: glam::Mat4::IDENTITY.to_cols_array_2d(),
+        projection: glam::Mat4::orthographic_rh(0.0, window_size.x, 0.0, window_size.y, -1.0, 1.0)
+            .to_cols_array_2d(),
+    };
+    let params = DrawParameters::default();
+
+    let center = [corner.x + width / 2.0, corner.y - height / 2.0, 0.0];
+    let background =
+        shapes::rectangle_filled(center, [0.1, 0.1, 0.1, 1.0], width / 2.0, height / 2.0);
+    let mut lines =
+        shapes::rectangle_lines(center, [0.6, 0.6, 0.6, 1.0], width / 2.0, height / 2.0);
+    for (i, line) in text.iter().enumerate() {
+        lines.append(&mut font::text(
+            [
+                corner.x + BOX_PADDING,
+                corner.y - BOX_PADDING - BOX_TEXT_HEIGHT - i as f32 * BOX_LINE_HEIGHT,
+                0.0,
+            ],
+            line,
+            BOX_TEXT_HEIGHT,
+            [1.0, 1.0, 1.0, 1.0],
+        ));
+    }
+
+    for (shape, primitive) in [
+        (background, glium::index::PrimitiveType::TrianglesList),
+        (lines, glium::index::PrimitiveType::LinesList),
+    ] {
+        let vertex_buffer = glium::VertexBuffer::new(display, &shape).unwrap();
+        target
+            .draw(
+                &vertex_buffer,
+                glium::index::NoIndices(primitive),
+                &program,
+                &uniforms,
+                &params,
+            )
+            .unwrap();
+    }
+}
+
+/// Draws a progress bar labeled "Loading" in the center of the window
+pub fn draw_progress_bar(progress: f32, target: &mut Frame, display: &Display<WindowSurface>) {
+    const HALF_WIDTH: f32 = 0.4;
+    const HALF_HEIGHT: f32 = 0.03;
+
+    let program = shader::program(display, Shader::Vertex, Shader::Fragment);
+
+    // Draw in normalized device coordinates
+    let identity = glam::Mat4::IDENTITY.to_cols_array_2d();
+    let uniforms = uniform! {
+        matrix: identity,
+        projection: identity,
+    };
+    let params = DrawParameters::default();
+
+    let mut lines = shapes::rectangle_lines(
+        [0.0, 0.0, 0.0],
+        [0.6, 0.6, 0.6, 1.0],
+        HALF_WIDTH,
+        HALF_HEIGHT,
+    );
+    lines.append(&mut font::text_centered(
+        [0.0, 0.1, 0.0],
+        "Loading",
+        0.06,
+        [1.0, 1.0, 1.0, 1.0],
+    ));
+    let vertex_buffer = glium::VertexBuffer::new(display, &lines).unwrap();
+    let indices = glium::index::NoIndices(glium::index::PrimitiveType::LinesList);
+    target
+        .draw(&vertex_buffer, indices, &program, &uniforms, &params)
+        .unwrap();
+
+    let filled_half_width = HALF_WIDTH * progress.clamp(0.0, 1.0);
+    let bar = shapes::thick_line(
+        [-HALF_WIDTH, 0.0, 0.0],
+        [-HALF_WIDTH + 2.0 * filled_half_width, 0.0, 0.0],
+        2.0 * HALF_HEIGHT,
+        [0.2, 0.6, 0.9, 1.0],
+    );
+    let vertex_buffer = glium::VertexBuffer::new(display, &bar).unwrap();
+    let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+    target
+        .draw(&vertex_buffer, indices, &program, &uniforms, &params)
         .unwrap();
 }
@@ -48,6 +48,11 @@ impl EventManager {
         }
     }
 
+    pub fn clear(&mut self) {
+        self.key_event.clear();
+        self.mouse_event.clear();
+    }
+
     pub fn insert_key(&mut self, vk: VirtualKeyCode) {
         self.key_event.insert(vk, InputEvent::new());
     }
@@ -0,0 +1,246 @@
+use super::{draw::Vertex, shapes};
+
+/// Width of a glyph in font units, glyphs are 4 units high
+const GLYPH_WIDTH: f32 = 2.0;
+const ADVANCE: f32 = 3.0;
+
+type Stroke = &'static [(f32, f32)];
+
+/// Draws `text` as strokes for `LinesList` with the baseline starting at `pos`.
+///
+/// Only supports digits, latin letters and some punctuation. Letters are drawn uppercase,
+/// other characters are drawn as `?`.
+pub fn text(pos: [f32; 3], text: &str, height: f32, color: [f32; 4]) -> Vec<Vertex> {
+    let scale = height / 4.0;
+    let mut shape = vec![];
+
+    for (i, c) in text.chars().enumerate() {
+        let x = pos[0] + i as f32 * ADVANCE * scale;
+        for stroke in glyph(c) {
+            for segment in stroke.windows(2) {
+                shape.append(&mut shapes::line(
+                    [
+                        x + segment[0].0 * scale,
+                        pos[1] + segment[0].1 * scale,
+                        pos[2],
+                    ],
+                    [
+                        x + segment[1].0 * scale,
+                        pos[1] + segment[1].1 * scale,
+                        pos[2],
+                    ],
+                    color,
+                ));
+            }
+        }
+    }
+    shape
+}
+
+/// Draws `text` centered at `pos`
+pub fn text_centered(pos: [f32; 3], text: &str, height: f32, color: [f32; 4]) -> Vec<Vertex> {
+    let width = text_width(text, height);
+    self::text(
+        [pos[0] - width / 2.0, pos[1] - height / 2.0, pos[2]],
+        text,
+        height,
+        color,
+    )
+}
+
+/// Width of `text` drawn with `height`
+pub fn text_width(text: &str, height: f32) -> f32 {
+    let count = text.chars().count();
+    if count == 0 {
+        return 0.0;
+    }
+    ((count - 1) as f32 * ADVANCE + GLYPH_WIDTH) * height / 4.0
+}
+
+fn glyph(c: char) -> &'static [Stroke] {
+    match c.to_ascii_uppercase() {
+        ' ' => &[],
+        '0' => &[
+            &[(0.0, 0.0), (2.0, 0.0), (2.0, 4.0), (0.0, 4.0), (0.0, 0.0)],
+            &[(0.0, 0.0), (2.0, 4.0)],
+        ],
+        '1' => &[
+            &[(0.0, 3.0), (1.0, 4.0), (1.0, 0.0)],
+            &[(0.0, 0.0), (2.0, 0.0)],
+        ],
+        '2' => &[&[
+            (0.0, 4.0),
+            (2.0, 4.0),
+            (2.0, 2.0),
+            (0.0, 2.0),
+            (0.0, 0.0),
+            (2.0, 0.0),
+        ]],
+        '3' => &[
+            &[(0.0, 4.0), (2.0, 4.0), (2.0, 0.0), (0.0, 0.0)],
+            &[(0.0, 2.0), (2.0, 2.0)],
+        ],
+        '4' => &[
+            &[(0.0, 4.0), (0.0, 2.0), (2.0, 2.0)],
+            &[(2.0, 4.0), (2.0, 0.0)],
+        ],
+        '5' => &[&[
+            (2.0, 4.0),
+            (0.0, 4.0),
+            (0.0, 2.5),
+            (2.0, 2.0),
+            (2.0, 0.0),
+            (0.0, 0.0),
+        ]],
+        '6' => &[&[
+            (2.0, 4.0),
+            (0.0, 4.0),
+            (0.0, 0.0),
+            (2.0, 0.0),
+            (2.0, 2.0),
+            (0.0, 2.0),
+        ]],
+        '7' => &[&[(0.0, 4.0), (2.0, 4.0), (1.0, 0.0)]],
+        '8' => &[
+            &[(0.0, 0.0), (2.0, 0.0), (2.0, 4.0), (0.0, 4.0), (0.0, 0.0)],
+            &[(0.0, 2.0), (2.0, 2.0)],
+        ],
+        '9' => &[&[
+            (2.0, 2.0),
+            (0.0, 2.0),
+            (0.0, 4.0),
+            (2.0, 4.0),
+            (2.0, 0.0),
+            (0.0, 0.0),
+        ]],
+        'A' => &[
+            &[(0.0, 0.0), (0.0, 3.0), (1.0, 4.0), (2.0, 3.0), (2.0, 0.0)],
+            &[(0.0, 2.0), (2.0, 2.0)],
+        ],
+        'B' => &[
+            &[
+                (0.0, 0.0),
+                (0.0, 4.0),
+                (1.5, 4.0),
+                (2.0, 3.0),
+                (1.5, 2.0),
+                (2.0, 1.0),
+                (1.5, 0.0),
+                (0.0, 0.0),
+            ],
+            &[(0.0, 2.0), (1.5, 2.0)],
+        ],
+        'C' => &[&[(2.0, 0.0), (0.0, 0.0), (0.0, 4.0), (2.0, 4.0)]],
+        'D' => &[&[
+            (0.0, 0.0),
+            (0.0, 4.0),
+            (1.0, 4.0),
+            (2.0, 3.0),
+            (2.0, 1.0),
+            (1.0, 0.0),
+            (0.0, 0.0),
+        ]],
+        'E' => &[
+            &[(2.0, 0.0), (0.0, 0.0), (0.0, 4.0), (2.0, 4.0)],
+            &[(0.0, 2.0), (1.5, 2.0)],
+        ],
+        'F' => &[
+            &[(0.0, 0.0), (0.0, 4.0), (2.0, 4.0)],
+            &[(0.0, 2.0), (1.5, 2.0)],
+        ],
+        'G' => &[&[
+            (2.0, 4.0),
+            (0.0, 4.0),
+            (0.0, 0.0),
+            (2.0, 0.0),
+            (2.0, 2.0),
+            (1.0, 2.0),
+        ]],
+        'H' => &[
+            &[(0.0, 0.0), (0.0, 4.0)],
+            &[(2.0, 0.0), (2.0, 4.0)],
+            &[(0.0, 2.0), (2.0, 2.0)],
+        ],
+        'I' => &[
+            &[(0.0, 0.0), (2.0, 0.0)],
+            &[(0.0, 4.0), (2.0, 4.0)],
+            &[(1.0, 0.0), (1.0, 4.0)],
+        ],
+        'J' => &[&[(0.0, 1.0), (0.0, 0.0), (2.0, 0.0), (2.0, 4.0)]],
+        'K' => &[
+            &[(0.0, 0.0), (0.0, 4.0)],
+            &[(2.0, 4.0), (0.0, 2.0), (2.0, 0.0)],
+        ],
+        'L' => &[&[(0.0, 4.0), (0.0, 0.0), (2.0, 0.0)]],
+        'M' => &[&[(0.0, 0.0), (0.0, 4.0), (1.0, 2.0), (2.0, 4.0), (2.0, 0.0)]],
+        'N' => &[&[(0.0, 0.0), (0.0, 4.0), (2.0, 0.0), (2.0, 4.0)]],
+        'O' => &[&[(0.0, 0.0), (2.0, 0.0), (2.0, 4.0), (0.0, 4.0), (0.0, 0.0)]],
+        'P' => &[&[(0.0, 0.0), (0.0, 4.0), (2.0, 4.0), (2.0, 2.0), (0.0, 2.0)]],
+        'Q' => &[
+            &[(0.0, 0.0), (2.0, 0.0), (2.0, 4.0), (0.0, 4.0), (0.0, 0.0)],
+            &[(1.0, 1.0), (2.0, -0.5)],
+        ],
+        'R' => &[&[
+            (0.0, 0.0),
+            (0.0, 4.0),
+            (2.0, 4.0),
+            (2.0, 2.0),
+            (0.0, 2.0),
+            (2.0, 0.0),
+        ]],
+        'S' => &[&[
+            (2.0, 4.0),
+            (0.0, 4.0),
+            (0.0, 2.0),
+            (2.0, 2.0),
+            (2.0, 0.0),
+            (0.0, 0.0),
+        ]],
+        'T' => &[&[(0.0, 4.0), (2.0, 4.0)], &[(1.0, 4.0), (1.0, 0.0)]],
+        'U' => &[&[(0.0, 4.0), (0.0, 0.0), (2.0, 0.0), (2.0, 4.0)]],
+        'V' => &[&[(0.0, 4.0), (1.0, 0.0), (2.0, 4.0)]],
+        'W' => &[&[(0.0, 4.0), (0.0, 0.0), (1.0, 2.0), (2.0, 0.0), (2.0, 4.0)]],
+        'X' => &[&[(0.0, 0.0), (2.0, 4.0)], &[(0.0, 4.0), (2.0, 0.0)]],
+        'Y' => &[
+            &[(0.0, 4.0), (1.0, 2.0), (2.0, 4.0)],
+            &[(1.0, 2.0), (1.0, 0.0)],
+        ],
+        'Z' => &[&[(0.0, 4.0), (2.0, 4.0), (0.0, 0.0), (2.0, 0.0)]],
+        '-' => &[&[(0.0, 2.0), (2.0, 2.0)]],
+        '_' => &[&[(0.0, 0.0), (2.0, 0.0)]],
+        '.' => &[&[(1.0, 0.0), (1.0, 0.4)]],
+        ',' => &[&[(1.0, 0.4), (0.7, -0.5)]],
+        ':' => &[&[(1.0, 0.5), (1.0, 0.9)], &[(1.0, 2.5), (1.0, 2.9)]],
+        '/' => &[&[(0.0, 0.0), (2.0, 4.0)]],
+        '(' => &[&[(1.5, 4.0), (0.5, 3.0), (0.5, 1.0), (1.5, 0.0)]],
+        ')' => &[&[(0.5, 4.0), (1.5, 3.0), (1.5, 1.0), (0.5, 0.0)]],
+        '+' => &[&[(0.0, 2.0), (2.0, 2.0)], &[(1.0, 1.0), (1.0, 3.0)]],
+        '\'' => &[&[(1.0, 4.0), (1.0, 3.0)]],
+        _ => &[
+            &[(0.0, 3.0), (1.0, 4.0), (2.0, 3.0), (1.0, 2.0), (1.0, 1.0)],
+            &[(1.0, 0.0), (1.0, 0.4)],
+        ],
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_text_width() {
+        assert_eq!(text_width("", 4.0), 0.0);
+        assert_eq!(text_width("A", 4.0), 2.0);
+        assert_eq!(text_width("AB", 4.0), 5.0);
+    }
+
+    #[test]
+    fn test_text_stays_in_cell() {
+        let shape = text([0.0, 0.0, 0.0], "HELLO 42", 4.0, [1.0; 4]);
+        assert!(!shape.is_empty());
+        for vertex in shape {
+            assert!(vertex.position[0] >= 0.0 && vertex.position[0] <= text_width("HELLO 42", 4.0));
+            assert!(vertex.position[1] >= -0.5 && vertex.position[1] <= 4.0);
+        }
+    }
+}
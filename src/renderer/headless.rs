@@ -0,0 +1,72 @@
+//! Fallback of `Renderer::create_window` when there is no display server or OpenGL, e.g. on a
+//! headless server or in CI. The layout is rasterized on the CPU with `raster::render`.
+
+use std::{error::Error, path::Path, thread, time::Duration};
+
+use super::{finish_loading, poll_stream, screenshot, Renderer, SceneContext};
+use crate::raster::{self, RenderStyle};
+
+/// How often frame requests are served and the simulations are checked for being paused
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Runs the simulations until all of them are paused, e.g. because they converged, then saves
+/// the first tab as a PNG in the working directory. Frames requested with
+/// `Renderer::save_frame` are saved in the meantime.
+pub(super) fn run(renderer: Renderer, error: Box<dyn Error>) {
+    eprintln!(
+        "Failed to create a window ({}), rendering on the CPU instead",
+        error
+    );
+    renderer.spawn_simulation_threads();
+
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        for scene_context in renderer.scene_contexts.iter() {
+            let mut scene_context = scene_context.lock().unwrap();
+            finish_loading(&mut scene_context);
+            poll_stream(&mut scene_context);
+        }
+
+        let requests: Vec<_> = renderer.frame_requests.lock().unwrap().drain(..).collect();
+        for path in requests {
+            save_frame(&renderer, &path);
+        }
+
+        let finished = renderer.scene_contexts.iter().all(|scene_context| {
+            let scene_context = scene_context.lock().unwrap();
+            scene_context.loading.is_none() && !scene_context.simulator.is_running()
+        });
+        if finished {
+            save_frame(&renderer, &screenshot::default_path());
+            return;
+        }
+    }
+}
+
+fn save_frame(renderer: &Renderer, path: &Path) {
+    let Some(scene_context) = renderer.scene_contexts.first() else {
+        return;
+    };
+    let pixmap = {
+        let scene_context = scene_context.lock().unwrap();
+        raster::render(
+            &scene_context.simulator,
+            &render_style(renderer, &scene_context),
+        )
+    };
+
+    if let Err(e) = pixmap.save_png(path) {
+        eprintln!("Failed to save frame to {}: {}", path.display(), e);
+    }
+}
+
+/// The window size, background and node colors the window would have used
+fn render_style(renderer: &Renderer, scene_context: &SceneContext) -> RenderStyle {
+    RenderStyle {
+        width: renderer.settings.window_size.0,
+        height: renderer.settings.window_size.1,
+        background_color: renderer.settings.background_color,
+        color_scheme: scene_context.color_scheme.clone(),
+        ..Default::default()
+    }
+}
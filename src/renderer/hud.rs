@@ -0,0 +1,79 @@
+use std::time::{Duration, Instant};
+
+use crate::simulator::SimulatorMetrics;
+
+/// Counts the frames drawn per second
+pub(super) struct FrameCounter {
+    second_start: Instant,
+    frames_this_second: u32,
+    fps: f32,
+}
+
+impl FrameCounter {
+    pub fn new() -> Self {
+        Self {
+            second_start: Instant::now(),
+            frames_this_second: 0,
+            fps: 0.0,
+        }
+    }
+
+    /// Counts a drawn frame
+    pub fn frame(&mut self) {
+        self.frames_this_second += 1;
+        let elapsed = self.second_start.elapsed();
+        if elapsed >= Duration::from_secs(1) {
+            self.fps = self.frames_this_second as f32 / elapsed.as_secs_f32();
+            self.second_start = Instant::now();
+            self.frames_this_second = 0;
+        }
+    }
+
+    /// Frames in the last full second
+    pub fn fps(&self) -> f32 {
+        self.fps
+    }
+}
+
+/// Lines of the HUD toggled with `I`
+pub(super) fn lines(fps: f32, metrics: &SimulatorMetrics, running: bool) -> Vec<String> {
+    let steps_per_second = if running {
+        format!("{:.0}", metrics.steps_per_second)
+    } else {
+        "paused".to_string()
+    };
+    vec![
+        format!("FPS {:.0}", fps),
+        format!("Steps/s {}", steps_per_second),
+        format!("Nodes {}", metrics.node_count),
+        format!("Edges {}", metrics.edge_count),
+        format!("Kinetic energy {:.3}", metrics.kinetic_energy),
+        format!("Quadtree depth {}", metrics.quadtree_depth),
+        format!(
+            "Step {:.2} ms",
+            metrics.step_stats.total.as_secs_f64() * 1000.0
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_lines() {
+        let metrics = SimulatorMetrics {
+            steps_per_second: 59.6,
+            node_count: 3,
+            quadtree_depth: 2,
+            ..Default::default()
+        };
+        let running = lines(30.0, &metrics, true);
+        assert_eq!(running[0], "FPS 30");
+        assert_eq!(running[1], "Steps/s 60");
+        assert_eq!(running[2], "Nodes 3");
+        assert_eq!(running[5], "Quadtree depth 2");
+
+        assert_eq!(lines(30.0, &metrics, false)[1], "Steps/s paused");
+    }
+}
@@ -0,0 +1,78 @@
+use glam::Vec2;
+
+/// Convex hull of `points` in counter-clockwise order, using Andrew's monotone chain
+pub fn convex_hull(points: &[Vec2]) -> Vec<Vec2> {
+    let mut points = points.to_vec();
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+
+    let cross = |o: Vec2, a: Vec2, b: Vec2| (a - o).perp_dot(b - o);
+    let mut hull: Vec<Vec2> = Vec::with_capacity(points.len() * 2);
+
+    // Lower hull, then upper hull
+    for pass in [points.clone(), points.into_iter().rev().collect()] {
+        let start = hull.len();
+        for p in pass {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        // The last point is the first point of the next pass
+        hull.pop();
+    }
+    hull
+}
+
+/// Whether `point` lies inside the counter-clockwise convex `hull`
+pub fn contains(hull: &[Vec2], point: Vec2) -> bool {
+    hull.len() >= 3
+        && (0..hull.len()).all(|i| {
+            let (a, b) = (hull[i], hull[(i + 1) % hull.len()]);
+            (b - a).perp_dot(point - a) >= 0.0
+        })
+}
+
+/// Area of the convex `hull`
+pub fn area(hull: &[Vec2]) -> f32 {
+    (0..hull.len())
+        .map(|i| hull[i].perp_dot(hull[(i + 1) % hull.len()]))
+        .sum::<f32>()
+        .abs()
+        / 2.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_convex_hull() {
+        let points = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(2.0, 2.0),
+            Vec2::new(0.0, 2.0),
+            Vec2::new(1.0, 0.0),
+        ];
+        let hull = convex_hull(&points);
+        assert_eq!(
+            hull,
+            vec![
+                Vec2::new(0.0, 0.0),
+                Vec2::new(2.0, 0.0),
+                Vec2::new(2.0, 2.0),
+                Vec2::new(0.0, 2.0)
+            ]
+        );
+        assert_eq!(area(&hull), 4.0);
+        assert!(contains(&hull, Vec2::new(1.0, 1.5)));
+        assert!(!contains(&hull, Vec2::new(3.0, 1.0)));
+    }
+}
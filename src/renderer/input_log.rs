@@ -0,0 +1,248 @@
+use std::{
+    error::Error,
+    fs::File,
+    io::{BufRead, BufReader, LineWriter, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use winit::{
+    dpi::PhysicalPosition,
+    event::{
+        DeviceId, ElementState, KeyboardInput, MouseButton, MouseScrollDelta, TouchPhase,
+        VirtualKeyCode, WindowEvent,
+    },
+};
+
+/// Input of the graph window, see `RendererBuilder::record_input`
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum InputEvent {
+    /// Cursor position in pixels from the top left of the window
+    CursorMoved {
+        x: f64,
+        y: f64,
+    },
+    MouseInput {
+        button: MouseButton,
+        pressed: bool,
+    },
+    /// Scrolled lines, positive away from the user
+    MouseWheel {
+        lines: f32,
+    },
+    Key {
+        key: VirtualKeyCode,
+        pressed: bool,
+    },
+    /// Typed character, e.g. the text of a note
+    Character(char),
+    /// The window was closed, which ends a replay
+    Close,
+}
+
+/// One line of an input log
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RecordedInput {
+    /// Milliseconds since the window was opened
+    pub time_ms: u64,
+    pub event: InputEvent,
+}
+
+impl InputEvent {
+    /// The recorded part of `event`, `None` for events that don't control the graph
+    pub fn from_window_event(event: &WindowEvent) -> Option<Self> {
+        let pressed = |state: &ElementState| *state == ElementState::Pressed;
+        Some(match event {
+            WindowEvent::CursorMoved { position, .. } => InputEvent::CursorMoved {
+                x: position.x,
+                y: position.y,
+            },
+            WindowEvent::MouseInput { state, button, .. } => InputEvent::MouseInput {
+                button: *button,
+                pressed: pressed(state),
+            },
+            WindowEvent::MouseWheel {
+                delta: MouseScrollDelta::LineDelta(_, y),
+                ..
+            } => InputEvent::MouseWheel { lines: *y },
+            WindowEvent::KeyboardInput { input, .. } => InputEvent::Key {
+                key: input.virtual_keycode?,
+                pressed: pressed(&input.state),
+            },
+            WindowEvent::ReceivedCharacter(c) => InputEvent::Character(*c),
+            WindowEvent::CloseRequested => InputEvent::Close,
+            _ => return None,
+        })
+    }
+
+    /// The window event that is replayed for `self`
+    #[allow(deprecated)]
+    pub fn to_window_event(&self) -> WindowEvent<'static> {
+        // SAFETY: the id is only compared by the event handlers of the renderer, it is never
+        // passed to winit
+        let device_id = unsafe { DeviceId::dummy() };
+        let state = |pressed: bool| {
+            if pressed {
+                ElementState::Pressed
+            } else {
+                ElementState::Released
+            }
+        };
+        match self {
+            InputEvent::CursorMoved { x, y } => WindowEvent::CursorMoved {
+                device_id,
+                position: PhysicalPosition::new(*x, *y),
+                modifiers: Default::default(),
+            },
+            InputEvent::MouseInput { button, pressed } => WindowEvent::MouseInput {
+                device_id,
+                state: state(*pressed),
+                button: *button,
+                modifiers: Default::default(),
+            },
+            InputEvent::MouseWheel { lines } => WindowEvent::MouseWheel {
+                device_id,
+                delta: MouseScrollDelta::LineDelta(0.0, *lines),
+                phase: TouchPhase::Moved,
+                modifiers: Default::default(),
+            },
+            InputEvent::Key { key, pressed } => WindowEvent::KeyboardInput {
+                device_id,
+                input: KeyboardInput {
+                    scancode: 0,
+                    state: state(*pressed),
+                    virtual_keycode: Some(*key),
+                    modifiers: Default::default(),
+                },
+                is_synthetic: true,
+            },
+            InputEvent::Character(c) => WindowEvent::ReceivedCharacter(*c),
+            InputEvent::Close => WindowEvent::CloseRequested,
+        }
+    }
+}
+
+/// Writes the input of the graph window as JSON lines of `RecordedInput`
+pub(super) struct InputRecorder {
+    writer: LineWriter<File>,
+    start: Instant,
+}
+
+impl InputRecorder {
+    pub fn create(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            writer: LineWriter::new(File::create(path)?),
+            start: Instant::now(),
+        })
+    }
+
+    pub fn record(&mut self, event: &WindowEvent) {
+        let Some(event) = InputEvent::from_window_event(event) else {
+            return;
+        };
+        let line = RecordedInput {
+            time_ms: self.start.elapsed().as_millis() as u64,
+            event,
+        };
+        let result = serde_json::to_string(&line)
+            .map_err(Box::<dyn Error>::from)
+            .and_then(|json| Ok(writeln!(self.writer, "{}", json)?));
+        if let Err(e) = result {
+            eprintln!("Failed to record input: {}", e);
+        }
+    }
+}
+
+/// Replays an input log at the pace it was recorded
+pub(super) struct InputReplay {
+    events: Vec<RecordedInput>,
+    next: usize,
+    start: Instant,
+}
+
+impl InputReplay {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let mut events = vec![];
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                events.push(serde_json::from_str::<RecordedInput>(&line)?);
+            }
+        }
+        events.sort_by_key(|e| e.time_ms);
+        Ok(Self {
+            events,
+            next: 0,
+            start: Instant::now(),
+        })
+    }
+
+    /// Events whose time has come since the last call, in order
+    pub fn due(&mut self) -> &[RecordedInput] {
+        self.due_at(self.start.elapsed())
+    }
+
+    fn due_at(&mut self, elapsed: Duration) -> &[RecordedInput] {
+        let first = self.next;
+        while self
+            .events
+            .get(self.next)
+            .is_some_and(|e| Duration::from_millis(e.time_ms) <= elapsed)
+        {
+            self.next += 1;
+        }
+        &self.events[first..self.next]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_window_event_roundtrip() {
+        let events = [
+            InputEvent::CursorMoved { x: 10.5, y: 20.0 },
+            InputEvent::MouseInput {
+                button: MouseButton::Left,
+                pressed: true,
+            },
+            InputEvent::MouseWheel { lines: -1.0 },
+            InputEvent::Key {
+                key: VirtualKeyCode::Space,
+                pressed: false,
+            },
+            InputEvent::Character('a'),
+            InputEvent::Close,
+        ];
+        for event in events {
+            let window_event = event.to_window_event();
+            assert_eq!(InputEvent::from_window_event(&window_event), Some(event));
+        }
+        assert_eq!(
+            InputEvent::from_window_event(&WindowEvent::Focused(true)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_replay_in_recorded_order() {
+        let path = std::env::temp_dir().join("grapher_test_input_log.jsonl");
+        std::fs::write(
+            &path,
+            "{\"time_ms\":50,\"event\":\"Close\"}\n\n\
+             {\"time_ms\":0,\"event\":{\"Character\":\"x\"}}\n",
+        )
+        .unwrap();
+        let mut replay = InputReplay::load(&path).unwrap();
+
+        let first = replay.due_at(Duration::from_millis(10));
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].event, InputEvent::Character('x'));
+        assert!(replay.due_at(Duration::from_millis(20)).is_empty());
+        assert_eq!(
+            replay.due_at(Duration::from_millis(50))[0].event,
+            InputEvent::Close
+        );
+    }
+}
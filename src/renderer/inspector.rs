@@ -0,0 +1,193 @@
+use std::error::Error;
+
+#[cfg(not(feature = "egui"))]
+use glam::Vec2;
+use glium::{glutin::surface::WindowSurface, Display, Surface};
+use winit::{event::WindowEvent, event_loop::EventLoopWindowTarget, window::Window};
+
+#[cfg(not(feature = "egui"))]
+use super::draw;
+use super::{hud, window, SceneContext};
+use crate::properties::RigidBody2D;
+#[cfg(not(feature = "egui"))]
+use crate::simulator::SimulationParameters;
+
+const INSPECTOR_SIZE: (u32, u32) = (360, 520);
+
+/// Second window with the details of the selected node, the simulation telemetry and the
+/// parameters. With the `egui` feature the parameter panel moves from the graph window into it.
+pub(super) struct Inspector {
+    window: Window,
+    display: Display<WindowSurface>,
+    #[cfg(feature = "egui")]
+    egui: egui_glium::EguiGlium,
+}
+
+impl Inspector {
+    /// Opens the window, it doesn't wait for vsync so it doesn't slow down the graph window
+    pub fn open(target: &EventLoopWindowTarget<()>, title: &str) -> Result<Self, Box<dyn Error>> {
+        let title = format!("{} - Inspector", title);
+        let (window, display) = window::build(target, &title, INSPECTOR_SIZE, false)?;
+        Ok(Self {
+            #[cfg(feature = "egui")]
+            egui: egui_glium::EguiGlium::new(&display, &window, target),
+            window,
+            display,
+        })
+    }
+
+    /// Handles an event of the inspector window, returns `false` once it is closed
+    pub fn on_event(&mut self, event: &WindowEvent) -> bool {
+        #[cfg(feature = "egui")]
+        let _ = self.egui.on_event(event);
+
+        match event {
+            WindowEvent::CloseRequested | WindowEvent::Destroyed => return false,
+            WindowEvent::Resized(size) => self.display.resize((*size).into()),
+            WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                self.display.resize((**new_inner_size).into())
+            }
+            _ => (),
+        }
+        true
+    }
+
+    pub fn draw(&mut self, scene_context: &SceneContext, fps: f32) {
+        let simulator = &scene_context.simulator;
+        let mut text = node_text(scene_context);
+        text.push(String::new());
+        text.extend(hud::lines(
+            fps,
+            &simulator.metrics(),
+            simulator.is_running(),
+        ));
+
+        let mut target = self.display.draw();
+        target.clear_color(0.05, 0.05, 0.05, 1.0);
+
+        #[cfg(feature = "egui")]
+        {
+            self.egui.run(&self.window, |ctx| {
+                super::panel::parameter_panel(ctx, simulator);
+                egui_glium::egui_winit::egui::CentralPanel::default().show(ctx, |ui| {
+                    for line in &text {
+                        ui.label(line);
+                    }
+                });
+            });
+            self.egui.paint(&self.display, &mut target);
+        }
+        #[cfg(not(feature = "egui"))]
+        {
+            text.push(String::new());
+            text.extend(parameter_lines(
+                &simulator.parameters(),
+                simulator.spring_stiffness(),
+            ));
+            let size = self.window.inner_size();
+            let window_size = Vec2::new(size.width as f32, size.height as f32);
+            let corner = Vec2::new(draw::BOX_PADDING, window_size.y - draw::BOX_PADDING);
+            draw::draw_text_box(&text, corner, &mut target, &self.display, window_size);
+        }
+
+        target.finish().unwrap();
+    }
+}
+
+/// Details of the selected node, or else the hovered node
+fn node_text(scene_context: &SceneContext) -> Vec<String> {
+    let Some(index) = scene_context
+        .selected_node_index
+        .or(scene_context.hovered_node_index)
+    else {
+        return vec!["No node selected".to_string()];
+    };
+
+    let rb_read = scene_context.simulator.rigid_bodies.read().unwrap();
+    let Some(rb) = rb_read.get(index as usize) else {
+        return vec!["No node selected".to_string()];
+    };
+    let degree = scene_context
+        .simulator
+        .springs
+        .read()
+        .unwrap()
+        .iter()
+        .filter(|s| s.rb1 == index as usize || s.rb2 == index as usize)
+        .count();
+    let label = scene_context
+        .labels
+        .as_ref()
+        .and_then(|labels| labels.get(index as usize))
+        .map(String::as_str);
+    node_lines(index, rb, degree, label)
+}
+
+fn node_lines(index: u32, rb: &RigidBody2D, degree: usize, label: Option<&str>) -> Vec<String> {
+    let mut lines = vec![format!("Node {}", index)];
+    if let Some(label) = label {
+        lines.push(format!("Label {}", label));
+    }
+    let state = match (rb.fixed, rb.frozen) {
+        (true, _) => "pinned",
+        (false, true) => "frozen",
+        (false, false) => "moving",
+    };
+    lines.extend([
+        format!("Position {:.1} {:.1}", rb.position.x, rb.position.y),
+        format!("Speed {:.2}", rb.velocity.length()),
+        format!("Mass {:.2}", rb.mass),
+        format!("Charge {:.2}", rb.charge),
+        format!("Degree {}", degree),
+        format!("State {}", state),
+    ]);
+    lines
+}
+
+/// The parameters, without the `egui` feature there are no sliders
+#[cfg(not(feature = "egui"))]
+fn parameter_lines(parameters: &SimulationParameters, spring_stiffness: f32) -> Vec<String> {
+    let on_off = |enabled: bool| if enabled { "on" } else { "off" };
+    vec![
+        format!(
+            "Springs {} stiffness {:.2}",
+            on_off(parameters.spring),
+            spring_stiffness
+        ),
+        format!(
+            "Repel {} force {:.1}",
+            on_off(parameters.repel),
+            parameters.repel_force_const
+        ),
+        format!(
+            "Gravity {} force {:.2}",
+            on_off(parameters.gravity),
+            parameters.gravity_force
+        ),
+        format!("Damping {:.2}", parameters.damping),
+        format!("Theta {:.2}", parameters.quadtree_theta),
+        format!("Delta time {:.4}", parameters.delta_time),
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use glam::Vec2;
+
+    #[test]
+    fn test_node_lines() {
+        let mut rb = RigidBody2D::new(Vec2::new(1.0, -2.5), 3.0);
+        rb.velocity = Vec2::new(3.0, 4.0);
+        rb.fixed = true;
+
+        let lines = node_lines(7, &rb, 2, Some("a"));
+        assert_eq!(lines[0], "Node 7");
+        assert_eq!(lines[1], "Label a");
+        assert_eq!(lines[2], "Position 1.0 -2.5");
+        assert_eq!(lines[3], "Speed 5.00");
+        assert_eq!(lines.last().unwrap(), "State pinned");
+
+        assert_eq!(node_lines(7, &rb, 2, None)[1], "Position 1.0 -2.5");
+    }
+}
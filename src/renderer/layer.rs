@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+/// Layer of the scene, drawn on a plane at a configurable z position.
+///
+/// The scene is drawn with depth testing (`IfLessOrEqual`) and depth writes enabled. A layer with
+/// a higher z covers layers with a lower z no matter in which order they are drawn. Within a
+/// layer, shapes drawn later cover shapes drawn earlier. Nodes are additionally offset by their
+/// `NodeCategory`, see `Renderer::set_z_offset`.
+///
+/// Screen space overlays like the tooltip, the energy graph and the progress bar are drawn after
+/// the scene without depth testing and always cover it. Shapes that should appear between two
+/// layers can be drawn at a z between them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Layer {
+    /// Outlines of the groups toggled with `H` and the quadtree toggled with `Q`
+    Hulls,
+    Edges,
+    /// Hovered and selected edges, the edge dragged in place mode, edge counts between groups and
+    /// the selection box
+    EdgeHighlights,
+    Nodes,
+    /// Node labels and the stiffness labels of highlighted edges
+    Labels,
+    /// Notes, arrows and regions, see `Annotation`
+    Annotations,
+}
+
+impl Layer {
+    /// Default z position, from back to front: hulls, edges, edge highlights, nodes, labels,
+    /// annotations
+    pub fn default_z(self) -> f32 {
+        match self {
+            Layer::Hulls => -1.1,
+            Layer::Edges => -1.0,
+            Layer::EdgeHighlights => -0.9,
+            Layer::Nodes => 0.0,
+            Layer::Labels => 0.5,
+            Layer::Annotations => 0.6,
+        }
+    }
+}
+
+/// z position of every layer and the color the frame is cleared with
+#[derive(Clone, Debug)]
+pub(super) struct Layers {
+    pub clear_color: [f32; 4],
+    pub z: HashMap<Layer, f32>,
+}
+
+impl Layers {
+    pub fn z(&self, layer: Layer) -> f32 {
+        self.z.get(&layer).copied().unwrap_or(layer.default_z())
+    }
+
+    /// Moves the edges and their highlights in front of or behind the nodes, keeping the labels
+    /// in front
+    pub fn set_edges_over_nodes(&mut self, edges_over_nodes: bool) {
+        if edges_over_nodes {
+            let nodes = self.z(Layer::Nodes);
+            let labels = self.z(Layer::Labels);
+            self.z.insert(Layer::Edges, nodes + (labels - nodes) / 3.0);
+            self.z
+                .insert(Layer::EdgeHighlights, nodes + (labels - nodes) * 2.0 / 3.0);
+        } else {
+            self.z.remove(&Layer::Edges);
+            self.z.remove(&Layer::EdgeHighlights);
+        }
+    }
+}
+
+impl Default for Layers {
+    fn default() -> Self {
+        Self {
+            clear_color: [0.0, 0.0, 0.0, 1.0],
+            z: HashMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_edges_over_nodes() {
+        let mut layers = Layers::default();
+        assert!(layers.z(Layer::Edges) < layers.z(Layer::Nodes));
+
+        layers.set_edges_over_nodes(true);
+        assert!(layers.z(Layer::Nodes) < layers.z(Layer::Edges));
+        assert!(layers.z(Layer::Edges) < layers.z(Layer::EdgeHighlights));
+        assert!(layers.z(Layer::EdgeHighlights) < layers.z(Layer::Labels));
+
+        layers.set_edges_over_nodes(false);
+        assert_eq!(layers.z(Layer::Edges), Layer::Edges.default_z());
+    }
+}
@@ -0,0 +1,102 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver, TryRecvError},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use petgraph::{prelude::StableGraph, Directed};
+
+use crate::{
+    io::{FormatRegistry, LoadError},
+    simulator::{Simulator, SimulatorBuilder},
+};
+
+type LoadResult = Result<Loaded, LoadError>;
+
+/// Result of a `Loading`
+pub enum Loaded {
+    /// New simulation and its node labels
+    Simulator(Box<Simulator>, Vec<String>),
+    /// Graph to merge into the current simulation, see `Loading::merge`
+    Graph(StableGraph<String, (), Directed, u32>),
+}
+
+/// Graph file that is loaded and turned into a `Simulator` on a background thread
+pub struct Loading {
+    pub path: PathBuf,
+    /// Keep the parameters and running state of the current simulator instead of starting the new one
+    pub inherit_state: bool,
+    progress: Arc<Mutex<f32>>,
+    result: Receiver<LoadResult>,
+}
+
+impl Loading {
+    /// Builds a new simulation of the graph with `builder`
+    pub fn spawn(
+        path: &Path,
+        formats: Arc<FormatRegistry>,
+        builder: SimulatorBuilder,
+        inherit_state: bool,
+    ) -> Self {
+        Self::load(path, formats, Some(builder), inherit_state)
+    }
+
+    /// Only loads the graph, to merge it into the current simulation
+    pub fn merge(path: &Path, formats: Arc<FormatRegistry>) -> Self {
+        Self::load(path, formats, None, true)
+    }
+
+    fn load(
+        path: &Path,
+        formats: Arc<FormatRegistry>,
+        builder: Option<SimulatorBuilder>,
+        inherit_state: bool,
+    ) -> Self {
+        let progress = Arc::new(Mutex::new(0.0));
+        let (sender, result) = mpsc::channel();
+
+        let thread_path = path.to_path_buf();
+        let thread_progress = Arc::clone(&progress);
+        thread::spawn(move || {
+            let result = formats
+                .load_file_with_progress(&thread_path, |p| {
+                    *thread_progress.lock().unwrap() = p;
+                })
+                .map(|graph| match builder {
+                    Some(builder) => {
+                        let labels = graph.node_weights().cloned().collect();
+                        Loaded::Simulator(Box::new(builder.build(graph)), labels)
+                    }
+                    None => Loaded::Graph(graph),
+                });
+            // The renderer may be gone already
+            let _ = sender.send(result);
+        });
+
+        Self {
+            path: path.to_path_buf(),
+            inherit_state,
+            progress,
+            result,
+        }
+    }
+
+    /// Fraction of the file that was read
+    pub fn progress(&self) -> f32 {
+        *self.progress.lock().unwrap()
+    }
+
+    /// Returns the loaded simulator or graph once loading finished
+    pub fn try_finish(&self) -> Option<LoadResult> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(LoadError::Io(std::io::Error::other(
+                "loading thread panicked",
+            )))),
+        }
+    }
+}
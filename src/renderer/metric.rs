@@ -0,0 +1,74 @@
+use std::sync::mpsc::Receiver;
+
+use super::color;
+use crate::{
+    analysis::DynamicMetric,
+    simulator::{remap, GraphChange, Simulator},
+};
+
+/// Rate the shown values approach the metric with, per second
+const COLOR_SMOOTHING: f32 = 4.0;
+
+/// Colors the nodes by a `DynamicMetric` and fades the colors when the metric changes, see
+/// `Renderer::set_dynamic_metric`
+pub(super) struct MetricColors {
+    metric: Box<dyn DynamicMetric>,
+    changes: Receiver<GraphChange>,
+    /// Values the colors are currently drawn with
+    shown: Vec<f32>,
+    target: Vec<f32>,
+}
+
+impl MetricColors {
+    pub fn new(mut metric: Box<dyn DynamicMetric>, simulator: &Simulator) -> Self {
+        let changes = simulator.subscribe_changes();
+        metric.reset(simulator);
+        let target = metric.values();
+        Self {
+            metric,
+            changes,
+            shown: target.clone(),
+            target,
+        }
+    }
+
+    /// Recomputes the metric for a new simulator, e.g. after a graph file was dropped
+    pub fn reset(&mut self, simulator: &Simulator) {
+        self.changes = simulator.subscribe_changes();
+        self.metric.reset(simulator);
+        self.target = self.metric.values();
+        self.shown = self.target.clone();
+    }
+
+    /// Applies the changes to the graph and moves the shown values `delta_time` seconds closer to
+    /// the metric
+    pub fn update(&mut self, delta_time: f32) {
+        let mut changed = false;
+        for change in self.changes.try_iter() {
+            self.metric.apply(&change);
+            if let GraphChange::NodesRemoved(mapping) | GraphChange::NodesReordered(mapping) =
+                &change
+            {
+                remap(&mut self.shown, mapping);
+            }
+            changed = true;
+        }
+        if changed {
+            self.target = self.metric.values();
+            // New nodes fade in from the lowest value
+            self.shown.resize(self.target.len(), 0.0);
+        }
+
+        let t = 1.0 - (-delta_time * COLOR_SMOOTHING).exp();
+        for (shown, target) in self.shown.iter_mut().zip(&self.target) {
+            *shown += (target - *shown) * t;
+        }
+    }
+
+    /// Color of the first `node_count` nodes
+    pub fn colors(&self, node_count: usize) -> Vec<[f32; 4]> {
+        (0..node_count)
+            .map(|i| color::gradient(self.shown.get(i).copied().unwrap_or(0.0)))
+            .collect()
+    }
+}
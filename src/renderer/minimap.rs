@@ -0,0 +1,69 @@
+use glam::Vec2;
+
+use crate::quadtree::BoundingBox2D;
+
+/// Side length of the minimap in pixels
+const SIZE: f32 = 180.0;
+/// Distance of the minimap to the bottom right corner of the window in pixels
+const MARGIN: f32 = 10.0;
+/// Space around the graph relative to its size
+const PADDING: f32 = 0.05;
+
+/// Square overview of the whole graph in the bottom right corner of the window.
+///
+/// Positions on the minimap are in window pixels with the origin at the top left, like the cursor.
+pub(super) struct Minimap {
+    /// Top left corner
+    pub min: Vec2,
+    pub size: f32,
+    center: Vec2,
+    /// Pixels per world unit
+    scale: f32,
+}
+
+impl Minimap {
+    /// Minimap that shows `bounds` in a window of `window_size` pixels
+    pub fn new(bounds: &BoundingBox2D, window_size: Vec2) -> Self {
+        let extent = bounds.width.max(bounds.height).max(1.0) * (1.0 + 2.0 * PADDING);
+        Self {
+            min: window_size - Vec2::splat(MARGIN + SIZE),
+            size: SIZE,
+            center: bounds.center,
+            scale: SIZE / extent,
+        }
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        let offset = point - self.min;
+        offset.cmpge(Vec2::ZERO).all() && offset.cmple(Vec2::splat(self.size)).all()
+    }
+
+    pub fn to_minimap(&self, world: Vec2) -> Vec2 {
+        let offset = (world - self.center) * self.scale;
+        self.min + self.size / 2.0 + Vec2::new(offset.x, -offset.y)
+    }
+
+    pub fn to_world(&self, point: Vec2) -> Vec2 {
+        let offset = (point - self.min - self.size / 2.0) / self.scale;
+        self.center + Vec2::new(offset.x, -offset.y)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let bounds = BoundingBox2D::new(Vec2::new(10.0, -5.0), 200.0, 50.0);
+        let minimap = Minimap::new(&bounds, Vec2::new(800.0, 600.0));
+
+        let corner = minimap.to_minimap(Vec2::new(110.0, 20.0));
+        assert!(minimap.contains(corner));
+        assert!(corner.x > minimap.min.x + minimap.size / 2.0);
+        // World y points up, window y points down
+        assert!(corner.y < minimap.min.y + minimap.size / 2.0);
+        assert!(minimap.to_world(corner).distance(Vec2::new(110.0, 20.0)) < 1e-3);
+        assert!(!minimap.contains(Vec2::ZERO));
+    }
+}
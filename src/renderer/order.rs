@@ -0,0 +1,100 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::properties::RigidBody2D;
+
+/// Maps a node index and its rigid body to a sort key, nodes with a higher key are drawn on top
+pub type NodeOrderFn = dyn Fn(u32, &RigidBody2D) -> f32 + Send + Sync;
+
+/// Order nodes of the same `NodeCategory` are drawn in, later nodes are drawn on top
+#[derive(Clone)]
+pub enum NodeOrder {
+    /// By node index
+    Index,
+    /// Big nodes first, so small nodes stay visible on top of them
+    SizeDescending,
+    /// Small nodes first
+    SizeAscending,
+    /// See `NodeOrderFn`
+    Custom(Arc<NodeOrderFn>),
+}
+
+impl Default for NodeOrder {
+    fn default() -> Self {
+        NodeOrder::SizeDescending
+    }
+}
+
+/// Nodes are drawn in the order of their category, with the selected node on top
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum NodeCategory {
+    Normal,
+    /// Nodes that are currently dragged
+    Highlighted,
+    /// The selected node
+    Selected,
+}
+
+/// Sorts the nodes by category and `NodeOrder` and gives every node its z position
+#[derive(Clone, Default)]
+pub(super) struct DrawOrder {
+    pub order: NodeOrder,
+    pub z_offsets: HashMap<NodeCategory, f32>,
+}
+
+impl DrawOrder {
+    pub fn z_offset(&self, category: NodeCategory) -> f32 {
+        self.z_offsets.get(&category).copied().unwrap_or(0.0)
+    }
+
+    /// Node indices in the order they are drawn
+    pub fn sort<F>(&self, rigid_bodies: &[RigidBody2D], radius: &[f32], category: F) -> Vec<usize>
+    where
+        F: Fn(usize) -> NodeCategory,
+    {
+        let key = |i: usize| match &self.order {
+            NodeOrder::Index => i as f32,
+            NodeOrder::SizeDescending => -radius[i],
+            NodeOrder::SizeAscending => radius[i],
+            NodeOrder::Custom(f) => f(i as u32, &rigid_bodies[i]),
+        };
+
+        let mut keys: Vec<(f32, NodeCategory, f32, usize)> = (0..rigid_bodies.len())
+            .map(|i| {
+                let category = category(i);
+                (self.z_offset(category), category, key(i), i)
+            })
+            .collect();
+        keys.sort_by(|a, b| {
+            a.0.total_cmp(&b.0)
+                .then(a.1.cmp(&b.1))
+                .then(a.2.total_cmp(&b.2))
+        });
+        keys.into_iter().map(|k| k.3).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use glam::Vec2;
+
+    #[test]
+    fn test_sort() {
+        let rbs = vec![RigidBody2D::new(Vec2::ZERO, 1.0); 4];
+        let radius = [1.0, 3.0, 2.0, 4.0];
+        let category = |i| {
+            if i == 3 {
+                NodeCategory::Selected
+            } else {
+                NodeCategory::Normal
+            }
+        };
+
+        let mut order = DrawOrder::default();
+        assert_eq!(order.sort(&rbs, &radius, category), vec![1, 2, 0, 3]);
+
+        order.order = NodeOrder::Index;
+        order.z_offsets.insert(NodeCategory::Normal, 1.0);
+        assert_eq!(order.sort(&rbs, &radius, category), vec![3, 0, 1, 2]);
+    }
+}
@@ -0,0 +1,50 @@
+use egui_glium::egui_winit::egui;
+
+use crate::simulator::Simulator;
+
+/// Side panel with sliders for the parameters of the running `simulator`
+pub fn parameter_panel(ctx: &egui::Context, simulator: &Simulator) {
+    let mut parameters = simulator.parameters();
+    let mut spring_stiffness = simulator.spring_stiffness();
+
+    egui::SidePanel::left("parameters").show(ctx, |ui| {
+        ui.heading("Simulation");
+
+        ui.checkbox(&mut parameters.spring, "Springs");
+        ui.add(
+            egui::Slider::new(&mut spring_stiffness, 0.0..=1000.0)
+                .logarithmic(true)
+                .text("Spring stiffness"),
+        );
+
+        ui.checkbox(&mut parameters.repel, "Repel");
+        ui.add(
+            egui::Slider::new(&mut parameters.repel_force_const, 0.0..=10000.0)
+                .logarithmic(true)
+                .text("Repel force"),
+        );
+        ui.add(egui::Slider::new(&mut parameters.softening, 0.0..=10.0).text("Softening"));
+
+        ui.checkbox(&mut parameters.gravity, "Gravity");
+        ui.add(
+            egui::Slider::new(&mut parameters.gravity_force, 0.0..=10.0)
+                .logarithmic(true)
+                .text("Gravity force"),
+        );
+
+        ui.add(egui::Slider::new(&mut parameters.damping, 0.0..=1.0).text("Damping"));
+        ui.add(egui::Slider::new(&mut parameters.quadtree_theta, 0.0..=2.0).text("Theta"));
+        ui.add(
+            egui::Slider::new(&mut parameters.delta_time, 0.0001..=0.1)
+                .logarithmic(true)
+                .text("Delta time"),
+        );
+    });
+
+    if parameters != simulator.parameters() {
+        simulator.set_parameters(parameters);
+    }
+    if spring_stiffness != simulator.spring_stiffness() {
+        simulator.set_spring_stiffness(spring_stiffness);
+    }
+}
@@ -0,0 +1,48 @@
+use glam::{Mat4, Vec2};
+use glium::{glutin::surface::WindowSurface, Display, DrawParameters, Frame};
+
+use super::{layer::Layers, Layer};
+use crate::simulator::SimulatorSnapshot;
+
+/// Custom drawing that runs every frame after the graph is drawn, see `Renderer::add_draw_pass`.
+///
+/// ```no_run
+/// use grapher::renderer::{DrawContext, DrawPass, Layer};
+///
+/// struct Marker;
+///
+/// impl DrawPass for Marker {
+///     fn draw(&mut self, context: &mut DrawContext<'_>) {
+///         let z = context.layer_z(Layer::Labels);
+///         // Draw with glium into `context.frame` at `z`, using `context.view` and
+///         // `context.projection` as the camera matrices
+///     }
+/// }
+/// ```
+pub trait DrawPass: Send {
+    fn draw(&mut self, context: &mut DrawContext<'_>);
+}
+
+/// Everything a `DrawPass` needs to draw a frame
+pub struct DrawContext<'a> {
+    pub frame: &'a mut Frame,
+    pub display: &'a Display<WindowSurface>,
+    /// World to camera space
+    pub view: Mat4,
+    /// Camera to clip space
+    pub projection: Mat4,
+    /// Draw parameters of the scene, with the depth test described in `Layer`
+    pub parameters: &'a DrawParameters<'a>,
+    /// State of the simulation at the time of the frame
+    pub snapshot: &'a SimulatorSnapshot,
+    /// Size of the window in pixels
+    pub window_size: Vec2,
+    pub(super) layers: &'a Layers,
+}
+
+impl DrawContext<'_> {
+    /// Current z position of `layer`, draw at a z between two layers to appear between them
+    pub fn layer_z(&self, layer: Layer) -> f32 {
+        self.layers.z(layer)
+    }
+}
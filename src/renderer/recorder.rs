@@ -0,0 +1,205 @@
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(feature = "ffmpeg")]
+use std::{
+    io::Write,
+    process::{Child, Command, Stdio},
+};
+
+use glium::{glutin::surface::WindowSurface, texture::RawImage2d, Display};
+
+#[cfg(not(feature = "ffmpeg"))]
+use super::screenshot;
+
+/// Captures the rendered frames while the simulation runs.
+///
+/// Without the `ffmpeg` feature the frames are written as numbered PNGs into the `output` directory.
+/// With the `ffmpeg` feature they are piped into `ffmpeg`, which has to be on the `PATH`,
+/// and encoded into the `output` video file.
+///
+/// Press `R` to start or stop recording.
+pub struct Recorder {
+    output: PathBuf,
+    fps: u32,
+    sink: Option<Sink>,
+    last_frame: Option<Instant>,
+}
+
+enum Sink {
+    #[cfg(not(feature = "ffmpeg"))]
+    Images { frame: usize },
+    /// `ffmpeg` is spawned once the size of the first frame is known
+    #[cfg(feature = "ffmpeg")]
+    Starting,
+    #[cfg(feature = "ffmpeg")]
+    Ffmpeg {
+        child: Child,
+        width: u32,
+        height: u32,
+    },
+}
+
+impl Recorder {
+    /// `recording-<unix time>` in the working directory, with the `mp4` extension when using `ffmpeg`
+    pub fn default_path() -> PathBuf {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let path = PathBuf::from(format!("recording-{}", time));
+        if cfg!(feature = "ffmpeg") {
+            path.with_extension("mp4")
+        } else {
+            path
+        }
+    }
+
+    pub fn new<P: AsRef<Path>>(output: P) -> Self {
+        Self {
+            output: output.as_ref().to_path_buf(),
+            fps: 30,
+            sink: None,
+            last_frame: None,
+        }
+    }
+
+    /// Frames captured per second. The renderer draws at most 30 frames per second.
+    ///
+    /// Default: `30`
+    pub fn fps(mut self, fps: u32) -> Self {
+        self.fps = fps.max(1);
+        self
+    }
+
+    /// Directory or video file the frames are written to
+    pub fn output(&self) -> &Path {
+        &self.output
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.sink.is_some()
+    }
+
+    /// Starts a new recording, overwriting the previous one
+    pub fn start(&mut self) {
+        self.stop();
+        #[cfg(not(feature = "ffmpeg"))]
+        let sink = Sink::Images { frame: 0 };
+        #[cfg(feature = "ffmpeg")]
+        let sink = Sink::Starting;
+        self.sink = Some(sink);
+        self.last_frame = None;
+    }
+
+    /// Stops the recording and finishes the video file
+    pub fn stop(&mut self) {
+        #[cfg(feature = "ffmpeg")]
+        if let Some(Sink::Ffmpeg { mut child, .. }) = self.sink.take() {
+            drop(child.stdin.take());
+            if let Err(e) = child.wait() {
+                eprintln!("Failed to finish recording: {}", e);
+            }
+        }
+        self.sink = None;
+    }
+
+    /// Captures the last presented frame if a new frame is due
+    pub(super) fn capture(&mut self, display: &Display<WindowSurface>) {
+        if self.sink.is_none()
+            || self
+                .last_frame
+                .is_some_and(|t| t.elapsed() < Duration::from_secs_f32(1.0 / self.fps as f32))
+        {
+            return;
+        }
+        self.last_frame = Some(Instant::now());
+
+        let result = display
+            .read_front_buffer::<RawImage2d<u8>>()
+            .map_err(|e| e.into())
+            .and_then(|image| self.write_frame(image));
+
+        if let Err(e) = result {
+            eprintln!("Recording stopped: {}", e);
+            self.stop();
+        }
+    }
+
+    #[cfg(not(feature = "ffmpeg"))]
+    fn write_frame(&mut self, image: RawImage2d<u8>) -> Result<(), Box<dyn Error>> {
+        let Some(Sink::Images { frame }) = &mut self.sink else {
+            return Ok(());
+        };
+
+        if *frame == 0 {
+            fs::create_dir_all(&self.output)?;
+        }
+        let path = self.output.join(format!("frame_{:05}.png", frame));
+        screenshot::write_png(&path, image.width, image.height, &image.data)?;
+        *frame += 1;
+        Ok(())
+    }
+
+    #[cfg(feature = "ffmpeg")]
+    fn write_frame(&mut self, image: RawImage2d<u8>) -> Result<(), Box<dyn Error>> {
+        // The video size is fixed by the first frame
+        if let Some(Sink::Starting) = self.sink {
+            if let Some(parent) = self.output.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let child = Command::new("ffmpeg")
+                .args([
+                    "-y",
+                    "-loglevel",
+                    "error",
+                    "-f",
+                    "rawvideo",
+                    "-pix_fmt",
+                    "rgba",
+                ])
+                .args(["-s", &format!("{}x{}", image.width, image.height)])
+                .args(["-r", &self.fps.to_string(), "-i", "-"])
+                .args(["-vf", "vflip,pad=ceil(iw/2)*2:ceil(ih/2)*2"])
+                .args(["-pix_fmt", "yuv420p"])
+                .arg(&self.output)
+                .stdin(Stdio::piped())
+                .spawn()?;
+            self.sink = Some(Sink::Ffmpeg {
+                child,
+                width: image.width,
+                height: image.height,
+            });
+        }
+
+        let Some(Sink::Ffmpeg {
+            child,
+            width,
+            height,
+        }) = &mut self.sink
+        else {
+            return Ok(());
+        };
+
+        // Skip frames while the window is resized
+        if image.width != *width || image.height != *height {
+            return Ok(());
+        }
+        child
+            .stdin
+            .as_mut()
+            .ok_or("ffmpeg stdin closed")?
+            .write_all(&image.data)?;
+        Ok(())
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
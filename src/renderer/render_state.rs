@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use glium::{
+    glutin::surface::WindowSurface,
+    vertex::{Vertex as GliumVertex, VertexBufferSlice},
+    Display, Program, VertexBuffer,
+};
+
+use super::{
+    draw::{Attr, Vertex},
+    shader::{self, Shader},
+    shapes,
+};
+
+/// Triangles of the circle that is instanced for every node
+const CIRCLE_RESOLUTION: usize = 10;
+
+/// GPU resources of a tab that are kept between frames
+pub(super) struct RenderState {
+    pub programs: Programs,
+    /// Unit circle drawn once per node instance
+    pub circle: VertexBuffer<Vertex>,
+    pub instances: Option<VertexBuffer<Attr>>,
+    pub edges: Option<VertexBuffer<Vertex>>,
+}
+
+impl RenderState {
+    pub fn new(display: &Display<WindowSurface>) -> Self {
+        let circle = shapes::circle(
+            [0.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0],
+            1.0,
+            CIRCLE_RESOLUTION,
+        );
+        Self {
+            programs: Programs::default(),
+            circle: VertexBuffer::new(display, &circle).unwrap(),
+            instances: None,
+            edges: None,
+        }
+    }
+}
+
+/// Programs by their vertex and fragment shader, compiled on first use
+#[derive(Default)]
+pub(super) struct Programs(HashMap<(Shader, Shader), Program>);
+
+impl Programs {
+    #[cfg(not(all(feature = "shader_reload", debug_assertions)))]
+    pub fn get(
+        &mut self,
+        display: &Display<WindowSurface>,
+        vertex: Shader,
+        fragment: Shader,
+    ) -> &Program {
+        self.0
+            .entry((vertex, fragment))
+            .or_insert_with(|| shader::program(display, vertex, fragment))
+    }
+
+    /// Recompiles the program every frame so changes to the sources on disk show up
+    #[cfg(all(feature = "shader_reload", debug_assertions))]
+    pub fn get(
+        &mut self,
+        display: &Display<WindowSurface>,
+        vertex: Shader,
+        fragment: Shader,
+    ) -> &Program {
+        let program = shader::program(display, vertex, fragment);
+        self.0.insert((vertex, fragment), program);
+        &self.0[&(vertex, fragment)]
+    }
+}
+
+/// Writes `data` to the start of `buffer` and returns the written part, the buffer only grows when
+/// `data` doesn't fit. Returns `None` for empty `data`.
+pub(super) fn upload<'a, T>(
+    display: &Display<WindowSurface>,
+    buffer: &'a mut Option<VertexBuffer<T>>,
+    data: &[T],
+) -> Option<VertexBufferSlice<'a, T>>
+where
+    T: GliumVertex + Copy + Send + 'static,
+{
+    if data.is_empty() {
+        return None;
+    }
+    if buffer.as_ref().is_none_or(|b| b.len() < data.len()) {
+        *buffer =
+            Some(VertexBuffer::empty_dynamic(display, data.len().next_power_of_two()).unwrap());
+    }
+
+    let slice = buffer.as_ref().unwrap().slice(0..data.len()).unwrap();
+    slice.write(data);
+    Some(slice)
+}
@@ -0,0 +1,65 @@
+use std::{
+    error::Error,
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use glium::{glutin::surface::WindowSurface, texture::RawImage2d, Display};
+
+/// `screenshot-<unix time>.png` in the working directory
+pub fn default_path() -> PathBuf {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    PathBuf::from(format!("screenshot-{}.png", time))
+}
+
+/// Reads back the last presented frame and writes it as a PNG
+pub fn save_front_buffer(
+    display: &Display<WindowSurface>,
+    path: &Path,
+) -> Result<(), Box<dyn Error>> {
+    let image: RawImage2d<u8> = display.read_front_buffer()?;
+    write_png(path, image.width, image.height, &image.data)
+}
+
+/// Writes RGBA8 `data` with the bottom row first, as returned by OpenGL
+pub fn write_png(path: &Path, width: u32, height: u32, data: &[u8]) -> Result<(), Box<dyn Error>> {
+    let row_len = width as usize * 4;
+    let flipped: Vec<u8> = data
+        .chunks_exact(row_len)
+        .rev()
+        .flatten()
+        .copied()
+        .collect();
+
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&flipped)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_write_png_flips_rows() {
+        let path = std::env::temp_dir().join("grapher_test_write_png.png");
+        let bottom = [255, 0, 0, 255];
+        let top = [0, 0, 255, 255];
+        write_png(&path, 1, 2, &[bottom, top].concat()).unwrap();
+
+        let decoder = png::Decoder::new(File::open(&path).unwrap());
+        let mut reader = decoder.read_info().unwrap();
+        let mut buf = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut buf).unwrap();
+        assert_eq!(buf, [top, bottom].concat());
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,90 @@
+use glam::Vec2;
+
+/// Nodes selected by dragging a box with shift held
+#[derive(Default)]
+pub struct Selection {
+    nodes: Vec<u32>,
+    /// Corner where the box that is currently dragged started, on the `z = 0` plane
+    box_start: Option<Vec2>,
+}
+
+impl Selection {
+    pub fn nodes(&self) -> &[u32] {
+        &self.nodes
+    }
+
+    pub fn contains(&self, index: u32) -> bool {
+        self.nodes.contains(&index)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Follows the selected nodes to their new indices and drops removed nodes, see
+    /// `GraphChange::NodesReordered` and `GraphChange::NodesRemoved`
+    pub fn remap(&mut self, mapping: &[u32]) {
+        for node in &mut self.nodes {
+            *node = mapping.get(*node as usize).copied().unwrap_or(*node);
+        }
+        self.nodes.retain(|node| *node != u32::MAX);
+    }
+
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.box_start = None;
+    }
+
+    pub fn start_box(&mut self, corner: Vec2) {
+        self.box_start = Some(corner);
+    }
+
+    pub fn is_dragging_box(&self) -> bool {
+        self.box_start.is_some()
+    }
+
+    /// Minimum and maximum corner of the box that is dragged to `cursor`
+    pub fn box_bounds(&self, cursor: Vec2) -> Option<(Vec2, Vec2)> {
+        self.box_start
+            .map(|start| (start.min(cursor), start.max(cursor)))
+    }
+
+    /// Replaces the selection with all nodes in the box dragged to `cursor` that are not `skipped`
+    pub fn finish_box<F>(&mut self, cursor: Vec2, positions: &[Vec2], skipped: F)
+    where
+        F: Fn(usize) -> bool,
+    {
+        let Some((min, max)) = self.box_bounds(cursor) else {
+            return;
+        };
+        self.box_start = None;
+        self.nodes = positions
+            .iter()
+            .enumerate()
+            .filter(|(i, p)| !skipped(*i) && p.cmpge(min).all() && p.cmple(max).all())
+            .map(|(i, _)| i as u32)
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_box_selection() {
+        let positions = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(3.0, 1.0),
+            Vec2::new(0.5, 0.5),
+        ];
+        let mut selection = Selection::default();
+        selection.start_box(Vec2::new(2.0, 2.0));
+        assert!(selection.is_dragging_box());
+
+        selection.finish_box(Vec2::new(-1.0, -1.0), &positions, |i| i == 3);
+        assert!(!selection.is_dragging_box());
+        assert_eq!(selection.nodes(), &[0, 1]);
+    }
+}
@@ -0,0 +1,193 @@
+use glam::Vec2;
+use petgraph::{
+    prelude::StableGraph,
+    stable_graph::NodeIndex,
+    visit::{EdgeRef, IntoEdgeReferences},
+    Undirected,
+};
+
+use crate::{
+    coarsen::{self, CoarseGraph, Mapping},
+    properties::{RigidBody2D, Spring},
+};
+
+/// Smallest distance between neighboring nodes on screen in pixels before a coarser level is shown
+const MIN_NODE_SPACING: f32 = 12.0;
+/// Coarsening stops at this many clusters
+const MIN_CLUSTERS: usize = 8;
+
+/// Shows clusters of the coarsened graph instead of the nodes when zoomed out, so dense graphs
+/// stay readable. Zooming in expands the clusters level by level.
+#[derive(Default)]
+pub(super) struct SemanticZoom {
+    pub enabled: bool,
+    /// Levels of `coarsen::coarsen_levels`, built when first needed
+    levels: Option<Vec<(CoarseGraph, Mapping)>>,
+    /// 0 for the graph itself, otherwise the index into `levels` plus one
+    level: usize,
+}
+
+/// Merged nodes of a coarse level
+pub(super) struct Cluster {
+    /// Mass weighted center of the members
+    pub position: Vec2,
+    pub members: Vec<usize>,
+}
+
+impl SemanticZoom {
+    /// Rebuilds the levels the next time they are needed, e.g. after the graph changed
+    pub fn invalidate(&mut self) {
+        self.levels = None;
+    }
+
+    /// Picks the level for the current zoom, `pixels_per_unit` is the size of one world unit on
+    /// screen
+    pub fn update(
+        &mut self,
+        rigid_bodies: &[RigidBody2D],
+        springs: &[Spring],
+        pixels_per_unit: f32,
+    ) {
+        if !self.enabled {
+            self.level = 0;
+            return;
+        }
+        let levels = self
+            .levels
+            .get_or_insert_with(|| build_levels(rigid_bodies.len(), springs));
+
+        let mean_length = springs
+            .iter()
+            .map(|s| {
+                rigid_bodies[s.rb1]
+                    .position
+                    .distance(rigid_bodies[s.rb2].position)
+            })
+            .sum::<f32>()
+            / springs.len().max(1) as f32;
+        self.level = level(mean_length * pixels_per_unit, levels.len());
+    }
+
+    /// Clusters of the current level by coarse node index, `None` when the nodes are shown
+    pub fn clusters(&self, rigid_bodies: &[RigidBody2D]) -> Option<Vec<Cluster>> {
+        let (coarse, mapping) = self.current()?;
+        Some(
+            coarse
+                .node_indices()
+                .map(|c| {
+                    let members: Vec<usize> = mapping
+                        .members(c)
+                        .iter()
+                        .map(|m| m.index())
+                        .filter(|m| *m < rigid_bodies.len())
+                        .collect();
+                    Cluster {
+                        position: center_of_mass(members.iter().map(|m| &rigid_bodies[*m])),
+                        members,
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    /// Edges between clusters of the current level and the number of edges they merge
+    pub fn edges(&self) -> Vec<(usize, usize, u32)> {
+        self.current().map_or(vec![], |(coarse, _)| {
+            coarse
+                .edge_references()
+                .map(|e| (e.source().index(), e.target().index(), *e.weight()))
+                .collect()
+        })
+    }
+
+    fn current(&self) -> Option<&(CoarseGraph, Mapping)> {
+        self.levels.as_ref()?.get(self.level.checked_sub(1)?)
+    }
+}
+
+fn build_levels(node_count: usize, springs: &[Spring]) -> Vec<(CoarseGraph, Mapping)> {
+    let mut graph: StableGraph<(), (), Undirected, u32> = StableGraph::default();
+    for _ in 0..node_count {
+        graph.add_node(());
+    }
+    for spring in springs {
+        graph.add_edge(NodeIndex::new(spring.rb1), NodeIndex::new(spring.rb2), ());
+    }
+    coarsen::coarsen_levels(&graph, MIN_CLUSTERS)
+}
+
+/// Level whose clusters are at least `MIN_NODE_SPACING` apart, given the on screen length of an
+/// edge. Every level roughly halves the nodes, which spreads them `sqrt(2)` times further apart.
+fn level(edge_pixels: f32, level_count: usize) -> usize {
+    if edge_pixels >= MIN_NODE_SPACING {
+        return 0;
+    }
+    if edge_pixels <= 0.0 || !edge_pixels.is_finite() {
+        return level_count;
+    }
+    let level = (2.0 * (MIN_NODE_SPACING / edge_pixels).log2()).ceil() as usize;
+    level.min(level_count)
+}
+
+fn center_of_mass<'a>(rigid_bodies: impl Iterator<Item = &'a RigidBody2D>) -> Vec2 {
+    let (sum, mass) = rigid_bodies.fold((Vec2::ZERO, 0.0), |(sum, mass), rb| {
+        (sum + rb.position * rb.mass, mass + rb.mass)
+    });
+    if mass > 0.0 {
+        sum / mass
+    } else {
+        Vec2::ZERO
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_level_from_edge_length() {
+        assert_eq!(level(MIN_NODE_SPACING * 2.0, 5), 0);
+        assert_eq!(level(MIN_NODE_SPACING / 2.0, 5), 2);
+        assert_eq!(level(MIN_NODE_SPACING / 1.2, 5), 1);
+        assert_eq!(level(0.01, 5), 5);
+        assert_eq!(level(0.0, 3), 3);
+    }
+
+    #[test]
+    fn test_clusters_follow_zoom() {
+        let rigid_bodies: Vec<RigidBody2D> = (0..16)
+            .map(|i| RigidBody2D::new(Vec2::new(i as f32, 0.0), 1.0))
+            .collect();
+        let springs: Vec<Spring> = (0..15)
+            .map(|i| Spring {
+                rb1: i,
+                rb2: i + 1,
+                spring_stiffness: 1.0,
+                spring_neutral_len: 1.0,
+                damping: 0.0,
+                kind: None,
+            })
+            .collect();
+        let mut zoom = SemanticZoom {
+            enabled: true,
+            ..Default::default()
+        };
+
+        zoom.update(&rigid_bodies, &springs, 100.0);
+        assert!(zoom.clusters(&rigid_bodies).is_none());
+
+        zoom.update(&rigid_bodies, &springs, 1.0);
+        let clusters = zoom.clusters(&rigid_bodies).unwrap();
+        assert!(clusters.len() < rigid_bodies.len());
+        let members: usize = clusters.iter().map(|c| c.members.len()).sum();
+        assert_eq!(members, 16);
+        let mean: Vec2 = clusters[0]
+            .members
+            .iter()
+            .map(|m| rigid_bodies[*m].position)
+            .sum::<Vec2>()
+            / clusters[0].members.len() as f32;
+        assert!(clusters[0].position.distance(mean) < 1e-4);
+        assert!(!zoom.edges().is_empty());
+    }
+}
@@ -0,0 +1,11 @@
+use super::Annotation;
+use crate::simulator::SimulatorSnapshot;
+
+/// State of a tab that can be saved and resumed with `Renderer::from_session`
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Session {
+    pub snapshot: SimulatorSnapshot,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub annotations: Vec<Annotation>,
+}
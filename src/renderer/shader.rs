@@ -0,0 +1,132 @@
+//! GLSL sources of the renderer.
+//!
+//! The sources are embedded into the binary. With the `shader_reload` feature debug builds read
+//! them from `src/renderer/shaders` instead and pick up changes on the next frame, so shaders can
+//! be tweaked without restarting the layout.
+
+use glium::{glutin::surface::WindowSurface, Display, Program};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Shader {
+    /// Vertices with a position and a color
+    Vertex,
+    /// One instanced shape per node
+    Instance,
+    Fragment,
+}
+
+impl Shader {
+    fn embedded_source(self) -> &'static str {
+        match self {
+            Shader::Vertex => include_str!("shaders/vertex.glsl"),
+            Shader::Instance => include_str!("shaders/instance.glsl"),
+            Shader::Fragment => include_str!("shaders/fragment.glsl"),
+        }
+    }
+}
+
+/// Compiles the program made of the `vertex` and `fragment` shader
+#[cfg(not(all(feature = "shader_reload", debug_assertions)))]
+pub fn program(display: &Display<WindowSurface>, vertex: Shader, fragment: Shader) -> Program {
+    Program::from_source(
+        display,
+        vertex.embedded_source(),
+        fragment.embedded_source(),
+        None,
+    )
+    .unwrap()
+}
+
+/// Compiles the program made of the `vertex` and `fragment` shader from the sources on disk.
+///
+/// Falls back to the embedded sources if the sources on disk don't compile.
+#[cfg(all(feature = "shader_reload", debug_assertions))]
+pub fn program(display: &Display<WindowSurface>, vertex: Shader, fragment: Shader) -> Program {
+    let (vertex_src, fragment_src) = (reload::source(vertex), reload::source(fragment));
+    match Program::from_source(display, &vertex_src, &fragment_src, None) {
+        Ok(program) => program,
+        Err(e) => {
+            reload::report(vertex, fragment, &e);
+            Program::from_source(
+                display,
+                vertex.embedded_source(),
+                fragment.embedded_source(),
+                None,
+            )
+            .unwrap()
+        }
+    }
+}
+
+#[cfg(all(feature = "shader_reload", debug_assertions))]
+mod reload {
+    use std::{collections::HashMap, fs, path::PathBuf, sync::Mutex, time::SystemTime};
+
+    use glium::ProgramCreationError;
+
+    use super::Shader;
+
+    /// Sources read from disk with the modification time of their file
+    static SOURCES: Mutex<Option<HashMap<Shader, (SystemTime, String)>>> = Mutex::new(None);
+    /// Last reported compile error, so a broken shader is only reported once
+    static LAST_ERROR: Mutex<Option<String>> = Mutex::new(None);
+
+    fn file_name(shader: Shader) -> &'static str {
+        match shader {
+            Shader::Vertex => "vertex.glsl",
+            Shader::Instance => "instance.glsl",
+            Shader::Fragment => "fragment.glsl",
+        }
+    }
+
+    fn path(shader: Shader) -> PathBuf {
+        [
+            env!("CARGO_MANIFEST_DIR"),
+            "src/renderer/shaders",
+            file_name(shader),
+        ]
+        .iter()
+        .collect()
+    }
+
+    /// Current source of `shader`, re-read if its file changed
+    pub fn source(shader: Shader) -> String {
+        let path = path(shader);
+        let Ok(modified) = fs::metadata(&path).and_then(|m| m.modified()) else {
+            return shader.embedded_source().to_string();
+        };
+
+        let mut sources = SOURCES.lock().unwrap();
+        let sources = sources.get_or_insert_with(HashMap::new);
+        if let Some((time, source)) = sources.get(&shader) {
+            if *time == modified {
+                return source.clone();
+            }
+        }
+
+        match fs::read_to_string(&path) {
+            Ok(source) => {
+                sources.insert(shader, (modified, source.clone()));
+                source
+            }
+            Err(e) => {
+                eprintln!("Failed to read shader {}: {}", path.display(), e);
+                shader.embedded_source().to_string()
+            }
+        }
+    }
+
+    pub fn report(vertex: Shader, fragment: Shader, error: &ProgramCreationError) {
+        let message = format!(
+            "Failed to compile {} with {}, using the embedded shaders: {}",
+            file_name(vertex),
+            file_name(fragment),
+            error
+        );
+        let mut last_error = LAST_ERROR.lock().unwrap();
+        if last_error.as_ref() != Some(&message) {
+            eprintln!("{}", message);
+            *last_error = Some(message);
+        }
+    }
+}
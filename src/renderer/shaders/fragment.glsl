@@ -0,0 +1,8 @@
+#version 140
+
+in vec4 vertex_color;
+out vec4 color;
+
+void main() {
+    color = vec4(vertex_color);
+}
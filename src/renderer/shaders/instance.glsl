@@ -0,0 +1,17 @@
+#version 150
+
+in vec3 position;
+in vec3 color;
+in vec4 color_attr;
+in vec3 world_position;
+in float scale;
+
+out vec4 vertex_color;
+
+uniform mat4 projection;
+uniform mat4 matrix;
+
+void main() {
+    vertex_color = color_attr;
+    gl_Position = projection * matrix * vec4((position*scale)+world_position, 1.0);
+}
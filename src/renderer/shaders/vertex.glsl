@@ -0,0 +1,13 @@
+#version 150
+
+in vec3 position;
+in vec4 color;
+out vec4 vertex_color;
+
+uniform mat4 projection;
+uniform mat4 matrix;
+
+void main() {
+    vertex_color = color;
+    gl_Position = projection * matrix * vec4(position, 1.0);
+}
@@ -32,7 +32,22 @@ pub fn rectangle(pos: [f32; 3], color: [f32; 4], s: f32) -> Vec<Vertex> {
     ]
 }
 
-#[allow(dead_code)]
+/// Filled rectangle with the half extents `x` and `y`, for `TrianglesList`
+pub fn rectangle_filled(pos: [f32; 3], color: [f32; 4], x: f32, y: f32) -> Vec<Vertex> {
+    let corner = |dx: f32, dy: f32| Vertex {
+        position: [pos[0] + dx * x, pos[1] + dy * y, pos[2]],
+        color,
+    };
+    vec![
+        corner(-1.0, -1.0),
+        corner(1.0, -1.0),
+        corner(-1.0, 1.0),
+        corner(1.0, 1.0),
+        corner(1.0, -1.0),
+        corner(-1.0, 1.0),
+    ]
+}
+
 pub fn rectangle_lines(pos: [f32; 3], color: [f32; 4], x: f32, y: f32) -> Vec<Vertex> {
     vec![
         Vertex {
@@ -113,3 +128,50 @@ pub fn line(p1: [f32; 3], p2: [f32; 3], color: [f32; 4]) -> Vec<Vertex> {
         },
     ]
 }
+
+/// A line with a `width` in world units, as two triangles
+pub fn thick_line(p1: [f32; 3], p2: [f32; 3], width: f32, color: [f32; 4]) -> Vec<Vertex> {
+    let dir = glam::Vec2::new(p2[0] - p1[0], p2[1] - p1[1]).normalize_or_zero();
+    let offset = dir.perp() * width * 0.5;
+    let corner = |p: [f32; 3], sign: f32| Vertex {
+        position: [p[0] + offset.x * sign, p[1] + offset.y * sign, p[2]],
+        color,
+    };
+
+    vec![
+        corner(p1, 1.0),
+        corner(p1, -1.0),
+        corner(p2, 1.0),
+        corner(p2, 1.0),
+        corner(p1, -1.0),
+        corner(p2, -1.0),
+    ]
+}
+
+/// A quadratic Bezier curve from `p1` to `p2` as `segments` lines
+pub fn bezier(
+    p1: [f32; 3],
+    control: [f32; 3],
+    p2: [f32; 3],
+    segments: usize,
+    color: [f32; 4],
+) -> Vec<Vertex> {
+    let point = |t: f32| {
+        let (a, b, c) = ((1.0 - t) * (1.0 - t), 2.0 * (1.0 - t) * t, t * t);
+        [
+            a * p1[0] + b * control[0] + c * p2[0],
+            a * p1[1] + b * control[1] + c * p2[1],
+            a * p1[2] + b * control[2] + c * p2[2],
+        ]
+    };
+
+    let mut shape = Vec::with_capacity(2 * segments);
+    for i in 0..segments {
+        shape.append(&mut line(
+            point(i as f32 / segments as f32),
+            point((i + 1) as f32 / segments as f32),
+            color,
+        ));
+    }
+    shape
+}
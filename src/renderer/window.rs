@@ -0,0 +1,132 @@
+use std::{error::Error, num::NonZeroU32, panic};
+
+use glium::{
+    glutin::{
+        self,
+        display::GetGlDisplay,
+        prelude::*,
+        surface::{SwapInterval, WindowSurface},
+    },
+    Display,
+};
+use raw_window_handle::HasRawWindowHandle;
+use winit::{
+    event_loop::{EventLoop, EventLoopBuilder, EventLoopWindowTarget},
+    monitor::{MonitorHandle, VideoMode},
+    window::{Fullscreen, Window},
+};
+
+/// How the window is shown, see `RendererBuilder::window_mode`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WindowMode {
+    #[default]
+    Windowed,
+    /// A borderless window covering the whole monitor
+    Borderless,
+    /// Takes over the monitor at its current resolution with the highest refresh rate, or else
+    /// its highest resolution. Falls back to `Borderless` where the video mode can't be changed,
+    /// e.g. on Wayland.
+    Exclusive,
+}
+
+/// Switches `window` to `mode` on the monitor it is currently on
+pub fn set_mode(window: &Window, mode: WindowMode) {
+    let monitor = window.current_monitor();
+    let fullscreen = match mode {
+        WindowMode::Windowed => None,
+        WindowMode::Borderless => Some(Fullscreen::Borderless(monitor)),
+        WindowMode::Exclusive => match monitor.as_ref().and_then(video_mode) {
+            Some(video_mode) => Some(Fullscreen::Exclusive(video_mode)),
+            None => Some(Fullscreen::Borderless(monitor)),
+        },
+    };
+    window.set_fullscreen(fullscreen);
+}
+
+/// Video mode for exclusive fullscreen on `monitor`
+fn video_mode(monitor: &MonitorHandle) -> Option<VideoMode> {
+    let size = monitor.size();
+    let native = monitor
+        .video_modes()
+        .filter(|m| m.size() == size)
+        .max_by_key(|m| m.refresh_rate_millihertz());
+    native.or_else(|| {
+        monitor.video_modes().max_by_key(|m| {
+            let size = m.size();
+            (size.width * size.height, m.refresh_rate_millihertz())
+        })
+    })
+}
+
+/// Creates the event loop. winit panics if there is no display server, which is turned into an
+/// error so the caller can fall back to rendering without a window.
+pub fn event_loop() -> Result<EventLoop<()>, Box<dyn Error>> {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let event_loop = panic::catch_unwind(|| EventLoopBuilder::new().build());
+    panic::set_hook(hook);
+
+    event_loop.map_err(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "failed to create event loop".to_string());
+        message.into()
+    })
+}
+
+/// Opens a window with an OpenGL context like `glium::backend::glutin::SimpleWindowBuilder`, which
+/// can't turn vsync on or off.
+///
+/// Fails if there is no OpenGL implementation, e.g. on a server without a GPU.
+pub fn build(
+    event_loop: &EventLoopWindowTarget<()>,
+    title: &str,
+    size: (u32, u32),
+    vsync: bool,
+) -> Result<(Window, Display<WindowSurface>), Box<dyn Error>> {
+    let window_builder = winit::window::WindowBuilder::new()
+        .with_title(title)
+        .with_inner_size(winit::dpi::PhysicalSize::new(size.0, size.1));
+    let (window, gl_config) = glutin_winit::DisplayBuilder::new()
+        .with_window_builder(Some(window_builder))
+        .build(
+            event_loop,
+            glutin::config::ConfigTemplateBuilder::new(),
+            |mut configs| configs.next().unwrap(),
+        )?;
+    let window = window.ok_or("failed to create window")?;
+
+    let (width, height): (u32, u32) = window.inner_size().into();
+    let attrs = glutin::surface::SurfaceAttributesBuilder::<WindowSurface>::new().build(
+        window.raw_window_handle(),
+        NonZeroU32::new(width.max(1)).unwrap(),
+        NonZeroU32::new(height.max(1)).unwrap(),
+    );
+    let surface = unsafe {
+        gl_config
+            .display()
+            .create_window_surface(&gl_config, &attrs)?
+    };
+    let context_attributes =
+        glutin::context::ContextAttributesBuilder::new().build(Some(window.raw_window_handle()));
+    let context = unsafe {
+        gl_config
+            .display()
+            .create_context(&gl_config, &context_attributes)?
+    }
+    .make_current(&surface)?;
+
+    let interval = if vsync {
+        SwapInterval::Wait(NonZeroU32::MIN)
+    } else {
+        SwapInterval::DontWait
+    };
+    if let Err(e) = surface.set_swap_interval(&context, interval) {
+        eprintln!("Failed to set vsync: {}", e);
+    }
+
+    let display = Display::from_context_surface(context, surface)?;
+    Ok((window, display))
+}
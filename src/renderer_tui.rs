@@ -0,0 +1,190 @@
+//! Renders the layout to the terminal with Braille characters, for previews without a GPU.
+//!
+//! # Example
+//! ```no_run
+//!use grapher::prelude::*;
+//!use grapher::renderer_tui;
+//!
+//!let mut rng = rand::thread_rng();
+//!let graph: petgraph::Graph<(), (), petgraph::Directed> =
+//!    petgraph_gen::barabasi_albert_graph(&mut rng, 200, 1, None);
+//!
+//!let simulator = SimulatorBuilder::new().build(graph.into());
+//!renderer_tui::run(&simulator, 80, 24);
+//! ```
+
+use std::{
+    io::{self, Write},
+    time::Duration,
+};
+
+use glam::Vec2;
+
+use crate::simulator::Simulator;
+
+/// Dot of a Braille character at `[x][y]` in its 2x4 cell
+const BRAILLE_DOTS: [[u32; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+const BRAILLE_BASE: u32 = 0x2800;
+const FRAME_TIME: Duration = Duration::from_millis(100);
+
+/// Draws the nodes and edges onto a canvas of `columns` x `rows` characters
+pub struct BrailleCanvas {
+    columns: usize,
+    rows: usize,
+    cells: Vec<u32>,
+}
+
+impl BrailleCanvas {
+    pub fn new(columns: usize, rows: usize) -> Self {
+        Self {
+            columns,
+            rows,
+            cells: vec![0; columns * rows],
+        }
+    }
+
+    /// Width in dots
+    pub fn width(&self) -> usize {
+        self.columns * 2
+    }
+
+    /// Height in dots
+    pub fn height(&self) -> usize {
+        self.rows * 4
+    }
+
+    /// Sets the dot at `x`, `y`, with `0, 0` at the top left. Dots outside the canvas are ignored.
+    pub fn set(&mut self, x: i64, y: i64) {
+        if x < 0 || y < 0 || x as usize >= self.width() || y as usize >= self.height() {
+            return;
+        }
+        let (x, y) = (x as usize, y as usize);
+        self.cells[(y / 4) * self.columns + x / 2] |= BRAILLE_DOTS[x % 2][y % 4];
+    }
+
+    /// Draws a line with Bresenham's algorithm
+    pub fn line(&mut self, from: (i64, i64), to: (i64, i64)) {
+        let (mut x, mut y) = from;
+        let dx = (to.0 - x).abs();
+        let dy = -(to.1 - y).abs();
+        let sx = if x < to.0 { 1 } else { -1 };
+        let sy = if y < to.1 { 1 } else { -1 };
+        let mut err = dx + dy;
+
+        loop {
+            self.set(x, y);
+            if (x, y) == to {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+
+    /// One line of text per row
+    pub fn to_string_lines(&self) -> Vec<String> {
+        self.cells
+            .chunks(self.columns)
+            .map(|row| {
+                row.iter()
+                    .map(|cell| char::from_u32(BRAILLE_BASE + cell).unwrap())
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Renders the current layout, scaled to fit `columns` x `rows` characters
+pub fn render(simulator: &Simulator, columns: usize, rows: usize) -> String {
+    let mut canvas = BrailleCanvas::new(columns, rows);
+    let positions = simulator.body_positions();
+
+    if !positions.is_empty() && columns > 0 && rows > 0 {
+        let min = positions.iter().fold(Vec2::INFINITY, |a, b| a.min(*b));
+        let max = positions.iter().fold(Vec2::NEG_INFINITY, |a, b| a.max(*b));
+        let size = Vec2::new(canvas.width() as f32 - 1.0, canvas.height() as f32 - 1.0);
+        // Same scale on both axes, Braille dots are about square
+        let scale = (size / (max - min).max(Vec2::splat(f32::EPSILON))).min_element();
+        let offset = (size - (max - min) * scale) / 2.0;
+
+        // Flip y, the canvas grows downwards
+        let to_canvas = |p: Vec2| {
+            let p = (p - min) * scale + offset;
+            (p.x.round() as i64, (size.y - p.y).round() as i64)
+        };
+
+        for spring in simulator.springs.read().unwrap().iter() {
+            canvas.line(
+                to_canvas(positions[spring.rb1]),
+                to_canvas(positions[spring.rb2]),
+            );
+        }
+        let rb_read = simulator.rigid_bodies.read().unwrap();
+        for (position, _) in positions
+            .iter()
+            .zip(rb_read.iter())
+            .filter(|(_, rb)| !rb.rope)
+        {
+            let (x, y) = to_canvas(*position);
+            canvas.set(x, y);
+            canvas.set(x + 1, y);
+            canvas.set(x, y + 1);
+            canvas.set(x + 1, y + 1);
+        }
+    }
+
+    canvas.to_string_lines().join("\n")
+}
+
+/// Runs the simulation and redraws it in the terminal until it is stopped with `Ctrl+C`.
+///
+/// The last row is used for the status line.
+pub fn run(simulator: &Simulator, columns: usize, rows: usize) {
+    let mut stdout = io::stdout();
+    // Clear the screen and hide the cursor
+    let _ = write!(stdout, "\x1b[2J\x1b[?25l");
+
+    loop {
+        let steps = simulator.step_for(FRAME_TIME);
+        let frame = render(simulator, columns, rows.saturating_sub(1));
+        let written = write!(
+            stdout,
+            "\x1b[H{}\n{} steps/frame, kinetic energy {:.2}\x1b[K",
+            frame,
+            steps,
+            simulator.total_kinetic_energy()
+        )
+        .and_then(|_| stdout.flush());
+
+        if written.is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_braille_canvas() {
+        let mut canvas = BrailleCanvas::new(2, 1);
+        canvas.set(0, 0);
+        canvas.set(3, 3);
+        canvas.set(4, 0);
+        assert_eq!(canvas.to_string_lines(), vec!["\u{2801}\u{2880}"]);
+
+        let mut canvas = BrailleCanvas::new(1, 1);
+        canvas.line((0, 0), (1, 3));
+        assert_eq!(canvas.to_string_lines()[0].chars().count(), 1);
+        assert_ne!(canvas.cells[0] & BRAILLE_DOTS[0][0], 0);
+        assert_ne!(canvas.cells[0] & BRAILLE_DOTS[1][3], 0);
+    }
+}
@@ -1,39 +1,403 @@
 use std::{
+    collections::{HashMap, VecDeque},
     fmt::Debug,
-    sync::{Arc, Mutex, RwLock},
-    thread::{self, JoinHandle},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Condvar, Mutex, RwLock,
+    },
+    time::{Duration, Instant},
 };
 
 use glam::{Vec2, Vec3, Vec3Swizzles};
 use petgraph::{
     prelude::StableGraph,
-    visit::{EdgeRef, IntoEdgeReferences},
+    stable_graph::NodeIndex,
+    visit::{EdgeRef, IntoEdgeReferences, NodeIndexable},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rayon::{
+    iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator},
+    ThreadPool, ThreadPoolBuilder,
 };
-use rand::Rng;
 
 use crate::{
-    properties::{RigidBody2D, Spring},
-    quadtree::BoundingBox2D,
-    quadtree::QuadTree,
+    analysis::{self, DynamicMetric, DynamicPageRank},
+    edge_grid::EdgeGrid,
+    layout::{InitialLayout, RANDOM_PLACEMENT},
+    properties::{EdgeKind, GravityWell, Obstacle, RigidBody2D, Spring},
+    quadtree::{self, BoundingBox2D, QuadTree},
 };
 
+const ROPE_PARTICLE_MASS: f32 = 0.2;
+const ROPE_PARTICLE_CHARGE: f32 = 0.1;
+/// Most segments a rope edge is split into, see `SimulatorBuilder::rope_segment_length`
+const MAX_ROPE_SEGMENTS: usize = 64;
+/// Range of the adaptive time step relative to `delta_time` and how fast it grows per step, see
+/// `SimulatorBuilder::adaptive_delta_time`
+const MIN_DELTA_TIME_SCALE: f32 = 1e-3;
+const MAX_DELTA_TIME_SCALE: f32 = 10.0;
+const DELTA_TIME_GROWTH: f32 = 1.1;
+/// Lowest temperature of `SimulatorBuilder::annealing` relative to the start temperature
+const MIN_TEMPERATURE_SCALE: f32 = 1e-3;
+
+/// Change to the graph of a running `Simulator`, see `Simulator::subscribe_changes`
+#[derive(Clone, Debug, PartialEq)]
+pub enum GraphChange {
+    /// A node was added after the existing nodes
+    NodeInserted,
+    /// A spring from the first to the second node was added
+    EdgeInserted(usize, usize),
+    /// A spring from the first to the second node was removed
+    EdgeRemoved(usize, usize),
+    /// Nodes were removed after their springs, contains the new index of every node and
+    /// `u32::MAX` for removed nodes
+    NodesRemoved(Vec<u32>),
+    /// Nodes were moved to new indices, see `SimulatorBuilder::spatial_sort_interval`. Contains
+    /// the new index of every node.
+    NodesReordered(Vec<u32>),
+}
+
+/// Changes applied together under a single lock with `Simulator::apply`.
+///
+/// The new nodes get the indices after the existing nodes in order, so `add_edges` can connect
+/// them. Edges are added before `remove_edges` and `remove_nodes` are applied, all indices refer
+/// to the nodes after adding `add_nodes`.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct GraphUpdate {
+    /// Number of nodes added after the existing nodes, placed close to their connected neighbors
+    pub add_nodes: usize,
+    /// Nodes connected with a spring of the default edge kind
+    pub add_edges: Vec<(u32, u32)>,
+    /// Nodes whose spring is removed, in either direction. Removes one spring per entry.
+    pub remove_edges: Vec<(u32, u32)>,
+    /// Nodes removed together with their springs
+    pub remove_nodes: Vec<u32>,
+}
+
+/// Moves every value to the index `mapping` assigns to it and drops values mapped to `u32::MAX`,
+/// see `GraphChange::NodesRemoved` and `GraphChange::NodesReordered`.
+///
+/// Values without an entry in `mapping` are kept at the end.
+pub(crate) fn remap<T>(values: &mut Vec<T>, mapping: &[u32]) {
+    let mut remapped: Vec<Option<T>> = std::iter::repeat_with(|| None)
+        .take(mapping.iter().filter(|m| **m != u32::MAX).count())
+        .collect();
+    let mut rest = vec![];
+    for (i, value) in values.drain(..).enumerate() {
+        match mapping.get(i) {
+            Some(&u32::MAX) => (),
+            Some(m) => {
+                if let Some(slot) = remapped.get_mut(*m as usize) {
+                    *slot = Some(value);
+                }
+            }
+            None => rest.push(value),
+        }
+    }
+    *values = remapped.into_iter().flatten().chain(rest).collect();
+}
+
+/// Parameters of a `Simulator` that can be changed while it is running.
+///
+/// See `SimulatorBuilder` for the meaning of each parameter.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SimulationParameters {
+    pub repel: bool,
+    pub spring: bool,
+    pub gravity: bool,
+    pub delta_time: f32,
+    pub gravity_force: f32,
+    pub repel_force_const: f32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub softening: f32,
+    /// Distance beyond which nodes don't repel each other
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cutoff_radius: Option<f32>,
+    pub damping: f32,
+    pub quadtree_theta: f32,
+    pub freeze_thresh: f32,
+}
+
+/// State of a `Simulator` that can be saved and resumed with `SimulatorBuilder::build_from_snapshot`
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SimulatorSnapshot {
+    pub rigid_bodies: Vec<RigidBody2D>,
+    pub springs: Vec<Spring>,
+    pub edge_kinds: Vec<EdgeKind>,
+    pub parameters: SimulationParameters,
+}
+
+/// Time spent in each phase of a simulation step, see `Simulator::step_stats`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StepStats {
+    pub quadtree: Duration,
+    /// Repel, center gravity and gravity well forces
+    pub node_forces: Duration,
+    pub spring_forces: Duration,
+    /// Applying the forces and moving the nodes
+    pub integration: Duration,
+    pub total: Duration,
+}
+
+impl std::ops::AddAssign for StepStats {
+    fn add_assign(&mut self, rhs: Self) {
+        self.quadtree += rhs.quadtree;
+        self.node_forces += rhs.node_forces;
+        self.spring_forces += rhs.spring_forces;
+        self.integration += rhs.integration;
+        self.total += rhs.total;
+    }
+}
+
+/// Telemetry of a `Simulator`, updated by every simulation step, see `Simulator::metrics`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SimulatorMetrics {
+    /// Steps since the simulator was built
+    pub steps: u64,
+    /// Steps in the last full second the simulation was running
+    pub steps_per_second: f32,
+    pub node_count: usize,
+    pub edge_count: usize,
+    /// See `Simulator::total_kinetic_energy`
+    pub kinetic_energy: f32,
+    /// Depth of the quadtree of the last step, `0` if repulsion and gravity are off
+    pub quadtree_depth: u32,
+    /// Phase timings of the last step
+    pub step_stats: StepStats,
+    /// Time step of the last step, see `SimulatorBuilder::adaptive_delta_time`
+    pub delta_time: f32,
+}
+
+/// Progress of a simulation, passed to the callbacks registered with `Simulator::on_step`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StepInfo {
+    /// Steps since the simulator was built, including this one
+    pub iteration: u64,
+    /// See `Simulator::total_kinetic_energy`
+    pub kinetic_energy: f32,
+    /// Duration of this step
+    pub step_time: Duration,
+    /// Time since the first step
+    pub elapsed: Duration,
+}
+
+/// Callback called after every simulation step, see `Simulator::on_step`
+pub type StepCallback = dyn Fn(&StepInfo) + Send + Sync;
+
+#[derive(Default)]
+struct StepCallbacks(Vec<Box<StepCallback>>);
+
+impl std::fmt::Debug for StepCallbacks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "StepCallbacks({})", self.0.len())
+    }
+}
+
+/// Rigid body of every node in the order of the graph the simulator was built from, followed by
+/// the inserted nodes. Rope particles are left out. Follows the changes passed to `notify`, so
+/// nodes keep their index even when `SimulatorBuilder::spatial_sort_interval` moves their bodies.
+#[derive(Clone, Debug)]
+struct NodeOrder {
+    bodies: Vec<u32>,
+    body_count: usize,
+}
+
+impl NodeOrder {
+    fn new(rigid_bodies: &[RigidBody2D]) -> Self {
+        Self {
+            bodies: (0..rigid_bodies.len() as u32)
+                .filter(|i| !rigid_bodies[*i as usize].rope)
+                .collect(),
+            body_count: rigid_bodies.len(),
+        }
+    }
+
+    fn apply(&mut self, change: &GraphChange) {
+        match change {
+            GraphChange::NodeInserted => {
+                self.bodies.push(self.body_count as u32);
+                self.body_count += 1;
+            }
+            GraphChange::NodesRemoved(mapping) | GraphChange::NodesReordered(mapping) => {
+                self.bodies
+                    .retain_mut(|body| match mapping.get(*body as usize) {
+                        Some(&u32::MAX) => false,
+                        Some(new) => {
+                            *body = *new;
+                            true
+                        }
+                        None => true,
+                    });
+                let kept = mapping.iter().filter(|m| **m != u32::MAX).count();
+                self.body_count = kept + self.body_count.saturating_sub(mapping.len());
+            }
+            GraphChange::EdgeInserted(..) | GraphChange::EdgeRemoved(..) => (),
+        }
+    }
+}
+
+fn rigid_bodies_positions(rigid_bodies: &[RigidBody2D]) -> Arc<[Vec2]> {
+    rigid_bodies.iter().map(|rb| rb.position).collect()
+}
+
+/// Positions of `new_nodes` nodes added after the nodes at `positions`, next to their already
+/// placed neighbors in `edges`. A node without placed neighbors starts a new component at a
+/// random position.
+fn seed_positions(
+    positions: &[Vec2],
+    new_nodes: usize,
+    edges: &[(u32, u32)],
+    spacing: f32,
+    rng: &mut StdRng,
+) -> Vec<Vec2> {
+    let first_new = positions.len();
+    let node_count = first_new + new_nodes;
+    let mut neighbors: Vec<Vec<usize>> = vec![vec![]; new_nodes];
+    for &(a, b) in edges {
+        let (a, b) = (a as usize, b as usize);
+        if a == b || a >= node_count || b >= node_count {
+            continue;
+        }
+        for (node, neighbor) in [(a, b), (b, a)] {
+            if node >= first_new {
+                neighbors[node - first_new].push(neighbor);
+            }
+        }
+    }
+
+    let mut placed: Vec<Option<Vec2>> = positions.iter().copied().map(Some).collect();
+    placed.resize(node_count, None);
+
+    // Place breadth first from the existing nodes so every node is placed next to a neighbor
+    let mut queue: VecDeque<usize> = (first_new..node_count)
+        .filter(|i| neighbors[i - first_new].iter().any(|n| *n < first_new))
+        .collect();
+    let mut next_component = first_new;
+    loop {
+        while let Some(node) = queue.pop_front() {
+            if placed[node].is_some() {
+                continue;
+            }
+            let placed_neighbors: Vec<Vec2> = neighbors[node - first_new]
+                .iter()
+                .filter_map(|n| placed[*n])
+                .collect();
+            let position = if placed_neighbors.is_empty() {
+                let range = -RANDOM_PLACEMENT..RANDOM_PLACEMENT;
+                Vec2::new(rng.gen_range(range.clone()), rng.gen_range(range))
+            } else {
+                let center = placed_neighbors.iter().sum::<Vec2>() / placed_neighbors.len() as f32;
+                center + Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)) * spacing
+            };
+            placed[node] = Some(position);
+            queue.extend(
+                neighbors[node - first_new]
+                    .iter()
+                    .filter(|n| placed[**n].is_none()),
+            );
+        }
+
+        // Start a new component at a random position
+        while next_component < node_count && placed[next_component].is_some() {
+            next_component += 1;
+        }
+        if next_component == node_count {
+            break;
+        }
+        queue.push_back(next_component);
+    }
+
+    placed.drain(first_new..).flatten().collect()
+}
+
+/// Counts the steps per second for `SimulatorMetrics`
+#[derive(Debug)]
+struct MetricsTracker {
+    metrics: SimulatorMetrics,
+    first_step: Option<Instant>,
+    second_start: Instant,
+    steps_this_second: u32,
+}
+
+impl MetricsTracker {
+    fn new() -> Self {
+        Self {
+            metrics: SimulatorMetrics::default(),
+            first_step: None,
+            second_start: Instant::now(),
+            steps_this_second: 0,
+        }
+    }
+
+    fn step(&mut self) {
+        self.first_step.get_or_insert_with(Instant::now);
+        self.metrics.steps += 1;
+        self.steps_this_second += 1;
+        let elapsed = self.second_start.elapsed();
+        if elapsed >= Duration::from_secs(1) {
+            self.metrics.steps_per_second = self.steps_this_second as f32 / elapsed.as_secs_f32();
+            self.second_start = Instant::now();
+            self.steps_this_second = 0;
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Simulator {
     pub rigid_bodies: Arc<RwLock<Vec<RigidBody2D>>>,
     pub springs: Arc<RwLock<Vec<Spring>>>,
-    repel: bool,
-    spring: bool,
-    gravity: bool,
-    spring_stiffness: f32,
-    spring_neutral_length: f32,
-    delta_time: f32,
-    gravity_force: f32,
-    repel_force_const: f32,
-    damping: f32,
-    quadtree_theta: f32,
-    freeze_thresh: f32,
-    max_threads: u32,
+    edge_kinds: Vec<EdgeKind>,
+    default_edge_kind: Arc<RwLock<EdgeKind>>,
+    mass_mode: MassMode,
+    /// PageRank of every node with `GravityMode::PageRank`, updated by `notify`
+    gravity_rank: Arc<Mutex<Option<DynamicPageRank>>>,
+    gravity_wells: Arc<RwLock<Vec<GravityWell>>>,
+    gravity_well_assignment: Arc<RwLock<Vec<Option<usize>>>>,
+    obstacles: Arc<RwLock<Vec<Obstacle>>>,
+    rng: Arc<Mutex<StdRng>>,
+    parameters: Arc<RwLock<SimulationParameters>>,
+    warm_restart_steps: u32,
+    reheat_steps_left: Arc<RwLock<u32>>,
+    spatial_sort_interval: Option<u32>,
+    steps_since_sort: Arc<RwLock<u32>>,
+    component_scheduling: bool,
+    /// Members of every connected component, cleared by `notify`
+    components: Arc<Mutex<Option<Components>>>,
+    thread_pool: Arc<ThreadPool>,
     simulation_thread_lock: Arc<RwLock<bool>>,
+    /// Signalled on `resume`, see `wait_until_running`
+    running: Arc<(Mutex<bool>, Condvar)>,
+    convergence_epsilon: Option<f32>,
+    max_step_displacement: Option<f32>,
+    recenter: bool,
+    angular_damping: f32,
+    /// Start temperature and cooling factor of `SimulatorBuilder::annealing`
+    annealing: Option<(f32, f32)>,
+    temperature: Arc<RwLock<f32>>,
+    /// Factor of `delta_time` for `SimulatorBuilder::adaptive_delta_time`
+    delta_time_scale: Arc<RwLock<f32>>,
+    converged_senders: Arc<Mutex<Vec<Sender<()>>>>,
+    change_senders: Arc<Mutex<Vec<Sender<GraphChange>>>>,
+    step_stats: Arc<RwLock<StepStats>>,
+    net_forces: Arc<RwLock<Vec<Vec2>>>,
+    metrics: Arc<Mutex<MetricsTracker>>,
+    /// Replaced after every step, see `positions_snapshot`
+    positions: Arc<RwLock<Arc<[Vec2]>>>,
+    node_order: Arc<RwLock<NodeOrder>>,
+    step_callbacks: Arc<RwLock<StepCallbacks>>,
+    /// Tree of the last step, rebuilt every step to avoid allocating a new tree, see
+    /// `QuadTree::rebuild`. Also searched by `find_closest_node_index`.
+    quadtree_buffer: Arc<Mutex<QuadTree>>,
+    /// If `quadtree_buffer` was built in the last step. Steps without repulsion and gravity or
+    /// with component scheduling don't build it.
+    quadtree_current: Arc<AtomicBool>,
+    /// Builder this simulator was built with, without its node specific settings
+    settings: Arc<SimulatorBuilder>,
 }
 
 impl Simulator {
@@ -41,6 +405,27 @@ impl Simulator {
         SimulatorBuilder::default()
     }
 
+    /// Resumes a simulation from a snapshot using the default builder settings
+    pub fn from_snapshot(snapshot: SimulatorSnapshot) -> Self {
+        SimulatorBuilder::default().build_from_snapshot(snapshot)
+    }
+
+    /// Captures the current state of the simulation
+    pub fn snapshot(&self) -> SimulatorSnapshot {
+        let _lock = self.simulation_thread_lock.write().unwrap();
+        SimulatorSnapshot {
+            rigid_bodies: self.rigid_bodies.read().unwrap().clone(),
+            springs: self.springs.read().unwrap().clone(),
+            edge_kinds: self.edge_kinds.clone(),
+            parameters: self.parameters(),
+        }
+    }
+
+    /// Edge kinds referenced by `Spring::kind`
+    pub fn edge_kinds(&self) -> &[EdgeKind] {
+        &self.edge_kinds
+    }
+
     pub fn average_node_position(&self) -> Vec2 {
         let rb_guard = self.rigid_bodies.read().unwrap();
 
@@ -52,6 +437,149 @@ impl Simulator {
         avg / rb_guard.len() as f32
     }
 
+    /// Smallest box that contains all nodes.
+    ///
+    /// Returns `None` if the graph has no nodes.
+    pub fn bounding_box(&self) -> Option<BoundingBox2D> {
+        let rb_guard = self.rigid_bodies.read().unwrap();
+        if rb_guard.is_empty() {
+            return None;
+        }
+
+        let mut min = Vec2::INFINITY;
+        let mut max = Vec2::NEG_INFINITY;
+        for rb in rb_guard.iter() {
+            min = min.min(rb.position);
+            max = max.max(rb.position);
+        }
+        let size = max - min;
+        Some(BoundingBox2D::new(min + size / 2.0, size.x, size.y))
+    }
+
+    /// Average position of the nodes at `indices`.
+    ///
+    /// Returns `None` if `indices` is empty.
+    pub fn centroid_of(&self, indices: &[u32]) -> Option<Vec2> {
+        self.weighted_centroid_of(indices, |_| 1.0)
+    }
+
+    /// Center of mass of the nodes at `indices`.
+    ///
+    /// Returns `None` if `indices` is empty.
+    pub fn center_of_mass_of(&self, indices: &[u32]) -> Option<Vec2> {
+        self.weighted_centroid_of(indices, |rb| rb.mass)
+    }
+
+    /// Average position of the nodes at `indices`, weighted by `weight_fn`.
+    ///
+    /// Returns `None` if `indices` is empty or the weights sum up to zero.
+    pub fn weighted_centroid_of<F>(&self, indices: &[u32], weight_fn: F) -> Option<Vec2>
+    where
+        F: Fn(&RigidBody2D) -> f32,
+    {
+        let rb_read = self.rigid_bodies.read().unwrap();
+
+        let mut sum = Vec2::ZERO;
+        let mut total_weight = 0.0;
+        for rb in indices.iter().map(|i| &rb_read[*i as usize]) {
+            let weight = weight_fn(rb);
+            sum += rb.position * weight;
+            total_weight += weight;
+        }
+
+        if total_weight == 0.0 {
+            None
+        } else {
+            Some(sum / total_weight)
+        }
+    }
+
+    /// Current position of every node in the order of `node_indices` of the graph the simulator
+    /// was built from, followed by the inserted nodes.
+    ///
+    /// The rigid bodies themselves may be in another order, see
+    /// `SimulatorBuilder::spatial_sort_interval`.
+    pub fn positions(&self) -> Vec<Vec2> {
+        let rb_read = self.rigid_bodies.read().unwrap();
+        let node_order = self.node_order.read().unwrap();
+        node_order
+            .bodies
+            .iter()
+            .map(|body| rb_read[*body as usize].position)
+            .collect()
+    }
+
+    /// Position of every rigid body, by the index of its body
+    pub(crate) fn body_positions(&self) -> Vec<Vec2> {
+        let rb_read = self.rigid_bodies.read().unwrap();
+        rb_read.iter().map(|rb| rb.position).collect()
+    }
+
+    /// Every edge as a pair of indices of `positions`. A chain of rope springs counts as the one
+    /// edge it replaced, see `SimulatorBuilder::rope_segment_length`.
+    pub fn edges(&self) -> Vec<(u32, u32)> {
+        let rb_read = self.rigid_bodies.read().unwrap();
+        let spring_read = self.springs.read().unwrap();
+        let node_order = self.node_order.read().unwrap();
+        let mut node_index = vec![u32::MAX; rb_read.len()];
+        for (node, body) in node_order.bodies.iter().enumerate() {
+            if let Some(index) = node_index.get_mut(*body as usize) {
+                *index = node as u32;
+            }
+        }
+
+        // Every rope particle sits between two springs of its chain
+        let mut rope_links: HashMap<usize, Vec<usize>> = HashMap::new();
+        for spring in spring_read.iter() {
+            for (a, b) in [(spring.rb1, spring.rb2), (spring.rb2, spring.rb1)] {
+                if rb_read[a].rope {
+                    rope_links.entry(a).or_default().push(b);
+                }
+            }
+        }
+
+        // Chains start at the source node of the replaced edge
+        let mut edges = vec![];
+        for spring in spring_read.iter().filter(|s| !rb_read[s.rb1].rope) {
+            let (mut previous, mut current) = (spring.rb1, spring.rb2);
+            while rb_read[current].rope {
+                let Some(next) = rope_links[&current].iter().find(|n| **n != previous) else {
+                    break;
+                };
+                (previous, current) = (current, *next);
+            }
+            let edge = (node_index[spring.rb1], node_index[current]);
+            if edge.0 != u32::MAX && edge.1 != u32::MAX {
+                edges.push(edge);
+            }
+        }
+        edges
+    }
+
+    /// Consumes the simulator and pairs every node weight of `graph` with its simulated position.
+    ///
+    /// `graph` has to be the graph the simulator was built from.
+    pub fn into_positioned_graph<T, E, D>(
+        self,
+        graph: &StableGraph<T, E, D, u32>,
+    ) -> StableGraph<(T, Vec2), E, D, u32>
+    where
+        T: Clone,
+        E: Clone,
+        D: petgraph::EdgeType,
+    {
+        let positions = self.positions();
+        let order: HashMap<NodeIndex, usize> = graph
+            .node_indices()
+            .enumerate()
+            .map(|(i, node)| (node, i))
+            .collect();
+        graph.map(
+            |i, weight| (weight.clone(), positions[order[&i]]),
+            |_, weight| weight.clone(),
+        )
+    }
+
     pub fn max_node_mass(&self) -> f32 {
         let graph_read_guard = self.rigid_bodies.read().unwrap();
         let mut max_m = 0.0;
@@ -66,147 +594,979 @@ impl Simulator {
 
         let mut rb = self.rigid_bodies.write().unwrap();
         rb.push(RigidBody2D::new(vec.xy(), 5.0));
+        self.notify(GraphChange::NodeInserted);
+    }
+
+    /// Adds the nodes and edges of `graph` to the running simulation.
+    ///
+    /// `node_matcher` maps a node of `graph` to an existing node of the simulation. Unmatched nodes
+    /// are added as new nodes and placed close to their already placed neighbors.
+    ///
+    /// Returns the simulation index of every node of `graph`, indexed by its `NodeIndex`.
+    pub fn merge_graph<T, E, D, F>(
+        &self,
+        graph: &StableGraph<T, E, D, u32>,
+        node_matcher: F,
+    ) -> Vec<u32>
+    where
+        D: petgraph::EdgeType,
+        F: Fn(NodeIndex, &T) -> Option<u32>,
+    {
+        let _lock = self.simulation_thread_lock.write().unwrap();
+        let mut rb_write = self.rigid_bodies.write().unwrap();
+        let mut spring_write = self.springs.write().unwrap();
+
+        let placed_before = rb_write.len();
+        let mut mapping: Vec<Option<u32>> = vec![None; graph.node_bound()];
+        for (i, weight) in graph.node_indices().map(|i| (i, &graph[i])) {
+            mapping[i.index()] = node_matcher(i, weight).filter(|m| (*m as usize) < rb_write.len());
+        }
+
+        // Place new nodes breadth first so they can be placed next to placed neighbors
+        let mut rng = self.rng.lock().unwrap();
+        let mut queue: VecDeque<NodeIndex> = graph
+            .node_indices()
+            .filter(|i| mapping[i.index()].is_some())
+            .collect();
+        let mut unplaced: Vec<NodeIndex> = graph
+            .node_indices()
+            .filter(|i| mapping[i.index()].is_none())
+            .collect();
+
+        loop {
+            while let Some(node) = queue.pop_front() {
+                for neighbor in graph.neighbors_undirected(node) {
+                    if mapping[neighbor.index()].is_some() {
+                        continue;
+                    }
+
+                    let placed_neighbors: Vec<Vec2> = graph
+                        .neighbors_undirected(neighbor)
+                        .filter_map(|n| mapping[n.index()])
+                        .map(|n| rb_write[n as usize].position)
+                        .collect();
+                    let center =
+                        placed_neighbors.iter().sum::<Vec2>() / placed_neighbors.len() as f32;
+                    let offset = Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0))
+                        * self.default_edge_kind.read().unwrap().spring_neutral_len;
+
+                    mapping[neighbor.index()] = Some(rb_write.len() as u32);
+                    rb_write.push(RigidBody2D::new(center + offset, self.mass_mode.mass(0, 0)));
+                    queue.push_back(neighbor);
+                }
+            }
+
+            // Start a new component at a random position
+            unplaced.retain(|i| mapping[i.index()].is_none());
+            let Some(node) = unplaced.pop() else {
+                break;
+            };
+            mapping[node.index()] = Some(rb_write.len() as u32);
+            let range = -RANDOM_PLACEMENT..RANDOM_PLACEMENT;
+            rb_write.push(RigidBody2D::new(
+                Vec2::new(rng.gen_range(range.clone()), rng.gen_range(range)),
+                self.mass_mode.mass(0, 0),
+            ));
+            queue.push_back(node);
+        }
+
+        for _ in placed_before..rb_write.len() {
+            self.notify(GraphChange::NodeInserted);
+        }
+
+        let default_edge_kind = self.default_edge_kind.read().unwrap();
+        for edge in graph.edge_references() {
+            let rb1 = mapping[edge.source().index()].unwrap() as usize;
+            let rb2 = mapping[edge.target().index()].unwrap() as usize;
+
+            let (source_mass, target_mass) = self.mass_mode.edge_mass_increment();
+            rb_write[rb1].mass += source_mass;
+            rb_write[rb2].mass += target_mass;
+
+            spring_write.push(Spring {
+                rb1,
+                rb2,
+                spring_stiffness: default_edge_kind.spring_stiffness,
+                spring_neutral_len: default_edge_kind.spring_neutral_len,
+                damping: default_edge_kind.damping,
+                kind: None,
+            });
+            self.notify(GraphChange::EdgeInserted(rb1, rb2));
+        }
+
+        mapping.into_iter().map(|m| m.unwrap_or(u32::MAX)).collect()
+    }
+
+    /// Applies all changes of `update` at once, see `GraphUpdate`. Edges with an unknown node are
+    /// skipped.
+    ///
+    /// Returns the new index of every node after adding `update.add_nodes`, `u32::MAX` for
+    /// removed nodes.
+    pub fn apply(&self, update: GraphUpdate) -> Vec<u32> {
+        let _lock = self.simulation_thread_lock.write().unwrap();
+        let mut rb_write = self.rigid_bodies.write().unwrap();
+        let mut spring_write = self.springs.write().unwrap();
+
+        let existing: Vec<Vec2> = rb_write.iter().map(|rb| rb.position).collect();
+        let positions = seed_positions(
+            &existing,
+            update.add_nodes,
+            &update.add_edges,
+            self.default_edge_kind.read().unwrap().spring_neutral_len,
+            &mut self.rng.lock().unwrap(),
+        );
+        for position in positions {
+            rb_write.push(RigidBody2D::new(position, self.mass_mode.mass(0, 0)));
+            self.notify(GraphChange::NodeInserted);
+        }
+
+        for (a, b) in update.add_edges {
+            self.push_spring(&mut rb_write, &mut spring_write, a, b, None);
+        }
+
+        let (source_mass, target_mass) = self.mass_mode.edge_mass_increment();
+        for (a, b) in update.remove_edges {
+            let (a, b) = (a as usize, b as usize);
+            let Some(index) = spring_write
+                .iter()
+                .position(|s| (s.rb1, s.rb2) == (a, b) || (s.rb1, s.rb2) == (b, a))
+            else {
+                continue;
+            };
+            let spring = spring_write.remove(index);
+            for (i, mass) in [(spring.rb1, source_mass), (spring.rb2, target_mass)] {
+                rb_write[i].mass -= mass;
+                rb_write[i].frozen = false;
+            }
+            self.notify(GraphChange::EdgeRemoved(spring.rb1, spring.rb2));
+        }
+
+        if update.remove_nodes.is_empty() {
+            return (0..rb_write.len() as u32).collect();
+        }
+        self.remove_nodes_locked(&mut rb_write, &mut spring_write, &update.remove_nodes)
+    }
+
+    /// Pauses the simulation. Embedders driving their own loop should check `is_running` before stepping.
+    pub fn pause(&self) {
+        *self.running.0.lock().unwrap() = false;
+    }
+
+    /// Resumes the simulation
+    pub fn resume(&self) {
+        *self.running.0.lock().unwrap() = true;
+        self.running.1.notify_all();
+    }
+
+    /// If the simulation is running. A new `Simulator` starts paused.
+    pub fn is_running(&self) -> bool {
+        *self.running.0.lock().unwrap()
+    }
+
+    /// Blocks while the simulation is paused, at most for `timeout`. Returns if it is running.
+    ///
+    /// Lets a simulation thread sleep instead of spinning while the simulation is paused.
+    pub fn wait_until_running(&self, timeout: Duration) -> bool {
+        let (running, resumed) = &*self.running;
+        let (running, _) = resumed
+            .wait_timeout_while(running.lock().unwrap(), timeout, |running| !*running)
+            .unwrap();
+        *running
+    }
+
+    /// Runs `n` simulation steps, regardless of whether the simulation is paused
+    pub fn step_n(&self, n: usize) {
+        for _ in 0..n {
+            self.simulation_step();
+        }
+    }
+
+    /// Runs as many simulation steps as fit into `budget`, regardless of whether the simulation is paused.
+    ///
+    /// Meant for embedders that can't spawn a simulation thread and step the simulation from their event loop.
+    /// At least one step is run. A step is only started if it is expected to finish within `budget`.
+    ///
+    /// Returns the number of steps that were run.
+    pub fn step_for(&self, budget: Duration) -> usize {
+        let start = Instant::now();
+        let mut steps = 0;
+        let mut longest_step = Duration::ZERO;
+
+        loop {
+            let step_start = Instant::now();
+            self.simulation_step();
+            steps += 1;
+            longest_step = longest_step.max(step_start.elapsed());
+
+            if start.elapsed() + longest_step > budget {
+                return steps;
+            }
+        }
     }
 
     pub fn simulation_step(&self) {
+        let info = self.locked_simulation_step();
+        for callback in self.step_callbacks.read().unwrap().0.iter() {
+            callback(&info);
+        }
+    }
+
+    /// Calls `callback` after every simulation step, e.g. to log progress, update a UI or pause the
+    /// simulation once a custom condition is met.
+    ///
+    /// The callbacks run on the simulation thread after the step has ended, so they may call any
+    /// method of the simulator except `on_step`.
+    pub fn on_step<F>(&self, callback: F)
+    where
+        F: Fn(&StepInfo) + Send + Sync + 'static,
+    {
+        self.step_callbacks
+            .write()
+            .unwrap()
+            .0
+            .push(Box::new(callback));
+    }
+
+    fn locked_simulation_step(&self) -> StepInfo {
         // Lock so actions can only be performed when sim step has ended
         let _lock = self.simulation_thread_lock.write().unwrap();
+        self.unlocked_simulation_step()
+    }
+
+    /// Simulation step for callers that already hold `simulation_thread_lock`
+    fn unlocked_simulation_step(&self) -> StepInfo {
+        let start = Instant::now();
+        let mut stats = StepStats::default();
+
+        let mut params = self.parameters();
+        let reheating = {
+            let mut reheat_steps_left = self.reheat_steps_left.write().unwrap();
+            let reheating = *reheat_steps_left > 0;
+            *reheat_steps_left = reheat_steps_left.saturating_sub(1);
+            reheating
+        };
 
         let f_vec = Arc::new(Mutex::new(vec![
             Vec2::ZERO;
             self.rigid_bodies.read().unwrap().len()
         ]));
 
-        self.calculate_forces(Arc::clone(&f_vec));
+        let quadtree_depth = self.calculate_forces(&params, Arc::clone(&f_vec), &mut stats);
+        self.net_forces
+            .write()
+            .unwrap()
+            .clone_from(&f_vec.lock().unwrap());
+
+        let integration_start = Instant::now();
+        if let Some(max_displacement) = self.max_step_displacement {
+            params.delta_time =
+                self.adapt_delta_time(&params, &f_vec.lock().unwrap(), max_displacement);
+        }
+        self.apply_node_force(&params, Arc::clone(&f_vec));
+        let max_displacement = self.annealing.map(|_| *self.temperature.read().unwrap());
+        self.update_node_position(&params, reheating, max_displacement);
+        if let Some((start, cooling)) = self.annealing {
+            let mut temperature = self.temperature.write().unwrap();
+            *temperature = (*temperature * cooling).max(start * MIN_TEMPERATURE_SCALE);
+        }
+        if self.recenter {
+            self.recenter();
+        }
+        if self.angular_damping > 0.0 {
+            self.damp_rotation();
+        }
+        stats.integration = integration_start.elapsed();
+
+        if let Some(interval) = self.spatial_sort_interval {
+            let mut steps_since_sort = self.steps_since_sort.write().unwrap();
+            *steps_since_sort += 1;
+            if *steps_since_sort >= interval {
+                *steps_since_sort = 0;
+                self.sort_spatially();
+            }
+        }
+
+        let positions = rigid_bodies_positions(&self.rigid_bodies.read().unwrap());
+        *self.positions.write().unwrap() = positions;
+
+        stats.total = start.elapsed();
+        *self.step_stats.write().unwrap() = stats;
+
+        let info = {
+            let mut tracker = self.metrics.lock().unwrap();
+            tracker.step();
+            let elapsed = tracker.first_step.map_or(Duration::ZERO, |t| t.elapsed());
+            let metrics = &mut tracker.metrics;
+            metrics.node_count = self.rigid_bodies.read().unwrap().len();
+            metrics.edge_count = self.springs.read().unwrap().len();
+            metrics.kinetic_energy = self.total_kinetic_energy();
+            metrics.quadtree_depth = quadtree_depth;
+            metrics.step_stats = stats;
+            metrics.delta_time = params.delta_time;
+            StepInfo {
+                iteration: metrics.steps,
+                kinetic_energy: metrics.kinetic_energy,
+                step_time: stats.total,
+                elapsed,
+            }
+        };
+
+        if reheating {
+            return info;
+        }
 
-        self.apply_node_force(Arc::clone(&f_vec));
-        self.update_node_position();
+        if let Some(epsilon) = self.convergence_epsilon {
+            if self.average_node_velocity() < epsilon {
+                self.pause();
+                self.converged_senders
+                    .lock()
+                    .unwrap()
+                    .retain(|sender| sender.send(()).is_ok());
+            }
+        }
+        info
     }
 
-    fn calculate_forces(&self, f_vec: Arc<Mutex<Vec<Vec2>>>) {
-        if self.repel || self.gravity {
-            let node_count = { self.rigid_bodies.read().unwrap().len() };
-            let thread_count = usize::min(node_count, self.max_threads as usize);
+    /// Time step for `SimulatorBuilder::adaptive_delta_time`. It is halved until no node moves
+    /// further than `max_displacement` with the forces of this step and grows again while all
+    /// nodes move less than half of it.
+    fn adapt_delta_time(
+        &self,
+        params: &SimulationParameters,
+        forces: &[Vec2],
+        max_displacement: f32,
+    ) -> f32 {
+        let rb_read = self.rigid_bodies.read().unwrap();
+        let mut scale = self.delta_time_scale.write().unwrap();
+        let displacement = |delta_time: f32| {
+            rb_read
+                .iter()
+                .zip(forces)
+                .filter(|(rb, _)| !(rb.fixed || rb.frozen))
+                .map(|(rb, force)| {
+                    let velocity = (rb.velocity + *force / rb.mass * delta_time) * params.damping;
+                    velocity.length() * delta_time
+                })
+                .fold(0.0, f32::max)
+        };
+
+        while *scale > MIN_DELTA_TIME_SCALE
+            && displacement(params.delta_time * *scale) > max_displacement
+        {
+            *scale = (*scale * 0.5).max(MIN_DELTA_TIME_SCALE);
+        }
+        let delta_time = params.delta_time * *scale;
+        if displacement(delta_time) < max_displacement / 2.0 {
+            *scale = (*scale * DELTA_TIME_GROWTH).min(MAX_DELTA_TIME_SCALE);
+        }
+        delta_time
+    }
 
-            let mut handles = Vec::with_capacity(thread_count);
+    /// Removes the drift of the whole layout for `SimulatorBuilder::recenter`
+    fn recenter(&self) {
+        let mut rb_write = self.rigid_bodies.write().unwrap();
+        if rb_write.iter().any(|rb| rb.fixed) {
+            return;
+        }
 
-            let nodes_per_thread = node_count / thread_count;
+        let (momentum, mass) = rb_write
+            .iter()
+            .filter(|rb| !rb.frozen)
+            .fold((Vec2::ZERO, 0.0), |(momentum, mass), rb| {
+                (momentum + rb.velocity * rb.mass, mass + rb.mass)
+            });
+        let (moment, total_mass) = rb_write
+            .iter()
+            .fold((Vec2::ZERO, 0.0), |(moment, mass), rb| {
+                (moment + rb.position * rb.mass, mass + rb.mass)
+            });
+        if mass <= 0.0 || total_mass <= 0.0 {
+            return;
+        }
 
-            let quadtree = Arc::new(build_quadtree(Arc::clone(&self.rigid_bodies)));
-            for thread in 0..thread_count {
-                let mut extra = 0;
+        let drift = momentum / mass;
+        let center = moment / total_mass;
+        for rb in rb_write.iter_mut() {
+            if !rb.frozen {
+                rb.velocity -= drift;
+            }
+            rb.position -= center;
+        }
+    }
 
-                if thread == thread_count - 1 {
-                    extra = node_count % thread_count;
-                }
+    /// Removes the `SimulatorBuilder::angular_damping` fraction of the rotation of the moving
+    /// nodes around their center of mass
+    fn damp_rotation(&self) {
+        let mut rb_write = self.rigid_bodies.write().unwrap();
+        let moving = |rb: &&mut RigidBody2D| !(rb.fixed || rb.frozen);
+
+        let (moment, mass) = rb_write
+            .iter_mut()
+            .filter(moving)
+            .fold((Vec2::ZERO, 0.0), |(moment, mass), rb| {
+                (moment + rb.position * rb.mass, mass + rb.mass)
+            });
+        if mass <= 0.0 {
+            return;
+        }
+        let center = moment / mass;
+
+        // Angular velocity of the rigid rotation with the same angular momentum
+        let (angular_momentum, inertia) = rb_write.iter_mut().filter(moving).fold(
+            (0.0, 0.0),
+            |(angular_momentum, inertia), rb| {
+                let offset = rb.position - center;
+                (
+                    angular_momentum + rb.mass * offset.perp_dot(rb.velocity),
+                    inertia + rb.mass * offset.length_squared(),
+                )
+            },
+        );
+        if inertia <= 0.0 {
+            return;
+        }
+        let angular_velocity = angular_momentum / inertia * self.angular_damping;
 
-                let handle = self.spawn_physics_thread(
-                    thread * nodes_per_thread,
-                    (thread + 1) * nodes_per_thread + extra,
-                    node_count,
-                    Arc::clone(&f_vec),
-                    Arc::clone(&self.rigid_bodies),
-                    Arc::clone(&quadtree),
-                );
+        for rb in rb_write.iter_mut().filter(moving) {
+            rb.velocity -= (rb.position - center).perp() * angular_velocity;
+        }
+    }
+
+    /// Reorders the nodes along a Z-order curve so nodes that are close in space are close in
+    /// memory, the caller holds the simulation thread lock
+    fn sort_spatially(&self) {
+        let mut rb_write = self.rigid_bodies.write().unwrap();
+        let boundary = quadtree::enclosing_boundary(&rb_write);
+        let mut order: Vec<usize> = (0..rb_write.len()).collect();
+        order.sort_by_cached_key(|i| quadtree::morton_code(rb_write[*i].position, &boundary));
+
+        let mut mapping = vec![0; order.len()];
+        for (new, old) in order.iter().enumerate() {
+            mapping[*old] = new as u32;
+        }
+        if mapping.iter().enumerate().all(|(i, m)| *m as usize == i) {
+            return;
+        }
+
+        remap(&mut rb_write, &mapping);
+        for spring in self.springs.write().unwrap().iter_mut() {
+            spring.rb1 = mapping[spring.rb1] as usize;
+            spring.rb2 = mapping[spring.rb2] as usize;
+        }
+        remap(&mut self.gravity_well_assignment.write().unwrap(), &mapping);
+        remap(&mut self.net_forces.write().unwrap(), &mapping);
+        drop(rb_write);
+        self.notify(GraphChange::NodesReordered(mapping));
+    }
+
+    /// Phase timings of the last simulation step
+    pub fn step_stats(&self) -> StepStats {
+        *self.step_stats.read().unwrap()
+    }
+
+    /// Node positions after the last simulation step, by node index.
+    ///
+    /// The snapshot is immutable and shared, so it can be read by other threads or renderers without
+    /// holding any lock while the simulation continues. Nodes inserted or removed since the last
+    /// step show up after the next step.
+    pub fn positions_snapshot(&self) -> Arc<[Vec2]> {
+        Arc::clone(&self.positions.read().unwrap())
+    }
 
-                handles.push(handle);
+    /// Telemetry of the last simulation step
+    pub fn metrics(&self) -> SimulatorMetrics {
+        self.metrics.lock().unwrap().metrics
+    }
+
+    /// Force on every node in the last simulation step, empty before the first step.
+    ///
+    /// Nodes added since the last step have no entry.
+    pub fn net_forces(&self) -> Vec<Vec2> {
+        self.net_forces.read().unwrap().clone()
+    }
+
+    /// Sum of the kinetic energy `0.5 * m * v^2` of all nodes
+    pub fn total_kinetic_energy(&self) -> f32 {
+        let rb_read = self.rigid_bodies.read().unwrap();
+        rb_read
+            .iter()
+            .map(|rb| 0.5 * rb.mass * rb.velocity.length_squared())
+            .sum()
+    }
+
+    /// Average velocity of all nodes
+    pub fn average_node_velocity(&self) -> f32 {
+        let rb_read = self.rigid_bodies.read().unwrap();
+        if rb_read.is_empty() {
+            return 0.0;
+        }
+        rb_read.iter().map(|rb| rb.total_velocity()).sum::<f32>() / rb_read.len() as f32
+    }
+
+    /// Returns a receiver that gets a message every time the simulation pauses because it converged.
+    ///
+    /// See `SimulatorBuilder::stop_when_converged`
+    pub fn subscribe_converged(&self) -> Receiver<()> {
+        let (sender, receiver) = mpsc::channel();
+        self.converged_senders.lock().unwrap().push(sender);
+        receiver
+    }
+
+    /// Receives every change to the nodes and springs made after subscribing, e.g. to keep an
+    /// `analysis::DynamicMetric` up to date
+    pub fn subscribe_changes(&self) -> Receiver<GraphChange> {
+        let (sender, receiver) = mpsc::channel();
+        self.change_senders.lock().unwrap().push(sender);
+        receiver
+    }
+
+    fn notify(&self, change: GraphChange) {
+        *self.components.lock().unwrap() = None;
+        if let GraphChange::NodesRemoved(_) | GraphChange::NodesReordered(_) = change {
+            // The indices of the tree are out of date until the next step
+            self.quadtree_buffer.lock().unwrap().clear();
+        }
+        if let Some(rank) = self.gravity_rank.lock().unwrap().as_mut() {
+            rank.apply(&change);
+        }
+        self.node_order.write().unwrap().apply(&change);
+        let mut senders = self.change_senders.lock().unwrap();
+        if senders.is_empty() {
+            return;
+        }
+        senders.retain(|sender| sender.send(change.clone()).is_ok());
+    }
+
+    /// Replaces the gravity wells and which well every node is pulled to, see
+    /// `SimulatorBuilder::gravity_well`
+    pub fn set_gravity_wells(&self, wells: Vec<GravityWell>, assignment: Vec<Option<usize>>) {
+        let _lock = self.simulation_thread_lock.write().unwrap();
+        *self.gravity_wells.write().unwrap() = wells;
+        *self.gravity_well_assignment.write().unwrap() = assignment;
+        for rb in self.rigid_bodies.write().unwrap().iter_mut() {
+            rb.frozen = false;
+        }
+    }
+
+    pub fn gravity_wells(&self) -> Vec<GravityWell> {
+        self.gravity_wells.read().unwrap().clone()
+    }
+
+    /// Replaces the obstacles, see `SimulatorBuilder::obstacle`
+    pub fn set_obstacles(&self, obstacles: Vec<Obstacle>) {
+        let _lock = self.simulation_thread_lock.write().unwrap();
+        *self.obstacles.write().unwrap() = obstacles;
+        for rb in self.rigid_bodies.write().unwrap().iter_mut() {
+            rb.frozen = false;
+        }
+    }
+
+    pub fn obstacles(&self) -> Vec<Obstacle> {
+        self.obstacles.read().unwrap().clone()
+    }
+
+    /// Stiffness of the springs without an edge kind
+    pub fn spring_stiffness(&self) -> f32 {
+        self.default_edge_kind.read().unwrap().spring_stiffness
+    }
+
+    /// Sets the stiffness of all springs without an edge kind, including springs added later
+    pub fn set_spring_stiffness(&self, spring_stiffness: f32) {
+        self.default_edge_kind.write().unwrap().spring_stiffness = spring_stiffness;
+        for spring in self.springs.write().unwrap().iter_mut() {
+            if spring.kind.is_none() {
+                spring.spring_stiffness = spring_stiffness;
             }
+        }
+    }
+
+    /// Builder with the settings this simulator was built with and its current parameters, to
+    /// simulate another graph the same way.
+    ///
+    /// Settings of single nodes, the initial positions and the gravity well assignment, are left
+    /// at their defaults.
+    pub fn settings(&self) -> SimulatorBuilder {
+        let parameters = self.parameters();
+        let default_edge_kind = self.default_edge_kind.read().unwrap().clone();
+        SimulatorBuilder {
+            repel: parameters.repel,
+            spring: parameters.spring,
+            gravity: parameters.gravity,
+            repel_force_const: parameters.repel_force_const,
+            softening: parameters.softening,
+            cutoff_radius: parameters.cutoff_radius,
+            gravity_force: parameters.gravity_force,
+            delta_time: parameters.delta_time,
+            damping: parameters.damping,
+            quadtree_theta: parameters.quadtree_theta,
+            freeze_thresh: parameters.freeze_thresh,
+            spring_stiffness: default_edge_kind.spring_stiffness,
+            spring_neutral_length: default_edge_kind.spring_neutral_len,
+            spring_damping: default_edge_kind.damping,
+            edge_kinds: self.edge_kinds.clone(),
+            gravity_wells: self.gravity_wells(),
+            obstacles: self.obstacles(),
+            ..(*self.settings).clone()
+        }
+    }
+
+    /// Current simulation parameters
+    pub fn parameters(&self) -> SimulationParameters {
+        *self.parameters.read().unwrap()
+    }
 
-            if self.spring {
+    /// Replaces the simulation parameters of the running simulation.
+    ///
+    /// Unless disabled with `SimulatorBuilder::warm_restart`, all frozen nodes are released and
+    /// freezing is suspended for a while, so the layout can adapt to the new forces.
+    pub fn set_parameters(&self, parameters: SimulationParameters) {
+        let _lock = self.simulation_thread_lock.write().unwrap();
+        *self.parameters.write().unwrap() = parameters;
+
+        if self.warm_restart_steps > 0 {
+            for rb in self.rigid_bodies.write().unwrap().iter_mut() {
+                rb.frozen = false;
+            }
+            *self.reheat_steps_left.write().unwrap() = self.warm_restart_steps;
+            if let Some((start, _)) = self.annealing {
+                *self.temperature.write().unwrap() = start;
+            }
+        }
+    }
+
+    /// Adds the forces on every node to `f_vec` and returns the depth of the quadtree, `0` if none
+    /// was built
+    fn calculate_forces(
+        &self,
+        params: &SimulationParameters,
+        f_vec: Arc<Mutex<Vec<Vec2>>>,
+        stats: &mut StepStats,
+    ) -> u32 {
+        let mut quadtree_depth = 0;
+        self.quadtree_current.store(false, Ordering::Relaxed);
+        if params.repel || params.gravity {
+            let quadtree_start = Instant::now();
+            let gravity_weights = self.gravity_weights();
+            let node_forces: Vec<Vec2> = if self.component_scheduling {
+                let rb_vec = self.rigid_bodies.read().unwrap();
+                let (node_forces, depth) =
+                    self.component_node_forces(params, &rb_vec, &gravity_weights, stats);
+                quadtree_depth = depth;
+                node_forces
+            } else {
+                let rb_vec = self.rigid_bodies.read().unwrap();
+                let quadtree_buffer: &mut QuadTree = &mut self.quadtree_buffer.lock().unwrap();
+                self.thread_pool.install(|| {
+                    // Built once per step and borrowed by all workers. Large trees are faster to
+                    // build in parallel than to rebuild without allocating.
+                    if QuadTree::builds_in_parallel(rb_vec.len()) {
+                        *quadtree_buffer = QuadTree::from_rigid_bodies_par(&rb_vec);
+                    } else {
+                        quadtree_buffer.rebuild(&rb_vec);
+                    }
+                    self.quadtree_current.store(true, Ordering::Relaxed);
+                    let quadtree: &QuadTree = quadtree_buffer;
+                    stats.quadtree = quadtree_start.elapsed();
+                    quadtree_depth = quadtree.depth();
+
+                    let node_forces_start = Instant::now();
+                    let node_forces = rb_vec
+                        .par_iter()
+                        .enumerate()
+                        .map(|(i, rb)| {
+                            let gravity_weight = gravity_weight(&gravity_weights, i);
+                            Self::compute_node_force(params, rb, gravity_weight, quadtree)
+                        })
+                        .collect();
+                    stats.node_forces = node_forces_start.elapsed();
+                    node_forces
+                })
+            };
+
+            if params.spring {
+                let spring_forces_start = Instant::now();
                 self.compute_spring_forces_edges(Arc::clone(&f_vec));
+                stats.spring_forces = spring_forces_start.elapsed();
             }
 
-            for handle in handles {
-                handle.join().unwrap();
+            let mut force_list = f_vec.lock().unwrap();
+            for (force, node_force) in force_list.iter_mut().zip(node_forces) {
+                *force += node_force;
             }
         }
+
+        let gravity_wells_start = Instant::now();
+        self.compute_gravity_well_forces(&f_vec);
+        self.compute_obstacle_forces(&f_vec);
+        stats.node_forces += gravity_wells_start.elapsed();
+        quadtree_depth
     }
 
-    fn spawn_physics_thread(
+    /// Node forces with `SimulatorBuilder::component_scheduling` and the deepest quadtree. Every
+    /// component gets its own worker and quadtree, the other components only push it as a whole.
+    fn component_node_forces(
         &self,
-        start_index: usize,
-        end_index: usize,
-        node_count: usize,
-        force_vec_out: Arc<Mutex<Vec<Vec2>>>,
-        rb_vec: Arc<RwLock<Vec<RigidBody2D>>>,
-        quadtree: Arc<QuadTree>,
-    ) -> JoinHandle<()> {
-        let repel_force_const = self.repel_force_const;
-        let repel_force = self.repel;
-        let gravity = self.gravity;
-        let gravity_force = self.gravity_force;
-        let theta = self.quadtree_theta;
-
-        let handle = thread::spawn(move || {
-            let mut force_vec: Vec<Vec2> = vec![Vec2::ZERO; node_count];
-
-            #[allow(clippy::needless_range_loop)]
-            for i in start_index..end_index {
-                let rb = &rb_vec.read().unwrap()[i];
-                if rb.fixed {
-                    continue;
-                }
-                if repel_force {
-                    // Get node approximation from Quadtree
-                    let node_approximations = quadtree.stack(&rb.position, theta);
-
-                    // Calculate Repel Force
-                    for node_approximation in node_approximations {
-                        let node_approximation_particle = RigidBody2D::new(
-                            node_approximation.position(),
-                            node_approximation.mass(),
-                        );
-                        let repel_force =
-                            Self::repel_force(repel_force_const, rb, &node_approximation_particle);
-
-                        force_vec[i] += repel_force;
+        params: &SimulationParameters,
+        rb_vec: &[RigidBody2D],
+        gravity_weights: &Option<Vec<f32>>,
+        stats: &mut StepStats,
+    ) -> (Vec<Vec2>, u32) {
+        let quadtree_start = Instant::now();
+        let components = self.components(rb_vec.len());
+        let aggregates: Vec<RigidBody2D> = components
+            .iter()
+            .map(|members| aggregate_body(rb_vec, members))
+            .collect();
+        let component_tree = QuadTree::from_rigid_bodies(&aggregates);
+        stats.quadtree = quadtree_start.elapsed();
+
+        let node_forces_start = Instant::now();
+        let component_forces: Vec<(Vec<Vec2>, u32)> = self.thread_pool.install(|| {
+            components
+                .par_iter()
+                .zip(aggregates.par_iter())
+                .map(|(members, own_aggregate)| {
+                    // Converged components are skipped as a whole
+                    if members
+                        .iter()
+                        .all(|i| rb_vec[*i].fixed || rb_vec[*i].frozen)
+                    {
+                        return (vec![Vec2::ZERO; members.len()], 0);
                     }
-                }
+                    let bodies: Vec<RigidBody2D> =
+                        members.iter().map(|i| rb_vec[*i].clone()).collect();
+                    let quadtree = QuadTree::from_rigid_bodies(&bodies);
+                    let forces = bodies
+                        .iter()
+                        .zip(members)
+                        .map(|(rb, i)| {
+                            let gravity_weight = gravity_weight(gravity_weights, *i);
+                            let mut force =
+                                Self::compute_node_force(params, rb, gravity_weight, &quadtree);
+                            if params.repel && !(rb.fixed || rb.frozen) {
+                                force += Self::component_repel_force(
+                                    params,
+                                    rb,
+                                    &component_tree,
+                                    own_aggregate,
+                                );
+                            }
+                            force
+                        })
+                        .collect();
+                    (forces, quadtree.depth())
+                })
+                .collect()
+        });
 
-                //Calculate Gravity Force
-                if gravity {
-                    let gravity_force = Self::compute_center_gravity(gravity_force, rb);
-                    force_vec[i] += gravity_force;
-                }
+        let mut node_forces = vec![Vec2::ZERO; rb_vec.len()];
+        let mut depth = component_tree.depth();
+        for (members, (forces, component_depth)) in components.iter().zip(component_forces) {
+            for (i, force) in members.iter().zip(forces) {
+                node_forces[*i] = force;
+            }
+            depth = depth.max(component_depth);
+        }
+        stats.node_forces = node_forces_start.elapsed();
+        (node_forces, depth)
+    }
+
+    /// Repel force of all other components on `rb`, the own component is pushing in the tree of
+    /// all components as well and is taken out again
+    fn component_repel_force(
+        params: &SimulationParameters,
+        rb: &RigidBody2D,
+        component_tree: &QuadTree,
+        own_aggregate: &RigidBody2D,
+    ) -> Vec2 {
+        let mut force = Vec2::ZERO;
+        let cutoff = params.cutoff_radius.unwrap_or(f32::INFINITY);
+        component_tree.for_each_approx_within(
+            &rb.position,
+            params.quadtree_theta,
+            cutoff,
+            |node| {
+                let particle = RigidBody2D::with_charge(
+                    node.center_of_mass(),
+                    node.total_mass(),
+                    node.charge() / node.total_mass(),
+                );
+                force +=
+                    Self::repel_force(params.repel_force_const, params.softening, rb, &particle);
+            },
+        );
+        if own_aggregate.position.distance(rb.position) <= cutoff {
+            force -= Self::repel_force(
+                params.repel_force_const,
+                params.softening,
+                rb,
+                own_aggregate,
+            );
+        }
+        force
+    }
+
+    /// Members of every connected component, recomputed after the graph changed
+    fn components(&self, node_count: usize) -> Components {
+        let mut components = self.components.lock().unwrap();
+        if let Some(components) = components.as_ref() {
+            if components.iter().map(Vec::len).sum::<usize>() == node_count {
+                return Arc::clone(components);
             }
+        }
+
+        let edges: Vec<(usize, usize)> = self
+            .springs
+            .read()
+            .unwrap()
+            .iter()
+            .map(|s| (s.rb1, s.rb2))
+            .collect();
+        let mut members: Vec<Vec<usize>> = vec![];
+        for (i, component) in analysis::components(node_count, &edges)
+            .into_iter()
+            .enumerate()
+        {
+            if component as usize == members.len() {
+                members.push(vec![]);
+            }
+            members[component as usize].push(i);
+        }
+        Arc::clone(components.insert(Arc::new(members)))
+    }
 
-            {
-                let mut force_list = force_vec_out.lock().unwrap();
+    /// Factor of the center gravity of every node with `GravityMode::PageRank`, the PageRank
+    /// scaled to an average of `1` so the total pull matches `GravityMode::Uniform`
+    fn gravity_weights(&self) -> Option<Vec<f32>> {
+        let mut rank = self.gravity_rank.lock().unwrap();
+        let mut weights = rank.as_mut()?.values();
+        let mean = weights.iter().sum::<f32>() / weights.len().max(1) as f32;
+        if mean > 0.0 {
+            weights.iter_mut().for_each(|w| *w /= mean);
+        }
+        Some(weights)
+    }
 
-                for (i, force) in force_vec.into_iter().enumerate() {
-                    force_list[i] += force;
-                }
+    fn compute_gravity_well_forces(&self, f_vec: &Mutex<Vec<Vec2>>) {
+        let wells = self.gravity_wells.read().unwrap();
+        if wells.is_empty() {
+            return;
+        }
+        let assignment = self.gravity_well_assignment.read().unwrap();
+        let rb_vec = self.rigid_bodies.read().unwrap();
+        let mut force_list = f_vec.lock().unwrap();
+
+        for (i, well) in assignment.iter().enumerate().take(rb_vec.len()) {
+            let (rb, Some(well)) = (&rb_vec[i], well.and_then(|w| wells.get(w))) else {
+                continue;
+            };
+            if rb.fixed || rb.frozen {
+                continue;
             }
-        });
+            force_list[i] += (well.position - rb.position) * rb.mass * well.strength;
+        }
+    }
 
-        handle
+    fn compute_obstacle_forces(&self, f_vec: &Mutex<Vec<Vec2>>) {
+        let obstacles = self.obstacles.read().unwrap();
+        if obstacles.is_empty() {
+            return;
+        }
+        let rb_vec = self.rigid_bodies.read().unwrap();
+        let mut force_list = f_vec.lock().unwrap();
+
+        for (rb, force) in rb_vec.iter().zip(force_list.iter_mut()) {
+            if rb.fixed || rb.frozen {
+                continue;
+            }
+            for obstacle in obstacles.iter() {
+                *force += obstacle.force(rb.position) * rb.mass;
+            }
+        }
+    }
+
+    fn compute_node_force(
+        params: &SimulationParameters,
+        rb: &RigidBody2D,
+        gravity_weight: f32,
+        quadtree: &QuadTree,
+    ) -> Vec2 {
+        let mut force = Vec2::ZERO;
+        if rb.fixed || rb.frozen {
+            return force;
+        }
+
+        if params.repel {
+            // Calculate Repel Force from the node approximations of the Quadtree
+            let cutoff = params.cutoff_radius.unwrap_or(f32::INFINITY);
+            quadtree.for_each_approx_within(
+                &rb.position,
+                params.quadtree_theta,
+                cutoff,
+                |node_approximation| {
+                    let node_approximation_particle = RigidBody2D::with_charge(
+                        node_approximation.center_of_mass(),
+                        node_approximation.total_mass(),
+                        node_approximation.charge() / node_approximation.total_mass(),
+                    );
+                    force += Self::repel_force(
+                        params.repel_force_const,
+                        params.softening,
+                        rb,
+                        &node_approximation_particle,
+                    );
+                },
+            );
+        }
+
+        //Calculate Gravity Force
+        if params.gravity {
+            force += Self::compute_center_gravity(params.gravity_force * gravity_weight, rb);
+        }
+
+        force
     }
 
-    fn apply_node_force(&self, force_vec_arc: Arc<Mutex<Vec<Vec2>>>) {
+    fn apply_node_force(
+        &self,
+        params: &SimulationParameters,
+        force_vec_arc: Arc<Mutex<Vec<Vec2>>>,
+    ) {
         let mut graph_write_guard = self.rigid_bodies.write().unwrap();
         let force_vec = force_vec_arc.lock().unwrap();
         for (i, rb) in graph_write_guard.iter_mut().enumerate() {
             let node_force = force_vec[i];
 
-            rb.velocity += node_force / rb.mass * self.delta_time;
+            rb.velocity += node_force / rb.mass * params.delta_time;
         }
     }
 
-    fn update_node_position(&self) {
+    /// Moves the nodes by their velocity, at most `max_displacement` far
+    fn update_node_position(
+        &self,
+        params: &SimulationParameters,
+        reheating: bool,
+        max_displacement: Option<f32>,
+    ) {
         let mut graph_write_guard = self.rigid_bodies.write().unwrap();
 
         'damping: for rb in graph_write_guard.iter_mut() {
-            if rb.fixed {
+            if rb.fixed || rb.frozen {
                 rb.velocity = Vec2::ZERO;
                 continue 'damping;
             }
 
-            rb.velocity *= self.damping;
+            rb.velocity *= params.damping;
 
-            rb.position += rb.velocity * self.delta_time;
+            if let Some(max_displacement) = max_displacement {
+                rb.velocity = rb
+                    .velocity
+                    .clamp_length_max(max_displacement / params.delta_time);
+            }
+            rb.position += rb.velocity * params.delta_time;
 
-            if self.freeze_thresh > rb.total_velocity() {
-                rb.fixed = true;
+            if !reheating && params.freeze_thresh > rb.total_velocity() {
+                rb.frozen = true;
             }
         }
     }
@@ -220,29 +1580,42 @@ impl Simulator {
             let rb1 = &g[spring.rb1];
             let rb2 = &g[spring.rb2];
 
-            let spring_force: Vec2 = self.compute_spring_force(rb1, rb2);
+            let spring_force: Vec2 = Self::compute_spring_force(spring, rb1, rb2);
 
             force_vec[spring.rb1] -= spring_force;
             force_vec[spring.rb2] += spring_force;
         }
     }
 
-    fn compute_spring_force(&self, n1: &RigidBody2D, n2: &RigidBody2D) -> Vec2 {
+    fn compute_spring_force(spring: &Spring, n1: &RigidBody2D, n2: &RigidBody2D) -> Vec2 {
         let direction_vec: Vec2 = n2.position - n1.position;
-        let force_magnitude =
-            self.spring_stiffness * (direction_vec.length() - self.spring_neutral_length);
+        let direction = direction_vec.normalize_or(Vec2::ZERO);
 
-        direction_vec.normalize_or(Vec2::ZERO) * -force_magnitude
+        // Only the relative velocity along the spring is damped, so rotation stays free
+        let stretch_velocity = (n2.velocity - n1.velocity).dot(direction);
+        let force_magnitude = spring.spring_stiffness
+            * (direction_vec.length() - spring.spring_neutral_len)
+            + spring.damping * stretch_velocity;
+
+        direction * -force_magnitude
     }
 
-    fn repel_force(repel_force_const: f32, n1: &RigidBody2D, n2: &RigidBody2D) -> Vec2 {
+    /// Repel force on `n1` from `n2`, the squared distance is increased by `softening²` to keep the force
+    /// finite for close bodies
+    pub(crate) fn repel_force(
+        repel_force_const: f32,
+        softening: f32,
+        n1: &RigidBody2D,
+        n2: &RigidBody2D,
+    ) -> Vec2 {
         let dir_vec: Vec2 = n2.position - n1.position;
 
         if dir_vec.length_squared() == 0.0 {
             return Vec2::ZERO;
         }
 
-        let f = -repel_force_const * (n1.mass * n2.mass).abs() / dir_vec.length_squared();
+        let f = -repel_force_const * (n1.mass * n1.charge) * (n2.mass * n2.charge)
+            / (dir_vec.length_squared() + softening * softening);
 
         let dir_vec_normalized = dir_vec.normalize_or(Vec2::ZERO);
         let force = dir_vec_normalized * f;
@@ -253,109 +1626,628 @@ impl Simulator {
         )
     }
 
-    fn compute_center_gravity(gravity_force: f32, node: &RigidBody2D) -> Vec2 {
-        -node.position * node.mass * gravity_force
+    fn compute_center_gravity(gravity_force: f32, node: &RigidBody2D) -> Vec2 {
+        -node.position * node.mass * gravity_force
+    }
+
+    /// Index of the node closest to `loc`, rope particles are skipped.
+    ///
+    /// While the simulation runs the quadtree of the last step is searched. When it is paused, the
+    /// last step built no tree, or the tree is in use by a step, all nodes are scanned since they
+    /// could have been dragged since the tree was built.
+    pub fn find_closest_node_index(&self, loc: Vec3) -> Option<u32> {
+        let rb_read = self.rigid_bodies.read().unwrap();
+        if self.is_running() {
+            if let Ok(quadtree) = self.quadtree_buffer.try_lock() {
+                if self.quadtree_current.load(Ordering::Relaxed)
+                    && quadtree.body_count() == rb_read.len()
+                {
+                    return quadtree
+                        .nearest_where(&loc.xy(), |i| !rb_read[i].rope)
+                        .map(|i| i as u32);
+                }
+            }
+        }
+
+        let mut dist = f32::INFINITY;
+        let mut index = 0;
+        for (i, rb) in rb_read.iter().enumerate().filter(|(_, rb)| !rb.rope) {
+            let new_dist = rb.position.distance(loc.xy());
+            if new_dist < dist {
+                dist = new_dist;
+                index = i as u32;
+            }
+        }
+        if dist.is_infinite() {
+            None
+        } else {
+            Some(index)
+        }
+    }
+
+    /// Index of the spring closest to `loc` that is at most `max_distance` away.
+    ///
+    /// Builds a spatial index over all springs on every call.
+    pub fn find_closest_edge_index(&self, loc: Vec3, max_distance: f32) -> Option<usize> {
+        self.edge_grid()
+            .closest(loc.xy(), max_distance)
+            .map(|(index, _)| index)
+    }
+
+    /// Spatial index over the current spring positions
+    pub(crate) fn edge_grid(&self) -> EdgeGrid {
+        let positions = self.body_positions();
+        EdgeGrid::new(&positions, &self.springs.read().unwrap())
+    }
+
+    /// Connects the nodes `a` and `b` with a new spring of the edge `kind`, or the default spring
+    /// settings if `kind` is `None` or unknown.
+    ///
+    /// The nodes gain mass like the edges of the graph the simulator was built from.
+    /// Returns the index of the new spring, or `None` if a node doesn't exist.
+    pub fn insert_edge(&self, a: u32, b: u32, kind: Option<usize>) -> Option<usize> {
+        let _lock = self.simulation_thread_lock.write().unwrap();
+        let mut rb_write = self.rigid_bodies.write().unwrap();
+        let mut spring_write = self.springs.write().unwrap();
+        self.push_spring(&mut rb_write, &mut spring_write, a, b, kind)
+    }
+
+    fn push_spring(
+        &self,
+        rb_write: &mut [RigidBody2D],
+        spring_write: &mut Vec<Spring>,
+        a: u32,
+        b: u32,
+        kind: Option<usize>,
+    ) -> Option<usize> {
+        let (a, b) = (a as usize, b as usize);
+        if a >= rb_write.len() || b >= rb_write.len() {
+            return None;
+        }
+
+        let kind = kind.filter(|k| *k < self.edge_kinds.len());
+        let edge_kind = match kind {
+            Some(k) => self.edge_kinds[k].clone(),
+            None => self.default_edge_kind.read().unwrap().clone(),
+        };
+
+        let (source_mass, target_mass) = self.mass_mode.edge_mass_increment();
+        rb_write[a].mass += source_mass;
+        rb_write[b].mass += target_mass;
+        rb_write[a].frozen = false;
+        rb_write[b].frozen = false;
+
+        spring_write.push(Spring {
+            rb1: a,
+            rb2: b,
+            spring_stiffness: edge_kind.spring_stiffness,
+            spring_neutral_len: edge_kind.spring_neutral_len,
+            damping: edge_kind.damping,
+            kind,
+        });
+        self.notify(GraphChange::EdgeInserted(a, b));
+        Some(spring_write.len() - 1)
+    }
+
+    /// Removes the spring at `index`, the indices of all following springs shift down by one.
+    ///
+    /// The mass the spring added to its nodes is removed as well and both nodes are unfrozen, so
+    /// they settle without the spring.
+    pub fn remove_spring(&self, index: usize) -> Option<Spring> {
+        let _lock = self.simulation_thread_lock.write().unwrap();
+        let mut spring_write = self.springs.write().unwrap();
+        if index >= spring_write.len() {
+            return None;
+        }
+        let spring = spring_write.remove(index);
+
+        let mut rb_write = self.rigid_bodies.write().unwrap();
+        let (source_mass, target_mass) = self.mass_mode.edge_mass_increment();
+        rb_write[spring.rb1].mass -= source_mass;
+        rb_write[spring.rb2].mass -= target_mass;
+        rb_write[spring.rb1].frozen = false;
+        rb_write[spring.rb2].frozen = false;
+        self.notify(GraphChange::EdgeRemoved(spring.rb1, spring.rb2));
+        Some(spring)
+    }
+
+    /// Removes the nodes at `indices` together with their springs, the remaining nodes shift down
+    /// to close the gaps.
+    ///
+    /// Returns the new index of every node, `u32::MAX` for removed nodes.
+    pub fn remove_nodes(&self, indices: &[u32]) -> Vec<u32> {
+        let _lock = self.simulation_thread_lock.write().unwrap();
+        let mut rb_write = self.rigid_bodies.write().unwrap();
+        let mut spring_write = self.springs.write().unwrap();
+        self.remove_nodes_locked(&mut rb_write, &mut spring_write, indices)
+    }
+
+    fn remove_nodes_locked(
+        &self,
+        rb_write: &mut Vec<RigidBody2D>,
+        spring_write: &mut Vec<Spring>,
+        indices: &[u32],
+    ) -> Vec<u32> {
+        let mut removed = vec![false; rb_write.len()];
+        for index in indices {
+            if let Some(r) = removed.get_mut(*index as usize) {
+                *r = true;
+            }
+        }
+
+        let mut next = 0;
+        let mapping: Vec<u32> = removed
+            .iter()
+            .map(|r| {
+                if *r {
+                    return u32::MAX;
+                }
+                next += 1;
+                next - 1
+            })
+            .collect();
+
+        // Remaining nodes lose the mass of their removed springs
+        let (source_mass, target_mass) = self.mass_mode.edge_mass_increment();
+        let mut removed_springs = vec![];
+        spring_write.retain(|s| {
+            if !removed[s.rb1] && !removed[s.rb2] {
+                return true;
+            }
+            removed_springs.push((s.rb1, s.rb2));
+            for (i, mass) in [(s.rb1, source_mass), (s.rb2, target_mass)] {
+                if !removed[i] {
+                    rb_write[i].mass -= mass;
+                    rb_write[i].frozen = false;
+                }
+            }
+            false
+        });
+        for spring in spring_write.iter_mut() {
+            spring.rb1 = mapping[spring.rb1] as usize;
+            spring.rb2 = mapping[spring.rb2] as usize;
+        }
+
+        let mut i = 0;
+        rb_write.retain(|_| {
+            i += 1;
+            !removed[i - 1]
+        });
+        let mut i = 0;
+        self.gravity_well_assignment.write().unwrap().retain(|_| {
+            i += 1;
+            !removed.get(i - 1).copied().unwrap_or(false)
+        });
+        remap(&mut self.net_forces.write().unwrap(), &mapping);
+
+        for (rb1, rb2) in removed_springs {
+            self.notify(GraphChange::EdgeRemoved(rb1, rb2));
+        }
+        self.notify(GraphChange::NodesRemoved(mapping.clone()));
+        mapping
+    }
+
+    pub fn set_node_location_by_index(&self, loc: Vec3, index: u32) {
+        let mut rb_write = self.rigid_bodies.write().unwrap();
+        rb_write[index as usize].position = loc.xy();
+    }
+
+    /// Pushes all nodes within `radius` of `loc` away from it.
+    ///
+    /// The impulse falls off linearly from `strength` at `loc` to zero at `radius`.
+    /// Useful to spread out a tangled cluster, the springs pull it back together over time.
+    pub fn explode(&self, loc: Vec3, radius: f32, strength: f32) {
+        let _lock = self.simulation_thread_lock.write().unwrap();
+        let mut rb_write = self.rigid_bodies.write().unwrap();
+        let center = loc.xy();
+
+        let quadtree = QuadTree::from_rigid_bodies(&rb_write);
+        let range = BoundingBox2D::new(center, radius * 2.0, radius * 2.0);
+
+        for i in quadtree.query_range(&range) {
+            let rb = &mut rb_write[i];
+            let dist = rb.position.distance(center);
+            if rb.fixed || dist > radius {
+                continue;
+            }
+
+            // Nodes exactly at the center get pushed in a random direction
+            let dir = (rb.position - center).try_normalize().unwrap_or_else(|| {
+                Vec2::from_angle(
+                    self.rng
+                        .lock()
+                        .unwrap()
+                        .gen_range(0.0..std::f32::consts::TAU),
+                )
+            });
+            rb.velocity += dir * strength * (1.0 - dist / radius);
+            rb.frozen = false;
+        }
+    }
+
+    /// Estimates the relative error of the Barnes-Hut approximation at the configured `quadtree_accuracy`.
+    ///
+    /// See `quadtree::estimate_error`
+    pub fn estimate_quadtree_error(&self) -> f32 {
+        let rb_read = self.rigid_bodies.read().unwrap();
+        quadtree::estimate_error(&rb_read, self.parameters().quadtree_theta)
+    }
+
+    /// Quadtree of the current node positions, as built for the repulsion of a simulation step
+    pub fn quadtree(&self) -> QuadTree {
+        let rb_read = self.rigid_bodies.read().unwrap();
+        self.thread_pool
+            .install(|| QuadTree::from_rigid_bodies_par(&rb_read))
+    }
+
+    /// Sets the charge of a node. Nodes with a negative charge attract nodes with a positive charge.
+    ///
+    /// Default: `1.0`
+    pub fn set_node_charge_by_index(&self, charge: f32, index: u32) {
+        let mut rb_write = self.rigid_bodies.write().unwrap();
+        rb_write[index as usize].charge = charge;
+    }
+
+    /// Pins the node at `index` in place, fixed nodes are not moved by the simulation
+    pub fn set_node_fixed_by_index(&self, fixed: bool, index: u32) {
+        let mut rb_write = self.rigid_bodies.write().unwrap();
+        rb_write[index as usize].fixed = fixed;
+    }
+
+    /// Moves the nodes at `indices` by `offset`
+    pub fn translate_nodes_by_index(&self, offset: Vec2, indices: &[u32]) {
+        let mut rb_write = self.rigid_bodies.write().unwrap();
+        for index in indices {
+            rb_write[*index as usize].position += offset;
+        }
+    }
+
+    /// Runs `steps` simulation steps that only move the nodes at `indices`, e.g. to clean up one
+    /// region of an otherwise finished layout.
+    ///
+    /// All other nodes are pinned during the steps, they still repel the subset and pull on it
+    /// through their springs. Runs regardless of whether the simulation is paused. The simulation
+    /// thread waits until the relayout is done, the `on_step` callbacks run afterwards.
+    pub fn relayout_subset(&self, indices: &[u32], steps: usize) {
+        let infos = {
+            let _lock = self.simulation_thread_lock.write().unwrap();
+            self.relayout_subset_locked(indices, steps)
+        };
+        let callbacks = self.step_callbacks.read().unwrap();
+        for info in &infos {
+            for callback in callbacks.0.iter() {
+                callback(info);
+            }
+        }
+    }
+
+    fn relayout_subset_locked(&self, indices: &[u32], steps: usize) -> Vec<StepInfo> {
+        let changes = self.subscribe_changes();
+        // Nodes that are pinned only for the relayout
+        let mut pinned: Vec<bool> = {
+            let mut rb_write = self.rigid_bodies.write().unwrap();
+            let mut in_subset = vec![false; rb_write.len()];
+            for index in indices {
+                if let Some(in_subset) = in_subset.get_mut(*index as usize) {
+                    *in_subset = true;
+                }
+            }
+            rb_write
+                .iter_mut()
+                .zip(in_subset)
+                .map(|(rb, in_subset)| {
+                    if in_subset {
+                        rb.frozen = false;
+                        false
+                    } else {
+                        !std::mem::replace(&mut rb.fixed, true)
+                    }
+                })
+                .collect()
+        };
+
+        let infos = (0..steps)
+            .map(|_| self.unlocked_simulation_step())
+            .collect();
+
+        // Follow the nodes in case they were reordered or removed meanwhile
+        for change in changes.try_iter() {
+            match change {
+                GraphChange::NodesRemoved(mapping) | GraphChange::NodesReordered(mapping) => {
+                    remap(&mut pinned, &mapping)
+                }
+                GraphChange::NodeInserted => pinned.push(false),
+                _ => (),
+            }
+        }
+        let mut rb_write = self.rigid_bodies.write().unwrap();
+        for (rb, pinned) in rb_write.iter_mut().zip(pinned) {
+            if pinned {
+                rb.fixed = false;
+            }
+        }
+        infos
+    }
+
+    /// Sets the neutral length of every spring to the current distance between its nodes.
+    ///
+    /// Useful to lock in the shape of a layout while the simulation keeps running.
+    pub fn freeze_current_lengths(&self) {
+        let _lock = self.simulation_thread_lock.write().unwrap();
+
+        let rb_read = self.rigid_bodies.read().unwrap();
+        let mut spring_write = self.springs.write().unwrap();
+
+        for spring in spring_write.iter_mut() {
+            spring.spring_neutral_len = rb_read[spring.rb1]
+                .position
+                .distance(rb_read[spring.rb2].position);
+        }
+    }
+}
+
+fn build_property_vec<T, E, D, F>(
+    graph: StableGraph<T, E, D, u32>,
+    mass_mode: MassMode,
+    default_kind: &EdgeKind,
+    edge_kinds: &[EdgeKind],
+    edge_kind_fn: F,
+    merge_parallel_edges: bool,
+    positions: Vec<Vec2>,
+) -> (Vec<RigidBody2D>, Vec<Spring>)
+where
+    D: petgraph::EdgeType,
+    F: Fn(&E) -> Option<usize>,
+{
+    let mut vec_rb: Vec<RigidBody2D> = positions
+        .into_iter()
+        .map(|position| RigidBody2D::new(position, 1.0))
+        .collect();
+    let mut vec_spring = vec![];
+    let dense = dense_indices(&graph);
+
+    for s in graph.edge_references() {
+        let kind = edge_kind_fn(s.weight()).filter(|k| *k < edge_kinds.len());
+        let edge_kind = kind.map_or(default_kind, |k| &edge_kinds[k]);
+
+        vec_spring.push(Spring {
+            rb1: dense[s.source().index()],
+            rb2: dense[s.target().index()],
+            spring_neutral_len: edge_kind.spring_neutral_len,
+            spring_stiffness: edge_kind.spring_stiffness,
+            damping: edge_kind.damping,
+            kind,
+        })
+    }
+
+    if merge_parallel_edges {
+        vec_spring = merge_parallel_springs(vec_spring, graph.is_directed());
+    }
+
+    // After merging, so merged edges only add mass once
+    let mut in_degree = vec![0; vec_rb.len()];
+    let mut out_degree = vec![0; vec_rb.len()];
+    for spring in &vec_spring {
+        in_degree[spring.rb2] += 1;
+        out_degree[spring.rb1] += 1;
+    }
+
+    for (i, rb) in vec_rb.iter_mut().enumerate() {
+        rb.mass = mass_mode.mass(in_degree[i], out_degree[i]);
+    }
+
+    (vec_rb, vec_spring)
+}
+
+/// Position of every node in `node_indices` by node index, removed nodes leave gaps in the indices
+fn dense_indices<T, E, D>(graph: &StableGraph<T, E, D, u32>) -> Vec<usize>
+where
+    D: petgraph::EdgeType,
+{
+    let mut dense = vec![usize::MAX; graph.node_bound()];
+    for (i, node) in graph.node_indices().enumerate() {
+        dense[node.index()] = i;
+    }
+    dense
+}
+
+/// Replaces every spring longer than `segment_length` with a chain of springs through new rope particles.
+///
+/// The springs of a chain are stiffer so the chain has the stiffness of the replaced spring.
+fn subdivide_ropes(
+    rigid_bodies: &mut Vec<RigidBody2D>,
+    springs: Vec<Spring>,
+    segment_length: f32,
+) -> Vec<Spring> {
+    let mut subdivided = Vec::with_capacity(springs.len());
+
+    for spring in springs {
+        let segments = (spring.spring_neutral_len / segment_length)
+            .ceil()
+            .min(MAX_ROPE_SEGMENTS as f32) as usize;
+        if segments < 2 {
+            subdivided.push(spring);
+            continue;
+        }
+
+        let start = rigid_bodies[spring.rb1].position;
+        let end = rigid_bodies[spring.rb2].position;
+        let mut chain = vec![spring.rb1];
+        for i in 1..segments {
+            let mut particle = RigidBody2D::with_charge(
+                start.lerp(end, i as f32 / segments as f32),
+                ROPE_PARTICLE_MASS,
+                ROPE_PARTICLE_CHARGE,
+            );
+            particle.rope = true;
+            chain.push(rigid_bodies.len());
+            rigid_bodies.push(particle);
+        }
+        chain.push(spring.rb2);
+
+        for link in chain.windows(2) {
+            subdivided.push(Spring {
+                rb1: link[0],
+                rb2: link[1],
+                spring_stiffness: spring.spring_stiffness * segments as f32,
+                spring_neutral_len: spring.spring_neutral_len / segments as f32,
+                damping: spring.damping * segments as f32,
+                kind: spring.kind,
+            });
+        }
     }
+    subdivided
+}
 
-    pub fn find_closest_node_index(&self, loc: Vec3) -> Option<u32> {
-        let rb_read = self.rigid_bodies.read().unwrap();
-        let mut dist = f32::INFINITY;
-        let mut index = 0;
-        for (i, rb) in rb_read.iter().enumerate() {
-            let new_dist = rb.position.distance(loc.xy());
-            if new_dist < dist {
-                dist = new_dist;
-                index = i as u32;
+/// Collapses springs connecting the same pair of nodes into one spring with the summed stiffness
+/// and damping. In directed graphs only springs in the same direction are merged.
+///
+/// The neutral length is the stiffness weighted mean of the merged lengths, where the parallel
+/// springs are at rest together. The kind is kept if all merged springs share it and dropped
+/// otherwise.
+fn merge_parallel_springs(springs: Vec<Spring>, directed: bool) -> Vec<Spring> {
+    let mut merged: Vec<Spring> = Vec::with_capacity(springs.len());
+    let mut index_map: HashMap<(usize, usize), usize> = HashMap::new();
+
+    for spring in springs {
+        let key = if directed {
+            (spring.rb1, spring.rb2)
+        } else {
+            (spring.rb1.min(spring.rb2), spring.rb1.max(spring.rb2))
+        };
+        if let Some(i) = index_map.get(&key) {
+            let kept = &mut merged[*i];
+            let stiffness = kept.spring_stiffness + spring.spring_stiffness;
+            if stiffness != 0.0 {
+                kept.spring_neutral_len = (kept.spring_neutral_len * kept.spring_stiffness
+                    + spring.spring_neutral_len * spring.spring_stiffness)
+                    / stiffness;
+            }
+            kept.spring_stiffness = stiffness;
+            kept.damping += spring.damping;
+            if kept.kind != spring.kind {
+                kept.kind = None;
             }
-        }
-        if dist.is_infinite() {
-            None
         } else {
-            Some(index)
+            index_map.insert(key, merged.len());
+            merged.push(spring);
         }
     }
 
-    pub fn set_node_location_by_index(&self, loc: Vec3, index: u32) {
-        let mut rb_write = self.rigid_bodies.write().unwrap();
-        rb_write[index as usize].position = loc.xy();
-    }
+    merged
 }
 
-fn build_quadtree(rb_vec_arc: Arc<RwLock<Vec<RigidBody2D>>>) -> QuadTree {
-    let rb_vec_guard = rb_vec_arc.read().unwrap();
+/// Members of every connected component, shared with the step that uses them
+type Components = Arc<Vec<Vec<usize>>>;
 
-    let mut min = Vec2::INFINITY;
-    let mut max = Vec2::NEG_INFINITY;
+/// Gravity factor of node `i`, see `Simulator::gravity_weights`
+fn gravity_weight(gravity_weights: &Option<Vec<f32>>, i: usize) -> f32 {
+    gravity_weights
+        .as_ref()
+        .and_then(|w| w.get(i).copied())
+        .unwrap_or(1.0)
+}
 
-    for rb in rb_vec_guard.iter() {
-        min = min.min(rb.position);
-        max = max.max(rb.position);
+/// All `members` as one body at their center of mass, with their total mass and charge
+fn aggregate_body(rb_vec: &[RigidBody2D], members: &[usize]) -> RigidBody2D {
+    let mass: f32 = members.iter().map(|i| rb_vec[*i].mass).sum();
+    let (center, charge) = members
+        .iter()
+        .fold((Vec2::ZERO, 0.0), |(center, charge), i| {
+            let rb = &rb_vec[*i];
+            (center + rb.position * rb.mass, charge + rb.charge * rb.mass)
+        });
+    if mass == 0.0 {
+        return RigidBody2D::with_charge(center, 0.0, 0.0);
     }
-    let dir = max - min;
-
-    let boundary = BoundingBox2D::new((dir / 2.0) + min, dir[0], dir[1]);
-    let mut quadtree = QuadTree::with_capacity(boundary.clone(), rb_vec_guard.len());
+    RigidBody2D::with_charge(center / mass, mass, charge / mass)
+}
 
-    for rb in rb_vec_guard.iter() {
-        quadtree.insert(rb.position, rb.mass);
-    }
-    quadtree
+/// How strongly each node is pulled to the center by the center gravity
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GravityMode {
+    /// Every node is pulled with `gravity_force`
+    #[default]
+    Uniform,
+    /// Nodes are pulled in proportion to their PageRank, so structurally important nodes anchor
+    /// the center. The ranks are kept up to date while nodes and edges change, see
+    /// `analysis::DynamicPageRank`.
+    PageRank,
 }
 
-fn build_property_vec<T, E, D>(
-    graph: StableGraph<T, E, D, u32>,
-    edge_based_mass: bool,
-) -> (Vec<RigidBody2D>, Vec<Spring>)
-where
-    D: petgraph::EdgeType,
-{
-    let mut vec_rb = vec![];
-    let mut vec_spring = vec![];
+/// How the mass of a node is derived from the edges connected to it
+#[derive(Clone, Copy, Debug)]
+pub enum MassMode {
+    /// Every node has a mass of `1`
+    Uniform,
+    /// `1` + number of incoming and outgoing edges
+    Degree,
+    /// `1` + number of incoming edges
+    InDegree,
+    /// `1` + number of outgoing edges
+    OutDegree,
+    /// Mass computed from the in- and out-degree of the node
+    Custom(fn(in_degree: usize, out_degree: usize) -> f32),
+}
 
-    for _ in 0..graph.node_count() {
-        vec_rb.push(RigidBody2D::new(
-            Vec2::new(
-                rand::thread_rng().gen_range(-60.0..60.0),
-                rand::thread_rng().gen_range(-60.0..60.0),
-            ),
-            1.0,
-        ));
+impl MassMode {
+    /// Mass added to the source and target node of an edge that is added at runtime
+    fn edge_mass_increment(&self) -> (f32, f32) {
+        match self {
+            MassMode::Uniform | MassMode::Custom(_) => (0.0, 0.0),
+            MassMode::Degree => (1.0, 1.0),
+            MassMode::InDegree => (0.0, 1.0),
+            MassMode::OutDegree => (1.0, 0.0),
+        }
     }
 
-    let edges = graph.edge_references();
-
-    for s in edges {
-        if edge_based_mass {
-            vec_rb[s.target().index()].mass += 1.0;
-            vec_rb[s.source().index()].mass += 1.0;
+    fn mass(&self, in_degree: usize, out_degree: usize) -> f32 {
+        match self {
+            MassMode::Uniform => 1.0,
+            MassMode::Degree => 1.0 + (in_degree + out_degree) as f32,
+            MassMode::InDegree => 1.0 + in_degree as f32,
+            MassMode::OutDegree => 1.0 + out_degree as f32,
+            MassMode::Custom(f) => f(in_degree, out_degree),
         }
-
-        vec_spring.push(Spring {
-            rb1: s.source().index(),
-            rb2: s.target().index(),
-            spring_neutral_len: 2.0,
-            spring_stiffness: 1.0,
-        })
     }
-
-    (vec_rb, vec_spring)
 }
 
 /// Builder for `Simulator`
+#[derive(Clone, Debug)]
 pub struct SimulatorBuilder {
     repel: bool,
     spring: bool,
     gravity: bool,
     spring_stiffness: f32,
     spring_neutral_length: f32,
+    spring_damping: f32,
     delta_time: f32,
     gravity_force: f32,
     repel_force_const: f32,
+    softening: f32,
+    cutoff_radius: Option<f32>,
     damping: f32,
     quadtree_theta: f32,
     freeze_thresh: f32,
     max_threads: u32,
-    edge_based_mass: bool,
+    mass_mode: MassMode,
+    gravity_mode: GravityMode,
+    edge_kinds: Vec<EdgeKind>,
+    gravity_wells: Vec<GravityWell>,
+    gravity_well_assignment: Vec<Option<usize>>,
+    obstacles: Vec<Obstacle>,
+    seed: Option<u64>,
+    initial_layout: InitialLayout,
+    merge_parallel_edges: bool,
+    rope_segment_length: f32,
+    convergence_epsilon: Option<f32>,
+    max_step_displacement: Option<f32>,
+    recenter: bool,
+    angular_damping: f32,
+    annealing: Option<(f32, f32)>,
+    warm_restart_steps: u32,
+    spatial_sort_interval: Option<u32>,
+    component_scheduling: bool,
 }
 
 impl SimulatorBuilder {
@@ -409,6 +2301,17 @@ impl SimulatorBuilder {
         self
     }
 
+    /// How strongly springs resist being stretched or compressed quickly, which stops stiff
+    /// springs from oscillating.
+    ///
+    /// Only applies to edges without an edge kind, see `EdgeKind::with_damping`.
+    ///
+    /// Default: `0.0`
+    pub fn spring_damping(mut self, spring_damping: f32) -> Self {
+        self.spring_damping = spring_damping;
+        self
+    }
+
     /// How strong the pull to the center should be.
     ///
     /// Default: `1.0`
@@ -417,6 +2320,14 @@ impl SimulatorBuilder {
         self
     }
 
+    /// How the pull to the center is spread over the nodes, see `GravityMode`
+    ///
+    /// Default: `GravityMode::Uniform`
+    pub fn gravity_mode(mut self, gravity_mode: GravityMode) -> Self {
+        self.gravity_mode = gravity_mode;
+        self
+    }
+
     /// How strong nodes should push others away.
     ///
     /// Default: `100.0`
@@ -425,6 +2336,25 @@ impl SimulatorBuilder {
         self
     }
 
+    /// Distance below which the repel force stops growing like `1/r²`, the force is
+    /// `1/(r² + softening²)`. Smooths the start of a simulation where many nodes are close.
+    ///
+    /// Default: `0.0`
+    pub fn softening(mut self, softening: f32) -> Self {
+        self.softening = softening;
+        self
+    }
+
+    /// Distance beyond which nodes don't repel each other. Parts of the quadtree further away are
+    /// skipped, which makes steps of huge graphs a lot faster at the cost of a less spread out
+    /// layout.
+    ///
+    /// Default: no cutoff
+    pub fn cutoff_radius(mut self, cutoff_radius: f32) -> Self {
+        self.cutoff_radius = Some(cutoff_radius);
+        self
+    }
+
     /// Amount of damping that should be applied to the nodes movement
     ///
     /// `1.0` -> No Damping
@@ -459,6 +2389,50 @@ impl SimulatorBuilder {
         self
     }
 
+    /// Pause the simulation once the average node velocity falls below `epsilon`.
+    ///
+    /// Use `Simulator::subscribe_converged` to get notified.
+    ///
+    /// Default: disabled
+    pub fn stop_when_converged(mut self, epsilon: f32) -> Self {
+        self.convergence_epsilon = Some(epsilon);
+        self
+    }
+
+    /// For how many steps freezing is suspended after `Simulator::set_parameters`.
+    ///
+    /// Frozen nodes are released on every parameter change so the layout can adapt to the new forces.
+    /// Set to `0` to keep frozen nodes frozen.
+    ///
+    /// Default: `100`
+    pub fn warm_restart(mut self, steps: u32) -> Self {
+        self.warm_restart_steps = steps;
+        self
+    }
+
+    /// Every `steps` simulation steps the nodes are reordered along a Z-order curve, so nodes that
+    /// are close in the layout are close in memory. Speeds up the force calculation of large
+    /// graphs.
+    ///
+    /// This changes the indices of the nodes, use `Simulator::subscribe_changes` to follow them
+    /// through `GraphChange::NodesReordered`. The `Renderer` remaps its own per node state.
+    ///
+    /// Default: no reordering
+    pub fn spatial_sort_interval(mut self, steps: u32) -> Self {
+        self.spatial_sort_interval = Some(steps.max(1));
+        self
+    }
+
+    /// Simulates every connected component on its own worker with its own quadtree. Other
+    /// components push a component only as a whole, from their center of mass, which scales
+    /// better to graphs with many components. Components whose nodes are all frozen are skipped.
+    ///
+    /// Default: `false`
+    pub fn component_scheduling(mut self, component_scheduling: bool) -> Self {
+        self.component_scheduling = component_scheduling;
+        self
+    }
+
     /// How much time a simulation step should simulate. (euler method)
     ///
     /// Bigger time steps result in faster simulations, but less accurate or even wrong simulations.
@@ -476,7 +2450,72 @@ impl SimulatorBuilder {
         self
     }
 
-    /// How many CPU threads should be used to calculate physics
+    /// Keeps the layout centered at the origin without `gravity`: every step the mean velocity of
+    /// the nodes is removed and the layout is moved so its center of mass stays at the origin.
+    ///
+    /// Does nothing while nodes are pinned, they already anchor the layout. Gravity wells and
+    /// obstacles don't move along, so combine them with `gravity` instead.
+    ///
+    /// Default: `false`
+    pub fn recenter(mut self, recenter: bool) -> Self {
+        self.recenter = recenter;
+        self
+    }
+
+    /// Fraction of the rotation of the whole layout around its center of mass that is removed
+    /// every step, so a finished layout keeps its orientation for comparisons and recordings.
+    /// `1.0` stops any rotation right away, the shape of the layout is not affected.
+    ///
+    /// Panics when `angular_damping` is outside of `0..=1`
+    ///
+    /// Default: `0.0`
+    pub fn angular_damping(mut self, angular_damping: f32) -> Self {
+        if !(0.0..=1.0).contains(&angular_damping) {
+            panic!("angular_damping has to be between 0 and 1!");
+        }
+        self.angular_damping = angular_damping;
+        self
+    }
+
+    /// Caps how far a node moves in one step by a global temperature that starts at
+    /// `temperature` and is multiplied by `cooling` after every step, like the Fruchterman-Reingold
+    /// layout. The layout settles into a stable shape as it cools, instead of relying on `damping`
+    /// alone. It stops cooling at `temperature / 1000` and heats up again with
+    /// `Simulator::set_parameters`, see `warm_restart`.
+    ///
+    /// Panics when `temperature` is `0` or below or `cooling` is outside of `0..=1`
+    ///
+    /// Default: disabled
+    pub fn annealing(mut self, temperature: f32, cooling: f32) -> Self {
+        if temperature <= 0.0 {
+            panic!("temperature may not be 0 or below!");
+        }
+        if !(0.0..=1.0).contains(&cooling) {
+            panic!("cooling has to be between 0 and 1!");
+        }
+        self.annealing = Some((temperature, cooling));
+        self
+    }
+
+    /// Adapts the time step every step so no node moves further than `max_displacement` in one
+    /// step. The step shrinks down to `delta_time / 1000` while the forces are strong and grows up
+    /// to `10 * delta_time` as the layout settles, so `delta_time` doesn't need hand tuning to
+    /// avoid explosions. See `SimulatorMetrics::delta_time` for the current step.
+    ///
+    /// Panics when `max_displacement` is `0` or below
+    ///
+    /// Default: disabled
+    pub fn adaptive_delta_time(mut self, max_displacement: f32) -> Self {
+        if max_displacement <= 0.0 {
+            panic!("max_displacement may not be 0 or below!");
+        }
+        self.max_step_displacement = Some(max_displacement);
+        self
+    }
+
+    /// How many CPU threads should be used to calculate physics.
+    ///
+    /// The threads are spawned once when the `Simulator` is built and reused every step.
     ///
     /// Panics when thread count is `0`
     ///
@@ -491,9 +2530,113 @@ impl SimulatorBuilder {
 
     /// If nodes mass should be increased based on the edges connected to a node
     ///
+    /// Shorthand for `mass_mode(MassMode::Degree)` or `mass_mode(MassMode::Uniform)`
+    ///
     /// Default: `true`
     pub fn edge_based_mass(mut self, edge_based_mass: bool) -> Self {
-        self.edge_based_mass = edge_based_mass;
+        self.mass_mode = if edge_based_mass {
+            MassMode::Degree
+        } else {
+            MassMode::Uniform
+        };
+        self
+    }
+
+    /// How the mass of a node is derived from its edges
+    ///
+    /// Default: `MassMode::Degree`
+    pub fn mass_mode(mut self, mass_mode: MassMode) -> Self {
+        self.mass_mode = mass_mode;
+        self
+    }
+
+    /// Where the nodes start, see `InitialLayout`. `Circle`, `Grid` and `Spectral` space the nodes
+    /// by `spring_neutral_length`.
+    ///
+    /// Default: `InitialLayout::Random(-60.0..60.0)`
+    pub fn initial_layout(mut self, initial_layout: InitialLayout) -> Self {
+        self.initial_layout = initial_layout;
+        self
+    }
+
+    /// If parallel edges between the same two nodes should be merged into a single spring, so
+    /// multigraphs don't get heavy nodes and stiff springs. In directed graphs `a -> b` and
+    /// `b -> a` are kept apart.
+    ///
+    /// The stiffness of the merged spring is the sum of the stiffness of all merged edges, its
+    /// neutral length their stiffness weighted mean. Merged edges of different kinds lose their
+    /// kind. Node masses count a merged spring as one edge.
+    ///
+    /// Default: `false`
+    pub fn merge_parallel_edges(mut self, merge_parallel_edges: bool) -> Self {
+        self.merge_parallel_edges = merge_parallel_edges;
+        self
+    }
+
+    /// Splits edges with a neutral length longer than `segment_length` into a chain of springs
+    /// connected by light rope particles, so the edges bend around dense areas.
+    ///
+    /// Rope particles are appended after the nodes and marked with `RigidBody2D::rope`.
+    /// Springs no longer map one to one to the edges of the graph, `Simulator::positions` and
+    /// `Simulator::edges` leave the particles out. Edges are split into at most 64 segments.
+    ///
+    /// Panics when `segment_length` is `0` or below or NaN
+    ///
+    /// Default: `f32::INFINITY` (disabled)
+    pub fn rope_segment_length(mut self, segment_length: f32) -> Self {
+        if segment_length.is_nan() || segment_length <= 0.0 {
+            panic!("segment_length has to be above 0!");
+        }
+        self.rope_segment_length = segment_length;
+        self
+    }
+
+    /// Adds a edge kind with its own spring stiffness, neutral length and color.
+    ///
+    /// Kinds are indexed in the order they are added and assigned to edges in `build_with_edge_kinds`.
+    ///
+    /// Default: no edge kinds
+    pub fn edge_kind(mut self, edge_kind: EdgeKind) -> Self {
+        self.edge_kinds.push(edge_kind);
+        self
+    }
+
+    /// Adds a point that pulls the nodes assigned to it with `gravity_well_assignment`.
+    ///
+    /// The pull grows with the distance to the well like the center gravity. See
+    /// `GravityWell::circle` to place one well per category.
+    ///
+    /// Default: no gravity wells
+    pub fn gravity_well(mut self, gravity_well: GravityWell) -> Self {
+        self.gravity_wells.push(gravity_well);
+        self
+    }
+
+    /// Index of the gravity well every node is pulled to, indexed by node index.
+    ///
+    /// Default: no node is assigned to a well
+    pub fn gravity_well_assignment(mut self, assignment: Vec<Option<usize>>) -> Self {
+        self.gravity_well_assignment = assignment;
+        self
+    }
+
+    /// Adds a rectangle or circle that pushes the nodes out, so the layout flows around it.
+    /// Rope particles are pushed as well, so rope edges bend around it.
+    ///
+    /// Default: no obstacles
+    pub fn obstacle(mut self, obstacle: Obstacle) -> Self {
+        self.obstacles.push(obstacle);
+        self
+    }
+
+    /// Seeds the random initial positions and placement of merged nodes.
+    ///
+    /// Simulators built with the same seed from the same graph and settings stay bitwise
+    /// identical, independent of `max_threads`. See `check_determinism`.
+    ///
+    /// Default: `None` (random)
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
         self
     }
 
@@ -502,24 +2645,151 @@ impl SimulatorBuilder {
     where
         D: petgraph::EdgeType,
     {
-        let (rigid_bodies, springs) = build_property_vec(graph, self.edge_based_mass);
-        Simulator {
-            simulation_thread_lock: Arc::new(RwLock::new(true)),
+        self.build_with_edge_kinds(graph, |_| None)
+    }
+
+    /// Constructs a instance of `Simulator` and assigns each edge the kind returned by `edge_kind_fn`.
+    ///
+    /// Edges mapped to `None` or a unknown kind use `spring_stiffness` and `spring_neutral_length`.
+    pub fn build_with_edge_kinds<T, E, D, F>(
+        self,
+        graph: StableGraph<T, E, D, u32>,
+        edge_kind_fn: F,
+    ) -> Simulator
+    where
+        D: petgraph::EdgeType,
+        F: Fn(&E) -> Option<usize>,
+    {
+        let default_kind = EdgeKind::new(self.spring_stiffness, self.spring_neutral_length)
+            .with_damping(self.spring_damping);
+        let dense = dense_indices(&graph);
+        let edges: Vec<(usize, usize)> = graph
+            .edge_references()
+            .map(|e| (dense[e.source().index()], dense[e.target().index()]))
+            .collect();
+        let positions = self.initial_layout.positions(
+            graph.node_count(),
+            &edges,
+            self.spring_neutral_length,
+            &mut self.rng(),
+        );
+        let (mut rigid_bodies, mut springs) = build_property_vec(
+            graph,
+            self.mass_mode,
+            &default_kind,
+            &self.edge_kinds,
+            edge_kind_fn,
+            self.merge_parallel_edges,
+            positions,
+        );
+        if self.rope_segment_length.is_finite() {
+            springs = subdivide_ropes(&mut rigid_bodies, springs, self.rope_segment_length);
+        }
+        let parameters = self.parameters();
+        self.assemble(rigid_bodies, springs, parameters)
+    }
+
+    /// Constructs a instance of `Simulator` that resumes from a snapshot.
+    ///
+    /// Physics parameters and edge kinds are taken from the snapshot, all other settings from the builder.
+    pub fn build_from_snapshot(mut self, snapshot: SimulatorSnapshot) -> Simulator {
+        self.edge_kinds = snapshot.edge_kinds;
+        self.assemble(snapshot.rigid_bodies, snapshot.springs, snapshot.parameters)
+    }
+
+    fn rng(&self) -> StdRng {
+        self.seed
+            .map_or_else(StdRng::from_entropy, StdRng::seed_from_u64)
+    }
+
+    fn parameters(&self) -> SimulationParameters {
+        SimulationParameters {
             repel: self.repel,
             spring: self.spring,
             gravity: self.gravity,
             repel_force_const: self.repel_force_const,
-            spring_stiffness: self.spring_stiffness,
-            spring_neutral_length: self.spring_neutral_length,
+            softening: self.softening,
+            cutoff_radius: self.cutoff_radius,
             gravity_force: self.gravity_force,
             delta_time: self.delta_time,
             damping: self.damping,
             quadtree_theta: self.quadtree_theta,
             freeze_thresh: self.freeze_thresh,
-            max_threads: self.max_threads,
+        }
+    }
+
+    fn assemble(
+        self,
+        rigid_bodies: Vec<RigidBody2D>,
+        springs: Vec<Spring>,
+        parameters: SimulationParameters,
+    ) -> Simulator {
+        let rng = self.rng();
+        let positions = rigid_bodies_positions(&rigid_bodies);
+        let node_order = NodeOrder::new(&rigid_bodies);
+        let settings = Arc::new(SimulatorBuilder {
+            initial_layout: InitialLayout::default(),
+            gravity_well_assignment: vec![],
+            ..self.clone()
+        });
+        let simulator = Simulator {
+            simulation_thread_lock: Arc::new(RwLock::new(true)),
+            running: Arc::new((Mutex::new(false), Condvar::new())),
+            convergence_epsilon: self.convergence_epsilon,
+            max_step_displacement: self.max_step_displacement,
+            recenter: self.recenter,
+            angular_damping: self.angular_damping,
+            annealing: self.annealing,
+            temperature: Arc::new(RwLock::new(
+                self.annealing.map_or(f32::INFINITY, |(start, _)| start),
+            )),
+            delta_time_scale: Arc::new(RwLock::new(1.0)),
+            converged_senders: Arc::new(Mutex::new(vec![])),
+            change_senders: Arc::new(Mutex::new(vec![])),
+            parameters: Arc::new(RwLock::new(parameters)),
+            warm_restart_steps: self.warm_restart_steps,
+            reheat_steps_left: Arc::new(RwLock::new(0)),
+            spatial_sort_interval: self.spatial_sort_interval,
+            component_scheduling: self.component_scheduling,
+            components: Arc::new(Mutex::new(None)),
+            steps_since_sort: Arc::new(RwLock::new(0)),
+            thread_pool: Arc::new(
+                ThreadPoolBuilder::new()
+                    .num_threads(self.max_threads as usize)
+                    .build()
+                    .unwrap(),
+            ),
             rigid_bodies: Arc::new(RwLock::new(rigid_bodies)),
             springs: Arc::new(RwLock::new(springs)),
+            edge_kinds: self.edge_kinds,
+            default_edge_kind: Arc::new(RwLock::new(
+                EdgeKind::new(self.spring_stiffness, self.spring_neutral_length)
+                    .with_damping(self.spring_damping),
+            )),
+            mass_mode: self.mass_mode,
+            gravity_rank: Arc::new(Mutex::new(None)),
+            gravity_wells: Arc::new(RwLock::new(self.gravity_wells)),
+            gravity_well_assignment: Arc::new(RwLock::new(self.gravity_well_assignment)),
+            obstacles: Arc::new(RwLock::new(self.obstacles)),
+            rng: Arc::new(Mutex::new(rng)),
+            step_stats: Arc::new(RwLock::new(StepStats::default())),
+            net_forces: Arc::new(RwLock::new(vec![])),
+            metrics: Arc::new(Mutex::new(MetricsTracker::new())),
+            positions: Arc::new(RwLock::new(positions)),
+            node_order: Arc::new(RwLock::new(node_order)),
+            step_callbacks: Arc::new(RwLock::new(StepCallbacks::default())),
+            quadtree_buffer: Arc::new(Mutex::new(QuadTree::new(BoundingBox2D::new(
+                Vec2::ZERO,
+                0.0,
+                0.0,
+            )))),
+            quadtree_current: Arc::new(AtomicBool::new(false)),
+            settings,
+        };
+        if self.gravity_mode == GravityMode::PageRank {
+            *simulator.gravity_rank.lock().unwrap() = Some(DynamicPageRank::new(&simulator));
         }
+        simulator
     }
 }
 
@@ -531,15 +2801,673 @@ impl Default for SimulatorBuilder {
             spring: true,
             gravity: true,
             repel_force_const: 100.0,
+            softening: 0.0,
+            cutoff_radius: None,
             spring_stiffness: 100.0,
             spring_neutral_length: 2.0,
+            spring_damping: 0.0,
             gravity_force: 1.0,
             delta_time: 0.005,
             damping: 0.9,
             quadtree_theta: 0.75,
             freeze_thresh: 1e-2,
             max_threads: 16,
-            edge_based_mass: true,
+            mass_mode: MassMode::Degree,
+            gravity_mode: GravityMode::Uniform,
+            edge_kinds: vec![],
+            gravity_wells: vec![],
+            gravity_well_assignment: vec![],
+            obstacles: vec![],
+            seed: None,
+            initial_layout: InitialLayout::default(),
+            merge_parallel_edges: false,
+            rope_segment_length: f32::INFINITY,
+            convergence_epsilon: None,
+            max_step_displacement: None,
+            recenter: false,
+            angular_damping: 0.0,
+            annealing: None,
+            warm_restart_steps: 100,
+            spatial_sort_interval: None,
+            component_scheduling: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use petgraph::{Directed, Undirected};
+
+    use super::*;
+
+    /// Path through `n` nodes
+    fn path(n: usize) -> StableGraph<usize, (), Undirected, u32> {
+        let mut graph = StableGraph::default();
+        let nodes: Vec<_> = (0..n).map(|i| graph.add_node(i)).collect();
+        for pair in nodes.windows(2) {
+            graph.add_edge(pair[0], pair[1], ());
+        }
+        graph
+    }
+
+    #[test]
+    fn test_rope_chain_keeps_stiffness_and_length() {
+        let graph = path(2);
+        let simulator = SimulatorBuilder::new()
+            .spring_stiffness(30.0)
+            .spring_neutral_length(10.0)
+            .rope_segment_length(3.0)
+            .build(graph);
+
+        let rigid_bodies = simulator.rigid_bodies.read().unwrap();
+        let springs = simulator.springs.read().unwrap();
+        assert_eq!(rigid_bodies.len(), 2 + 3);
+        assert!(rigid_bodies[2..].iter().all(|rb| rb.rope));
+        assert_eq!(springs.len(), 4);
+        // Springs in series: the chain is as long and as stiff as the replaced spring
+        let length: f32 = springs.iter().map(|s| s.spring_neutral_len).sum();
+        let compliance: f32 = springs.iter().map(|s| 1.0 / s.spring_stiffness).sum();
+        assert!((length - 10.0).abs() < 1e-4);
+        assert!((1.0 / compliance - 30.0).abs() < 1e-3);
+        drop((rigid_bodies, springs));
+
+        assert_eq!(simulator.positions().len(), 2);
+        assert_eq!(simulator.edges(), vec![(0, 1)]);
+    }
+
+    #[test]
+    fn test_rope_segments_are_capped() {
+        let simulator = SimulatorBuilder::new()
+            .spring_neutral_length(1e6)
+            .rope_segment_length(f32::MIN_POSITIVE)
+            .build(path(2));
+        assert_eq!(simulator.springs.read().unwrap().len(), MAX_ROPE_SEGMENTS);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_rope_segment_length_zero_panics() {
+        SimulatorBuilder::new().rope_segment_length(0.0);
+    }
+
+    #[test]
+    fn test_positions_follow_spatial_sort() {
+        let graph = path(16);
+        // Scattered so sorting along the Z-order curve has to move the nodes
+        let expected: Vec<Vec2> = (0..16)
+            .map(|i| Vec2::new(((i * 7) % 16) as f32, ((i * 11) % 16) as f32) * 10.0)
+            .collect();
+        let simulator = SimulatorBuilder::new()
+            .initial_layout(InitialLayout::FromPositions(expected.clone()))
+            .spatial_sort_interval(1)
+            .build(graph.clone());
+        for i in 0..16 {
+            simulator.set_node_fixed_by_index(true, i);
+        }
+        let changes = simulator.subscribe_changes();
+        simulator.step_n(3);
+        assert!(changes
+            .try_iter()
+            .any(|c| matches!(c, GraphChange::NodesReordered(_))));
+
+        assert_eq!(simulator.positions(), expected);
+        let edges = simulator.edges();
+        assert!((0..15).all(|i| edges.contains(&(i, i + 1)) || edges.contains(&(i + 1, i))));
+        let positioned = simulator.into_positioned_graph(&graph);
+        for node in positioned.node_indices() {
+            let (i, position) = positioned[node];
+            assert_eq!(position, expected[i]);
+        }
+    }
+
+    fn spring(rb1: usize, rb2: usize, stiffness: f32, length: f32, kind: Option<usize>) -> Spring {
+        Spring {
+            rb1,
+            rb2,
+            spring_stiffness: stiffness,
+            spring_neutral_len: length,
+            damping: 0.0,
+            kind,
+        }
+    }
+
+    #[test]
+    fn test_merge_parallel_springs() {
+        let springs = vec![
+            spring(0, 1, 1.0, 2.0, Some(0)),
+            spring(0, 1, 3.0, 6.0, Some(0)),
+            spring(1, 0, 1.0, 1.0, Some(1)),
+        ];
+
+        let directed = merge_parallel_springs(springs.clone(), true);
+        assert_eq!(directed.len(), 2);
+        assert_eq!(directed[0].spring_stiffness, 4.0);
+        assert_eq!(directed[0].spring_neutral_len, 5.0);
+        assert_eq!(directed[0].kind, Some(0));
+        assert_eq!(directed[1].kind, Some(1));
+
+        let undirected = merge_parallel_springs(springs, false);
+        assert_eq!(undirected.len(), 1);
+        assert_eq!(undirected[0].spring_stiffness, 5.0);
+        assert_eq!(undirected[0].spring_neutral_len, 21.0 / 5.0);
+        assert_eq!(undirected[0].kind, None);
+    }
+
+    #[test]
+    fn test_merged_edges_add_mass_once() {
+        let mut graph: StableGraph<(), (), Undirected, u32> = StableGraph::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        for _ in 0..3 {
+            graph.add_edge(a, b, ());
+        }
+        let simulator = SimulatorBuilder::new()
+            .merge_parallel_edges(true)
+            .build(graph);
+
+        assert_eq!(simulator.springs.read().unwrap().len(), 1);
+        let rigid_bodies = simulator.rigid_bodies.read().unwrap();
+        assert!(rigid_bodies.iter().all(|rb| rb.mass == 2.0));
+    }
+
+    #[test]
+    fn test_relayout_subset_only_moves_subset() {
+        let simulator = SimulatorBuilder::new().build(path(5));
+        let before = simulator.body_positions();
+
+        simulator.relayout_subset(&[2], 20);
+
+        let after = simulator.body_positions();
+        for i in [0, 1, 3, 4] {
+            assert_eq!(before[i], after[i]);
+        }
+        assert_ne!(before[2], after[2]);
+        let rigid_bodies = simulator.rigid_bodies.read().unwrap();
+        assert!(rigid_bodies.iter().all(|rb| !rb.fixed));
+    }
+
+    #[test]
+    fn test_remove_spring_unfreezes_nodes() {
+        let simulator = SimulatorBuilder::new().build(path(3));
+        simulator
+            .rigid_bodies
+            .write()
+            .unwrap()
+            .iter_mut()
+            .for_each(|rb| rb.frozen = true);
+
+        let spring = simulator.remove_spring(0).unwrap();
+
+        let rigid_bodies = simulator.rigid_bodies.read().unwrap();
+        assert!(!rigid_bodies[spring.rb1].frozen);
+        assert!(!rigid_bodies[spring.rb2].frozen);
+        assert!(rigid_bodies
+            .iter()
+            .enumerate()
+            .all(|(i, rb)| rb.frozen == (i != spring.rb1 && i != spring.rb2)));
+    }
+
+    #[test]
+    fn test_repel_force_follows_charges() {
+        let n1 = RigidBody2D::with_charge(Vec2::ZERO, 1.0, 1.0);
+        let positive = RigidBody2D::with_charge(Vec2::X, 1.0, 1.0);
+        let negative = RigidBody2D::with_charge(Vec2::X, 1.0, -1.0);
+
+        // Like charges push `n1` away from the other node, opposite charges pull it closer
+        let repelled = Simulator::repel_force(1.0, 0.0, &n1, &positive);
+        let attracted = Simulator::repel_force(1.0, 0.0, &n1, &negative);
+        assert!(repelled.x < 0.0);
+        assert!(attracted.x > 0.0);
+        assert_eq!(repelled, -attracted);
+    }
+
+    #[test]
+    fn test_freeze_current_lengths() {
+        let simulator = SimulatorBuilder::new()
+            .repel(false)
+            .gravity_force(0.0)
+            .freeze_threshold(-1.0)
+            .initial_layout(InitialLayout::FromPositions(vec![
+                Vec2::ZERO,
+                Vec2::new(3.0, 0.0),
+                Vec2::new(3.0, 4.0),
+            ]))
+            .build(path(3));
+
+        simulator.freeze_current_lengths();
+
+        let lengths: Vec<f32> = simulator
+            .springs
+            .read()
+            .unwrap()
+            .iter()
+            .map(|s| s.spring_neutral_len)
+            .collect();
+        assert_eq!(lengths, vec![3.0, 4.0]);
+        // All springs are at rest, so the layout keeps its shape
+        let before = simulator.positions();
+        simulator.step_n(10);
+        assert_eq!(before, simulator.positions());
+
+        // Stretched springs pull back to the frozen lengths
+        simulator.rigid_bodies.write().unwrap()[2].position = Vec2::new(3.0, 8.0);
+        simulator.step_n(1);
+        assert!(simulator.positions()[2].y < 8.0);
+    }
+
+    #[test]
+    fn test_edge_kinds_have_their_own_physics() {
+        let mut graph: StableGraph<(), Option<usize>, Undirected, u32> = StableGraph::default();
+        let nodes: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        graph.add_edge(nodes[0], nodes[1], Some(0));
+        graph.add_edge(nodes[2], nodes[3], None);
+        let simulator = SimulatorBuilder::new()
+            .repel(false)
+            .gravity_force(0.0)
+            .freeze_threshold(-1.0)
+            .spring_stiffness(10.0)
+            .spring_neutral_length(2.0)
+            .edge_kind(EdgeKind::new(40.0, 6.0).with_damping(1.0))
+            .initial_layout(InitialLayout::FromPositions(vec![
+                Vec2::ZERO,
+                Vec2::X,
+                Vec2::new(0.0, 10.0),
+                Vec2::new(1.0, 10.0),
+            ]))
+            .build_with_edge_kinds(graph, |kind| *kind);
+
+        {
+            let springs = simulator.springs.read().unwrap();
+            assert_eq!(springs[0].kind, Some(0));
+            assert_eq!(springs[0].spring_stiffness, 40.0);
+            assert_eq!(springs[0].damping, 1.0);
+            assert_eq!(springs[1].kind, None);
+            assert_eq!(springs[1].spring_stiffness, 10.0);
+        }
+
+        simulator.step_n(5000);
+        let positions = simulator.positions();
+        assert!((positions[0].distance(positions[1]) - 6.0).abs() < 1e-2);
+        assert!((positions[2].distance(positions[3]) - 2.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_mass_modes() {
+        // 0 -> 1, 0 -> 2
+        let mut graph: StableGraph<(), (), Directed, u32> = StableGraph::default();
+        let nodes: Vec<_> = (0..3).map(|_| graph.add_node(())).collect();
+        graph.add_edge(nodes[0], nodes[1], ());
+        graph.add_edge(nodes[0], nodes[2], ());
+        let masses = |mass_mode| -> Vec<f32> {
+            let simulator = SimulatorBuilder::new()
+                .mass_mode(mass_mode)
+                .build(graph.clone());
+            simulator.insert_edge(1, 2, None);
+            let rigid_bodies = simulator.rigid_bodies.read().unwrap();
+            rigid_bodies.iter().map(|rb| rb.mass).collect()
+        };
+
+        // Including the edge 1 -> 2 inserted at runtime
+        assert_eq!(masses(MassMode::Uniform), vec![1.0, 1.0, 1.0]);
+        assert_eq!(masses(MassMode::Degree), vec![3.0, 3.0, 3.0]);
+        assert_eq!(masses(MassMode::InDegree), vec![1.0, 2.0, 3.0]);
+        assert_eq!(masses(MassMode::OutDegree), vec![3.0, 2.0, 1.0]);
+        assert_eq!(
+            masses(MassMode::Custom(|in_degree, out_degree| {
+                (10 * in_degree + out_degree) as f32
+            })),
+            vec![2.0, 10.0, 10.0]
+        );
+    }
+
+    #[test]
+    fn test_stop_when_converged() {
+        let simulator = SimulatorBuilder::new()
+            .stop_when_converged(0.5)
+            .build(path(4));
+        let converged = simulator.subscribe_converged();
+        simulator.resume();
+
+        let mut steps = 0;
+        while simulator.is_running() && steps < 10_000 {
+            simulator.simulation_step();
+            steps += 1;
+        }
+
+        assert!(!simulator.is_running());
+        assert!(simulator.average_node_velocity() < 0.5);
+        assert!(converged.try_recv().is_ok());
+
+        // Without an epsilon the simulation keeps running
+        let simulator = SimulatorBuilder::new().build(path(4));
+        simulator.resume();
+        simulator.step_n(steps);
+        assert!(simulator.is_running());
+    }
+
+    #[test]
+    fn test_into_positioned_graph() {
+        let mut graph: StableGraph<&str, u8, Directed, u32> = StableGraph::default();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        graph.add_edge(a, c, 7);
+        graph.remove_node(b);
+        let positions = vec![Vec2::new(1.0, 2.0), Vec2::new(3.0, 4.0)];
+        let simulator = SimulatorBuilder::new()
+            .initial_layout(InitialLayout::FromPositions(positions.clone()))
+            .build(graph.clone());
+
+        let positioned = simulator.into_positioned_graph(&graph);
+
+        assert_eq!(positioned.node_count(), 2);
+        assert_eq!(positioned[a], ("a", positions[0]));
+        assert_eq!(positioned[c], ("c", positions[1]));
+        let edge = positioned.find_edge(a, c).unwrap();
+        assert_eq!(positioned[edge], 7);
+    }
+
+    #[test]
+    fn test_warm_restart_releases_frozen_nodes() {
+        let frozen = |simulator: &Simulator| {
+            let rigid_bodies = simulator.rigid_bodies.read().unwrap();
+            rigid_bodies.iter().filter(|rb| rb.frozen).count()
+        };
+        let simulator = SimulatorBuilder::new()
+            .freeze_threshold(f32::MAX)
+            .warm_restart(5)
+            .build(path(3));
+        simulator.step_n(1);
+        assert_eq!(frozen(&simulator), 3);
+
+        simulator.set_parameters(simulator.parameters());
+        assert_eq!(frozen(&simulator), 0);
+        // Freezing is suspended for the warm restart steps
+        simulator.step_n(5);
+        assert_eq!(frozen(&simulator), 0);
+        simulator.step_n(1);
+        assert_eq!(frozen(&simulator), 3);
+
+        // Without warm restart frozen nodes stay frozen
+        let simulator = SimulatorBuilder::new()
+            .freeze_threshold(f32::MAX)
+            .warm_restart(0)
+            .build(path(3));
+        simulator.step_n(1);
+        simulator.set_parameters(simulator.parameters());
+        assert_eq!(frozen(&simulator), 3);
+    }
+
+    #[test]
+    fn test_spring_damping_resists_stretching_only() {
+        let mut spring = spring(0, 1, 1.0, 2.0, None);
+        let n1 = RigidBody2D::new(Vec2::ZERO, 1.0);
+        let mut n2 = RigidBody2D::new(Vec2::new(2.0, 0.0), 1.0);
+        n2.velocity = Vec2::new(1.0, 0.0);
+
+        // At rest length only the damping acts, against the stretching
+        assert_eq!(
+            Simulator::compute_spring_force(&spring, &n1, &n2),
+            Vec2::ZERO
+        );
+        spring.damping = 3.0;
+        let force = Simulator::compute_spring_force(&spring, &n1, &n2);
+        assert_eq!(force, Vec2::new(-3.0, 0.0));
+        assert!(force.dot(n2.velocity - n1.velocity) < 0.0);
+
+        // Moving sideways rotates the spring without stretching it
+        n2.velocity = Vec2::new(0.0, 1.0);
+        assert_eq!(
+            Simulator::compute_spring_force(&spring, &n1, &n2),
+            Vec2::ZERO
+        );
+    }
+
+    #[test]
+    fn test_softening_bounds_close_repulsion() {
+        let n1 = RigidBody2D::new(Vec2::ZERO, 1.0);
+        let close = RigidBody2D::new(Vec2::new(1e-3, 0.0), 1.0);
+        let far = RigidBody2D::new(Vec2::new(100.0, 0.0), 1.0);
+
+        // Without softening close nodes hit the force clamp
+        assert_eq!(Simulator::repel_force(1.0, 0.0, &n1, &close).x, -100000.0);
+        // With softening the force at distance 0 approaches `1 / softening²`
+        let softened = Simulator::repel_force(1.0, 1.0, &n1, &close);
+        assert!((softened.x + 1.0).abs() < 1e-3);
+        // Far away the softening barely matters
+        let unsoftened = Simulator::repel_force(1.0, 0.0, &n1, &far);
+        let softened = Simulator::repel_force(1.0, 1.0, &n1, &far);
+        assert!((softened.x / unsoftened.x - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_adaptive_delta_time_bounds() {
+        let delta_time = 0.005;
+
+        // Strong forces shrink the step down to its lower bound
+        let simulator = SimulatorBuilder::new()
+            .delta_time(delta_time)
+            .repel_force(1e6)
+            .adaptive_delta_time(1e-9)
+            .initial_layout(InitialLayout::FromPositions(vec![
+                Vec2::ZERO,
+                Vec2::new(1e-3, 0.0),
+            ]))
+            .build(path(2));
+        simulator.step_n(1);
+        assert_eq!(
+            simulator.metrics().delta_time,
+            delta_time * MIN_DELTA_TIME_SCALE
+        );
+
+        // Without forces it grows up to its upper bound
+        let simulator = SimulatorBuilder::new()
+            .delta_time(delta_time)
+            .repel(false)
+            .gravity_force(0.0)
+            .adaptive_delta_time(1.0)
+            .build(path(1));
+        simulator.step_n(100);
+        assert_eq!(
+            simulator.metrics().delta_time,
+            delta_time * MAX_DELTA_TIME_SCALE
+        );
+
+        // In between no node moves further than `max_displacement` per step
+        let simulator = SimulatorBuilder::new()
+            .freeze_threshold(-1.0)
+            .adaptive_delta_time(0.5)
+            .seed(1)
+            .build(path(10));
+        for _ in 0..50 {
+            let before = simulator.positions();
+            simulator.step_n(1);
+            let moved = before
+                .iter()
+                .zip(simulator.positions())
+                .map(|(a, b)| a.distance(b))
+                .fold(0.0, f32::max);
+            assert!(moved <= 0.5 + 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_recenter() {
+        let drifting = |pin_first: bool| {
+            let simulator = SimulatorBuilder::new()
+                .repel(false)
+                .gravity(false)
+                .spring(false)
+                .damping(1.0)
+                .freeze_threshold(-1.0)
+                .recenter(true)
+                .initial_layout(InitialLayout::FromPositions(vec![
+                    Vec2::new(10.0, 0.0),
+                    Vec2::new(12.0, 4.0),
+                ]))
+                .build(path(2));
+            for rb in simulator.rigid_bodies.write().unwrap().iter_mut() {
+                rb.velocity = Vec2::new(3.0, -1.0);
+            }
+            simulator.rigid_bodies.write().unwrap()[0].fixed = pin_first;
+            simulator.step_n(1);
+            simulator
+        };
+
+        let simulator = drifting(false);
+        let rigid_bodies = simulator.rigid_bodies.read().unwrap();
+        let center: Vec2 = rigid_bodies.iter().map(|rb| rb.position).sum::<Vec2>() / 2.0;
+        assert!(center.length() < 1e-5);
+        assert!(rigid_bodies.iter().all(|rb| rb.velocity.length() < 1e-5));
+        // The shape is kept
+        let distance = rigid_bodies[0].position.distance(rigid_bodies[1].position);
+        assert!((distance - Vec2::new(2.0, 4.0).length()).abs() < 1e-5);
+
+        // Pinned nodes anchor the layout, so the other node keeps drifting
+        let simulator = drifting(true);
+        let delta_time = simulator.parameters().delta_time;
+        let positions = simulator.positions();
+        assert_eq!(positions[0], Vec2::new(10.0, 0.0));
+        assert_eq!(
+            positions[1],
+            Vec2::new(12.0, 4.0) + Vec2::new(3.0, -1.0) * delta_time
+        );
+    }
+
+    #[test]
+    fn test_angular_damping_stops_rotation() {
+        let ring: Vec<Vec2> = (0..8)
+            .map(|i| Vec2::from_angle(i as f32 / 8.0 * std::f32::consts::TAU) * 10.0)
+            .collect();
+        let simulator = SimulatorBuilder::new()
+            .repel(false)
+            .gravity(false)
+            .spring(false)
+            .damping(1.0)
+            .freeze_threshold(-1.0)
+            .angular_damping(1.0)
+            .mass_mode(MassMode::Uniform)
+            .initial_layout(InitialLayout::FromPositions(ring.clone()))
+            .build(path(8));
+        let translation = Vec2::new(1.0, 2.0);
+        for rb in simulator.rigid_bodies.write().unwrap().iter_mut() {
+            rb.velocity = rb.position.perp() * 0.5 + translation;
+        }
+
+        simulator.damp_rotation();
+
+        // Only the rigid rotation is removed, the ring keeps moving as a whole
+        let rigid_bodies = simulator.rigid_bodies.read().unwrap();
+        let angular_momentum: f32 = rigid_bodies
+            .iter()
+            .map(|rb| rb.mass * rb.position.perp_dot(rb.velocity))
+            .sum();
+        assert!(angular_momentum.abs() < 1e-3);
+        for (rb, position) in rigid_bodies.iter().zip(&ring) {
+            assert_eq!(rb.position, *position);
+            assert!(rb.velocity.distance(translation) < 1e-5);
         }
+        drop(rigid_bodies);
+
+        // Stepping keeps the shape of the ring
+        simulator.step_n(10);
+        let positions = simulator.positions();
+        for i in 0..8 {
+            let j = (i + 1) % 8;
+            let before = ring[i].distance(ring[j]);
+            assert!((positions[i].distance(positions[j]) - before).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_annealing_cools_down_to_floor() {
+        let simulator = SimulatorBuilder::new()
+            .freeze_threshold(-1.0)
+            .annealing(2.0, 0.5)
+            .seed(1)
+            .build(path(10));
+        let temperature = |simulator: &Simulator| *simulator.temperature.read().unwrap();
+
+        for _ in 0..30 {
+            let cap = temperature(&simulator);
+            let before = simulator.positions();
+            simulator.step_n(1);
+            let moved = before
+                .iter()
+                .zip(simulator.positions())
+                .map(|(a, b)| a.distance(b))
+                .fold(0.0, f32::max);
+            assert!(moved <= cap + 1e-4);
+        }
+        assert_eq!(temperature(&simulator), 2.0 * MIN_TEMPERATURE_SCALE);
+
+        // Parameter changes heat the layout up again
+        simulator.set_parameters(simulator.parameters());
+        assert_eq!(temperature(&simulator), 2.0);
+    }
+
+    #[test]
+    fn test_wait_until_running() {
+        let simulator = SimulatorBuilder::new().build(path(2));
+        assert!(!simulator.wait_until_running(Duration::from_millis(10)));
+
+        let resumer = simulator.clone();
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(10));
+            resumer.resume();
+        });
+        assert!(simulator.wait_until_running(Duration::from_secs(10)));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_closest_node_ignores_stale_quadtree() {
+        let simulator = SimulatorBuilder::new()
+            .freeze_threshold(-1.0)
+            .initial_layout(InitialLayout::FromPositions(vec![
+                Vec2::ZERO,
+                Vec2::new(10.0, 0.0),
+            ]))
+            .build(path(2));
+        simulator.resume();
+        simulator.step_n(1);
+
+        // Steps without repulsion and gravity don't rebuild the tree
+        simulator.set_parameters(SimulationParameters {
+            repel: false,
+            gravity: false,
+            ..simulator.parameters()
+        });
+        simulator.step_n(1);
+        simulator.rigid_bodies.write().unwrap()[0].position = Vec2::new(100.0, 0.0);
+
+        let closest = simulator.find_closest_node_index(Vec3::new(100.0, 0.0, 0.0));
+        assert_eq!(closest, Some(0));
+    }
+
+    #[test]
+    fn test_settings_keep_builder_and_parameters() {
+        let simulator = SimulatorBuilder::new()
+            .repel_force(42.0)
+            .quadtree_accuracy(0.5)
+            .mass_mode(MassMode::Uniform)
+            .edge_kind(EdgeKind::new(3.0, 4.0))
+            .build(path(3));
+        simulator.set_parameters(SimulationParameters {
+            damping: 0.5,
+            ..simulator.parameters()
+        });
+        simulator.set_spring_stiffness(7.0);
+
+        let other = simulator.settings().build(path(5));
+        let parameters = other.parameters();
+        assert_eq!(parameters.repel_force_const, 42.0);
+        assert_eq!(parameters.quadtree_theta, 0.5);
+        assert_eq!(parameters.damping, 0.5);
+        assert_eq!(other.spring_stiffness(), 7.0);
+        assert_eq!(other.edge_kinds().len(), 1);
+        let rigid_bodies = other.rigid_bodies.read().unwrap();
+        assert_eq!(rigid_bodies.len(), 5);
+        assert!(rigid_bodies.iter().all(|rb| rb.mass == 1.0));
     }
 }
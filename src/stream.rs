@@ -0,0 +1,72 @@
+//! Live graphs whose nodes and edges arrive while they are shown, e.g. from a socket or a log
+//! tail, see `Renderer::set_stream`.
+//!
+//! # Example
+//! ```no_run
+//!use std::{sync::mpsc, thread, time::Duration};
+//!
+//!use grapher::prelude::*;
+//!use petgraph::{prelude::StableGraph, Directed};
+//!
+//!let (sender, receiver) = mpsc::channel();
+//!thread::spawn(move || {
+//!    for i in 0..100 {
+//!        let update = GraphUpdate {
+//!            add_nodes: 1,
+//!            add_edges: vec![(i / 2, i + 1)],
+//!            ..Default::default()
+//!        };
+//!        if sender.send(update).is_err() {
+//!            return;
+//!        }
+//!        thread::sleep(Duration::from_millis(100));
+//!    }
+//!});
+//!
+//!let mut graph: StableGraph<(), (), Directed> = StableGraph::new();
+//!graph.add_node(());
+//!let mut renderer = Renderer::new(SimulatorBuilder::new().build(graph));
+//!renderer.set_stream(receiver);
+//!renderer.create_window();
+//! ```
+
+use std::sync::mpsc::Receiver;
+
+use crate::simulator::GraphUpdate;
+
+/// Source of changes to a shown graph, polled once per frame
+pub trait GraphStream {
+    /// Updates that arrived since the last poll, must not block
+    fn poll_updates(&mut self) -> Vec<GraphUpdate>;
+}
+
+/// Updates sent from another thread
+impl GraphStream for Receiver<GraphUpdate> {
+    fn poll_updates(&mut self) -> Vec<GraphUpdate> {
+        self.try_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_receiver_stream() {
+        let (sender, mut receiver) = mpsc::channel();
+        assert!(receiver.poll_updates().is_empty());
+
+        let update = GraphUpdate {
+            add_nodes: 2,
+            ..Default::default()
+        };
+        sender.send(update.clone()).unwrap();
+        sender.send(GraphUpdate::default()).unwrap();
+        assert_eq!(
+            receiver.poll_updates(),
+            vec![update, GraphUpdate::default()]
+        );
+        assert!(receiver.poll_updates().is_empty());
+    }
+}
@@ -0,0 +1,229 @@
+//! Finds simulation parameters that lay out a graph well by trying combinations of them.
+//!
+//! # Example
+//! ```no_run
+//!use grapher::prelude::*;
+//!use grapher::tune::{self, LayoutMetric, Parameter, ParameterGrid};
+//!use petgraph::{prelude::StableGraph, Directed};
+//!
+//!let graph: StableGraph<(), (), Directed> = StableGraph::new();
+//!let grid = ParameterGrid::new(SimulatorBuilder::new().seed(1))
+//!    .vary(Parameter::RepelForce, vec![50.0, 100.0, 200.0])
+//!    .vary(Parameter::SpringStiffness, vec![50.0, 100.0]);
+//!let results = tune::sweep(&graph, &grid, LayoutMetric::Stress);
+//!
+//!println!("Best parameters {:?}", results[0].parameters);
+//!let simulator = results[0].builder.clone().build(graph);
+//! ```
+
+use petgraph::{prelude::StableGraph, EdgeType};
+use rayon::prelude::*;
+
+use crate::{
+    analysis,
+    simulator::{Simulator, SimulatorBuilder},
+};
+
+/// Parameter of `SimulatorBuilder` that is varied by a `ParameterGrid`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Parameter {
+    SpringStiffness,
+    SpringNeutralLength,
+    RepelForce,
+    GravityForce,
+    Damping,
+    DeltaTime,
+    QuadtreeAccuracy,
+}
+
+impl Parameter {
+    fn apply(self, builder: SimulatorBuilder, value: f32) -> SimulatorBuilder {
+        match self {
+            Parameter::SpringStiffness => builder.spring_stiffness(value),
+            Parameter::SpringNeutralLength => builder.spring_neutral_length(value),
+            Parameter::RepelForce => builder.repel_force(value),
+            Parameter::GravityForce => builder.gravity_force(value),
+            Parameter::Damping => builder.damping(value),
+            Parameter::DeltaTime => builder.delta_time(value),
+            Parameter::QuadtreeAccuracy => builder.quadtree_accuracy(value),
+        }
+    }
+}
+
+/// Layout quality used to rank the combinations of a sweep, lower is better
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LayoutMetric {
+    /// See `analysis::stress`
+    Stress,
+    /// See `analysis::edge_length_variation`
+    EdgeLengthVariation,
+    /// See `analysis::edge_crossings`
+    EdgeCrossings,
+}
+
+impl LayoutMetric {
+    /// Score of the current layout of `simulator`
+    pub fn score(&self, simulator: &Simulator) -> f32 {
+        match self {
+            LayoutMetric::Stress => analysis::stress(simulator),
+            LayoutMetric::EdgeLengthVariation => analysis::edge_length_variation(simulator),
+            LayoutMetric::EdgeCrossings => analysis::edge_crossings(simulator) as f32,
+        }
+    }
+}
+
+/// Combinations of parameter values that are tried by `sweep`
+#[derive(Clone)]
+pub struct ParameterGrid {
+    base: SimulatorBuilder,
+    steps: usize,
+    axes: Vec<(Parameter, Vec<f32>)>,
+}
+
+impl ParameterGrid {
+    /// Grid around `base`, which sets everything that isn't varied. Give it a `seed` so every
+    /// combination starts from the same positions.
+    pub fn new(base: SimulatorBuilder) -> Self {
+        Self {
+            base,
+            steps: 1000,
+            axes: vec![],
+        }
+    }
+
+    /// Simulation steps run for every combination before it is scored
+    ///
+    /// Default: `1000`
+    pub fn steps(mut self, steps: usize) -> Self {
+        self.steps = steps;
+        self
+    }
+
+    /// Tries every value of `values` for `parameter`, combined with every value of the other
+    /// varied parameters. Varying a parameter again replaces its values.
+    pub fn vary(mut self, parameter: Parameter, values: Vec<f32>) -> Self {
+        self.axes.retain(|(p, _)| *p != parameter);
+        self.axes.push((parameter, values));
+        self
+    }
+
+    /// Every combination of the varied parameter values, one empty combination if nothing is
+    /// varied
+    pub fn combinations(&self) -> Vec<Vec<(Parameter, f32)>> {
+        self.axes
+            .iter()
+            .fold(vec![vec![]], |combinations, (parameter, values)| {
+                combinations
+                    .iter()
+                    .flat_map(|combination| {
+                        values.iter().map(move |value| {
+                            let mut combination = combination.clone();
+                            combination.push((*parameter, *value));
+                            combination
+                        })
+                    })
+                    .collect()
+            })
+    }
+
+    /// The base builder with `combination` applied
+    pub fn builder(&self, combination: &[(Parameter, f32)]) -> SimulatorBuilder {
+        combination
+            .iter()
+            .fold(self.base.clone(), |builder, (parameter, value)| {
+                parameter.apply(builder, *value)
+            })
+    }
+}
+
+/// Scored combination of a sweep
+#[derive(Clone)]
+pub struct SweepResult {
+    pub parameters: Vec<(Parameter, f32)>,
+    /// Score of the layout after `ParameterGrid::steps`, `f32::INFINITY` if the layout diverged
+    pub score: f32,
+    /// Builder with the parameters applied, to build the final layout with
+    pub builder: SimulatorBuilder,
+}
+
+/// Lays out `graph` with every combination of `grid` in parallel without a window and scores the
+/// layouts with `metric`.
+///
+/// Returns all combinations, the best first. Every layout runs on a single thread, so the
+/// combinations are spread over the cores.
+pub fn sweep<T, E, D>(
+    graph: &StableGraph<T, E, D, u32>,
+    grid: &ParameterGrid,
+    metric: LayoutMetric,
+) -> Vec<SweepResult>
+where
+    T: Clone + Sync,
+    E: Clone + Sync,
+    D: EdgeType + Sync,
+{
+    let mut results: Vec<SweepResult> = grid
+        .combinations()
+        .into_par_iter()
+        .map(|parameters| {
+            let builder = grid.builder(&parameters);
+            let simulator = builder.clone().max_threads(1).build(graph.clone());
+            simulator.step_n(grid.steps);
+
+            let score = metric.score(&simulator);
+            SweepResult {
+                parameters,
+                score: if score.is_finite() {
+                    score
+                } else {
+                    f32::INFINITY
+                },
+                builder,
+            }
+        })
+        .collect();
+    results.sort_by(|a, b| a.score.total_cmp(&b.score));
+    results
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use petgraph::Undirected;
+
+    #[test]
+    fn test_combinations() {
+        let grid = ParameterGrid::new(SimulatorBuilder::new())
+            .vary(Parameter::Damping, vec![0.5, 0.9])
+            .vary(Parameter::RepelForce, vec![10.0, 20.0, 30.0]);
+        let combinations = grid.combinations();
+        assert_eq!(combinations.len(), 6);
+        assert_eq!(
+            combinations[1],
+            vec![(Parameter::Damping, 0.5), (Parameter::RepelForce, 20.0)]
+        );
+
+        let grid = grid.vary(Parameter::Damping, vec![0.7]);
+        assert_eq!(grid.combinations().len(), 3);
+        assert_eq!(
+            ParameterGrid::new(SimulatorBuilder::new()).combinations(),
+            vec![vec![]]
+        );
+    }
+
+    #[test]
+    fn test_sweep_sorts_best_first() {
+        let mut graph: StableGraph<(), (), Undirected, u32> = StableGraph::default();
+        let nodes: Vec<_> = (0..6).map(|_| graph.add_node(())).collect();
+        for pair in nodes.windows(2) {
+            graph.add_edge(pair[0], pair[1], ());
+        }
+        let grid = ParameterGrid::new(SimulatorBuilder::new().seed(3))
+            .steps(50)
+            .vary(Parameter::SpringStiffness, vec![0.0, 100.0]);
+
+        let results = sweep(&graph, &grid, LayoutMetric::Stress);
+        assert_eq!(results.len(), 2);
+        assert!(results[0].score <= results[1].score);
+        assert!(results.iter().all(|r| (0.0..=1.0).contains(&r.score)));
+    }
+}